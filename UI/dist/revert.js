@@ -1,104 +0,0 @@
-"use strict";
-var __awaiter = (this && this.__awaiter) || function (thisArg, _arguments, P, generator) {
-    function adopt(value) { return value instanceof P ? value : new P(function (resolve) { resolve(value); }); }
-    return new (P || (P = Promise))(function (resolve, reject) {
-        function fulfilled(value) { try { step(generator.next(value)); } catch (e) { reject(e); } }
-        function rejected(value) { try { step(generator["throw"](value)); } catch (e) { reject(e); } }
-        function step(result) { result.done ? resolve(result.value) : adopt(result.value).then(fulfilled, rejected); }
-        step((generator = generator.apply(thisArg, _arguments || [])).next());
-    });
-};
-var __generator = (this && this.__generator) || function (thisArg, body) {
-    var _ = { label: 0, sent: function() { if (t[0] & 1) throw t[1]; return t[1]; }, trys: [], ops: [] }, f, y, t, g;
-    return g = { next: verb(0), "throw": verb(1), "return": verb(2) }, typeof Symbol === "function" && (g[Symbol.iterator] = function() { return this; }), g;
-    function verb(n) { return function (v) { return step([n, v]); }; }
-    function step(op) {
-        if (f) throw new TypeError("Generator is already executing.");
-        while (_) try {
-            if (f = 1, y && (t = op[0] & 2 ? y["return"] : op[0] ? y["throw"] || ((t = y["return"]) && t.call(y), 0) : y.next) && !(t = t.call(y, op[1])).done) return t;
-            if (y = 0, t) op = [op[0] & 2, t.value];
-            switch (op[0]) {
-                case 0: case 1: t = op; break;
-                case 4: _.label++; return { value: op[1], done: false };
-                case 5: _.label++; y = op[1]; op = [0]; continue;
-                case 7: op = _.ops.pop(); _.trys.pop(); continue;
-                default:
-                    if (!(t = _.trys, t = t.length > 0 && t[t.length - 1]) && (op[0] === 6 || op[0] === 2)) { _ = 0; continue; }
-                    if (op[0] === 3 && (!t || (op[1] > t[0] && op[1] < t[3]))) { _.label = op[1]; break; }
-                    if (op[0] === 6 && _.label < t[1]) { _.label = t[1]; t = op; break; }
-                    if (t && _.label < t[2]) { _.label = t[2]; _.ops.push(op); break; }
-                    if (t[2]) _.ops.pop();
-                    _.trys.pop(); continue;
-            }
-            op = body.call(thisArg, _);
-        } catch (e) { op = [6, e]; y = 0; } finally { f = t = 0; }
-        if (op[0] & 5) throw op[1]; return { value: op[0] ? op[1] : void 0, done: true };
-    }
-};
-var __importDefault = (this && this.__importDefault) || function (mod) {
-    return (mod && mod.__esModule) ? mod : { "default": mod };
-};
-Object.defineProperty(exports, "__esModule", { value: true });
-exports.revert = void 0;
-var web3_js_1 = require("@solana/web3.js");
-var bn_js_1 = __importDefault(require("bn.js"));
-var layout_1 = require("./layout");
-var connection = new web3_js_1.Connection("https://api.devnet.solana.com", 'singleGossip');
-var revert = function (escrow_acc) { return __awaiter(void 0, void 0, void 0, function () {
-    var escrow_acc_key, encodedEscrowState, decodedEscrowState, initialiser_main_acc_key, initialiser_game_acc_key, taker_game_acc_key, revertIX, tx, encodediGameAccState, decodediGameAccState, encodedtGameAccState, decodedtGameAccState, initialiser_asset, initialiser_rented_asset, taker_asset, taker_rented_asset, i;
-    return __generator(this, function (_a) {
-        switch (_a.label) {
-            case 0:
-                escrow_acc_key = new web3_js_1.PublicKey(escrow_acc);
-                return [4 /*yield*/, connection.getAccountInfo(escrow_acc_key, 'singleGossip')];
-            case 1:
-                encodedEscrowState = (_a.sent()).data;
-                decodedEscrowState = layout_1.EscrowAcc_Data_Layout.decode(encodedEscrowState);
-                initialiser_main_acc_key = new web3_js_1.PublicKey(decodedEscrowState.initialiser_main_acc);
-                initialiser_game_acc_key = new web3_js_1.PublicKey(decodedEscrowState.initialiser_game_acc);
-                taker_game_acc_key = new web3_js_1.PublicKey(decodedEscrowState.taker_game_acc);
-                revertIX = new web3_js_1.TransactionInstruction({
-                    programId: layout_1.program_id,
-                    keys: [
-                        { pubkey: initialiser_main_acc_key, isSigner: false, isWritable: true },
-                        { pubkey: escrow_acc_key, isSigner: false, isWritable: true },
-                        { pubkey: initialiser_game_acc_key, isSigner: false, isWritable: true },
-                        { pubkey: taker_game_acc_key, isSigner: false, isWritable: true },
-                    ],
-                    data: Buffer.from(Uint8Array.of(2))
-                });
-                tx = new web3_js_1.Transaction().add(revertIX);
-                return [4 /*yield*/, connection.sendTransaction(tx, [], { skipPreflight: false, preflightCommitment: 'singleGossip' })];
-            case 2:
-                _a.sent();
-                return [4 /*yield*/, new Promise(function (resolve) { return setTimeout(resolve, 1000); })];
-            case 3:
-                _a.sent();
-                return [4 /*yield*/, connection.getAccountInfo(initialiser_game_acc_key, 'singleGossip')];
-            case 4:
-                encodediGameAccState = (_a.sent()).data;
-                decodediGameAccState = layout_1.GameAcc_Data_Layout.decode(encodediGameAccState);
-                return [4 /*yield*/, connection.getAccountInfo(taker_game_acc_key, 'singleGossip')];
-            case 5:
-                encodedtGameAccState = (_a.sent()).data;
-                decodedtGameAccState = layout_1.GameAcc_Data_Layout.decode(encodedtGameAccState);
-                initialiser_asset = [];
-                initialiser_rented_asset = [];
-                taker_asset = [];
-                taker_rented_asset = [];
-                for (i = 0; i < layout_1.Asset_Space; i++) {
-                    initialiser_rented_asset[i] = new bn_js_1.default(decodediGameAccState.rented.slice(i * 8, (i + 1) * 8)).toNumber();
-                    initialiser_asset[i] = new bn_js_1.default(decodediGameAccState.owned.slice(i * 8, (i + 1) * 8)).toNumber();
-                    taker_rented_asset[i] = new bn_js_1.default(decodedtGameAccState.rented.slice(i * 8, (i + 1) * 8)).toNumber();
-                    taker_asset[i] = new bn_js_1.default(decodedtGameAccState.owned.slice(i * 8, (i + 1) * 8)).toNumber();
-                }
-                return [2 /*return*/, {
-                        initialiser_asset: initialiser_asset.toString(),
-                        initialiser_rented_asset: initialiser_rented_asset.toString(),
-                        taker_asset: taker_asset.toString(),
-                        taker_rented_asset: taker_rented_asset.toString()
-                    }];
-        }
-    });
-}); };
-exports.revert = revert;
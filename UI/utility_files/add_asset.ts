@@ -1,6 +1,6 @@
-import { Connection, Transaction,PublicKey, TransactionInstruction } from "@solana/web3.js";
-import { GameAcc_Data_Layout,GameAcc_Layout,Asset_Space, program_id} from "./layout";
-import BN from "bn.js";
+import { Connection, Transaction,PublicKey } from "@solana/web3.js";
+import * as game_nest from "./game_nest";
+import { program_id } from "./program_id";
 
 const connection = new Connection("https://api.devnet.solana.com", 'singleGossip');
 
@@ -10,13 +10,7 @@ export const add_asset = async(
 ) =>{
     const game_acc_key = new PublicKey(game_acc);
 
-    const add_assetIX = new TransactionInstruction({
-        programId : program_id,
-        keys : [
-            {pubkey: game_acc_key, isSigner: false, isWritable:true},
-        ],
-        data : Buffer.from(Uint8Array.of(4,...new BN(asset_id).toArray("le",8)))
-    })
+    const add_assetIX = game_nest.add_asset(program_id, {game_acc : game_acc_key}, {asset_id});
 
     const tx = new Transaction().add(add_assetIX);
 
@@ -25,13 +19,9 @@ export const add_asset = async(
     await new Promise((resolve) => setTimeout(resolve, 1000));
 
     const encodedGameAccState = (await connection.getAccountInfo(game_acc_key, 'singleGossip'))!.data;
-    const decodedGameAccState = GameAcc_Data_Layout.decode(encodedGameAccState) as GameAcc_Layout;
+    const decodedGameAccState = game_nest.decode_game_acc(encodedGameAccState);
 
-    var initialiser_asset = [];
-
-    for(var i=0; i < Asset_Space;i++){
-        initialiser_asset[i] = new BN(decodedGameAccState.owned.slice(i*8,(i+1)*8)).toNumber();
-    }
+    const initialiser_asset = decodedGameAccState.owned.map((asset) => asset.toNumber());
 
     return initialiser_asset.toString();
 
@@ -0,0 +1,1347 @@
+// Generated by gamenest-idl from idl/game_nest.json, do not edit.
+// Run `cargo run -p gamenest-idl` after changing the program's instructions, accounts or errors.
+
+import { AccountMeta, PublicKey, TransactionInstruction } from "@solana/web3.js";
+import BN from "bn.js";
+
+export type Numberish = BN | number;
+
+class Reader {
+    private offset: number;
+
+    constructor(private readonly data: Buffer, offset: number) {
+        this.offset = offset;
+    }
+
+    u8(): number {
+        const value = this.data.readUInt8(this.offset);
+        this.offset += 1;
+        return value;
+    }
+
+    bool(): boolean {
+        return this.u8() !== 0;
+    }
+
+    u64(): BN {
+        const value = new BN(this.data.slice(this.offset, this.offset + 8), "le");
+        this.offset += 8;
+        return value;
+    }
+
+    i64(): BN {
+        return this.u64().fromTwos(64);
+    }
+
+    publicKey(): PublicKey {
+        const value = new PublicKey(this.data.slice(this.offset, this.offset + 32));
+        this.offset += 32;
+        return value;
+    }
+
+    array<T>(len: number, item: () => T): T[] {
+        const items: T[] = [];
+        for (let i = 0; i < len; i++) {
+            items.push(item());
+        }
+        return items;
+    }
+}
+
+class Writer {
+    private readonly bytes: number[] = [];
+
+    u8(value: number): void {
+        if (value < 0 || value > 0xff) {
+            throw new Error(`${value} doesn't fit in a u8`);
+        }
+        this.bytes.push(value);
+    }
+
+    bool(value: boolean): void {
+        this.u8(value ? 1 : 0);
+    }
+
+    u64(value: Numberish): void {
+        const bn = new BN(value);
+        if (bn.isNeg()) {
+            throw new Error(`${bn.toString()} doesn't fit in a u64`);
+        }
+        this.push(bn.toArray("le", 8));
+    }
+
+    i64(value: Numberish): void {
+        this.push(new BN(value).toTwos(64).toArray("le", 8));
+    }
+
+    publicKey(value: PublicKey): void {
+        this.push(value.toBytes());
+    }
+
+    array<T>(items: T[], len: number, item: (value: T) => void): void {
+        if (items.length !== len) {
+            throw new Error(`expected ${len} items, got ${items.length}`);
+        }
+        items.forEach((value) => item(value));
+    }
+
+    toBuffer(): Buffer {
+        return Buffer.from(this.bytes);
+    }
+
+    private push(bytes: ArrayLike<number>): void {
+        for (let i = 0; i < bytes.length; i++) {
+            this.bytes.push(bytes[i]);
+        }
+    }
+}
+
+export const ACCOUNT_HEADER_LEN = 2;
+export const ACCOUNT_VERSION = 5;
+export const LEGACY_HEADER_LEN = 1;
+export const ALLOWED_TAKERS_LEN = 3;
+export const DISPUTE_TIMEOUT = 604800;
+export const DISPUTE_FAULT_NONE = 0;
+export const DISPUTE_FAULT_RENTER = 1;
+export const DISPUTE_FAULT_LENDER = 2;
+export const DELIVERY_ATTESTATION_PREFIX = Buffer.from("gamenest:delivered:");
+export const KEEPER_BOUNTY = 10000;
+export const ESCROW_KIND_RENT_REQUEST = 0;
+export const ESCROW_KIND_ENGLISH_AUCTION = 1;
+export const ESCROW_KIND_DUTCH_AUCTION = 2;
+export const MARKET_CAPACITY = 16;
+export const HISTORY_CAPACITY = 16;
+export const HISTORY_SEED = Buffer.from("history");
+export const HISTORY_KIND_RENTAL = 0;
+export const HISTORY_KIND_SALE = 1;
+export const HISTORY_KIND_RETURN = 2;
+export const REPUTATION_SEED = Buffer.from("reputation");
+
+export enum AccType {
+    Escrow = 1,
+    GameAcc = 2,
+    Offer = 3,
+    Market = 4,
+    History = 5,
+    Reputation = 6,
+    Closed = 255,
+}
+
+// checks the account header before the fields after it are read
+function account_reader(data: Buffer, acc_type: AccType, len: number, name: string): Reader {
+    if (data.length > 0 && data[0] === AccType.Closed) {
+        throw new Error(`the ${name} account is closed`);
+    }
+    if (data.length < ACCOUNT_HEADER_LEN || data[0] !== acc_type) {
+        throw new Error(`not a ${name} account`);
+    }
+    if (data[1] !== ACCOUNT_VERSION) {
+        throw new Error(`the ${name} account is outdated, MigrateAccount upgrades it to version ${ACCOUNT_VERSION}`);
+    }
+    if (data.length !== len) {
+        throw new Error(`the ${name} account is ${data.length} bytes instead of ${len}`);
+    }
+    return new Reader(data, ACCOUNT_HEADER_LEN);
+}
+
+export interface MarketEntry {
+    price: BN;
+    asset_id: BN;
+    escrow: PublicKey;
+}
+
+function read_market_entry(reader: Reader): MarketEntry {
+    return {
+        price: reader.u64(),
+        asset_id: reader.u64(),
+        escrow: reader.publicKey(),
+    };
+}
+
+export interface HistoryEntry {
+    kind: number;
+    from_game_acc: PublicKey;
+    to_game_acc: PublicKey;
+    price: BN;
+    start: BN;
+    end: BN;
+}
+
+function read_history_entry(reader: Reader): HistoryEntry {
+    return {
+        kind: reader.u8(),
+        from_game_acc: reader.publicKey(),
+        to_game_acc: reader.publicKey(),
+        price: reader.u64(),
+        start: reader.i64(),
+        end: reader.i64(),
+    };
+}
+
+export const ESCROW_LEN = 436;
+
+export interface Escrow {
+    is_taken: boolean;
+    initialiser_main_acc: PublicKey;
+    initialiser_game_acc: PublicKey;
+    taker_game_acc: PublicKey;
+    esc_taken_time: BN;
+    amount: BN;
+    time: BN;
+    asset_id: BN;
+    allowed_takers: PublicKey[];
+    expires_at: BN;
+    kind: number;
+    taker_main_acc: PublicKey;
+    min_increment: BN;
+    end_time: BN;
+    start_price: BN;
+    end_price: BN;
+    start_time: BN;
+    asset_id_max: BN;
+    market: PublicKey;
+    min_reputation: BN;
+    arbiter: PublicKey;
+    disputed_at: BN;
+    oracle: PublicKey;
+    delivered_at: BN;
+}
+
+export function decode_escrow(data: Buffer): Escrow {
+    const reader = account_reader(data, AccType.Escrow, ESCROW_LEN, "Escrow");
+    return {
+        is_taken: reader.bool(),
+        initialiser_main_acc: reader.publicKey(),
+        initialiser_game_acc: reader.publicKey(),
+        taker_game_acc: reader.publicKey(),
+        esc_taken_time: reader.i64(),
+        amount: reader.u64(),
+        time: reader.u64(),
+        asset_id: reader.u64(),
+        allowed_takers: reader.array(3, () => reader.publicKey()),
+        expires_at: reader.i64(),
+        kind: reader.u8(),
+        taker_main_acc: reader.publicKey(),
+        min_increment: reader.u64(),
+        end_time: reader.i64(),
+        start_price: reader.u64(),
+        end_price: reader.u64(),
+        start_time: reader.i64(),
+        asset_id_max: reader.u64(),
+        market: reader.publicKey(),
+        min_reputation: reader.u64(),
+        arbiter: reader.publicKey(),
+        disputed_at: reader.i64(),
+        oracle: reader.publicKey(),
+        delivered_at: reader.i64(),
+    };
+}
+
+export const GAME_ACC_LEN = 546;
+
+export interface GameAcc {
+    userspace_owner: PublicKey;
+    owned: BN[];
+    rented: BN[];
+    lent: BN[];
+    locked_by: PublicKey;
+}
+
+export function decode_game_acc(data: Buffer): GameAcc {
+    const reader = account_reader(data, AccType.GameAcc, GAME_ACC_LEN, "GameAcc");
+    return {
+        userspace_owner: reader.publicKey(),
+        owned: reader.array(20, () => reader.u64()),
+        rented: reader.array(20, () => reader.u64()),
+        lent: reader.array(20, () => reader.u64()),
+        locked_by: reader.publicKey(),
+    };
+}
+
+export const OFFER_LEN = 122;
+
+export interface Offer {
+    escrow: PublicKey;
+    offerer_main_acc: PublicKey;
+    offerer_game_acc: PublicKey;
+    amount: BN;
+    time: BN;
+    asset_id: BN;
+}
+
+export function decode_offer(data: Buffer): Offer {
+    const reader = account_reader(data, AccType.Offer, OFFER_LEN, "Offer");
+    return {
+        escrow: reader.publicKey(),
+        offerer_main_acc: reader.publicKey(),
+        offerer_game_acc: reader.publicKey(),
+        amount: reader.u64(),
+        time: reader.u64(),
+        asset_id: reader.u64(),
+    };
+}
+
+export const MARKET_LEN = 867;
+
+export interface Market {
+    authority: PublicKey;
+    len: number;
+    entries: MarketEntry[];
+    arbiter: PublicKey;
+    oracle: PublicKey;
+}
+
+export function decode_market(data: Buffer): Market {
+    const reader = account_reader(data, AccType.Market, MARKET_LEN, "Market");
+    return {
+        authority: reader.publicKey(),
+        len: reader.u8(),
+        entries: reader.array(16, () => read_market_entry(reader)),
+        arbiter: reader.publicKey(),
+        oracle: reader.publicKey(),
+    };
+}
+
+export const HISTORY_LEN = 1445;
+
+export interface History {
+    asset_id: BN;
+    bump: number;
+    head: number;
+    len: number;
+    total: BN;
+    entries: HistoryEntry[];
+}
+
+export function decode_history(data: Buffer): History {
+    const reader = account_reader(data, AccType.History, HISTORY_LEN, "History");
+    return {
+        asset_id: reader.u64(),
+        bump: reader.u8(),
+        head: reader.u8(),
+        len: reader.u8(),
+        total: reader.u64(),
+        entries: reader.array(16, () => read_history_entry(reader)),
+    };
+}
+
+export const REPUTATION_LEN = 59;
+
+export interface Reputation {
+    wallet: PublicKey;
+    bump: number;
+    completed: BN;
+    cancelled: BN;
+    disputed: BN;
+}
+
+export function decode_reputation(data: Buffer): Reputation {
+    const reader = account_reader(data, AccType.Reputation, REPUTATION_LEN, "Reputation");
+    return {
+        wallet: reader.publicKey(),
+        bump: reader.u8(),
+        completed: reader.u64(),
+        cancelled: reader.u64(),
+        disputed: reader.u64(),
+    };
+}
+
+export interface InitEscrowAccounts {
+    /** Escrow A/c */
+    escrow_acc: PublicKey;
+    /** initialisers Game Program A/c */
+    initialiser_game_acc: PublicKey;
+    /** initialisers main A/c */
+    initialiser_main_acc: PublicKey;
+    /** optional Market A/c to list the rent request in */
+    market_acc?: PublicKey;
+}
+
+export interface InitEscrowArgs {
+    amount: Numberish;
+    time: Numberish;
+    asset_id: Numberish;
+    allowed_takers: PublicKey[];
+    expires_at: Numberish;
+    asset_id_max: Numberish;
+    min_reputation: Numberish;
+}
+
+/**
+ * Accounts expected for Init Escrow
+ *
+ * the escrow takes the market's arbiter, if it has one the rent stays in the Escrow A/c
+ * till the rental ends and is paid to the taker on Revert so it can be disputed
+ *
+ * if any allowed_takers are given then only those main A/c's can take the escrow
+ *
+ * if asset_id_max is non 0 it is a collection bid that can be taken with any asset
+ * from asset_id to asset_id_max, so a category of assets should be given a range of ids,
+ * asset_id can't be 0 and asset_id_max can't be below it
+ *
+ * only amount, time and asset_id are required, the data can stop after any later field
+ * and the fields left out read as zero
+ */
+export function init_escrow(
+    program_id: PublicKey,
+    accounts: InitEscrowAccounts,
+    args: InitEscrowArgs,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.escrow_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.initialiser_game_acc, isSigner: false, isWritable: false },
+        { pubkey: accounts.initialiser_main_acc, isSigner: false, isWritable: false },
+    ];
+    if (accounts.market_acc) {
+        keys.push({ pubkey: accounts.market_acc, isSigner: false, isWritable: true });
+    }
+
+    const writer = new Writer();
+    writer.u8(0);
+    writer.u64(args.amount);
+    writer.u64(args.time);
+    writer.u64(args.asset_id);
+    writer.array(args.allowed_takers, 3, (item) => { writer.publicKey(item); });
+    writer.i64(args.expires_at);
+    writer.u64(args.asset_id_max);
+    writer.u64(args.min_reputation);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface TakeEscrowAccounts {
+    /** taker's main A/c (signed) to transfer assets from his game_acc to other game_acc it must be one of the escrow's allowed_takers when the escrow is private */
+    taker_main_acc: PublicKey;
+    /** Escrow A/c */
+    escrow_acc: PublicKey;
+    /** initialisers Game Program A/c */
+    initialiser_game_acc: PublicKey;
+    /** Taker's Game Program A/c */
+    taker_game_acc: PublicKey;
+    /** initialisers main A/c for dutch auctions, or the escrow's Market A/c if a rent request is listed in one */
+    extra_acc?: PublicKey;
+}
+
+export interface TakeEscrowArgs {
+    asset_id: Numberish;
+    max_price: Numberish;
+    arbiter: PublicKey;
+    oracle: PublicKey;
+}
+
+/**
+ * Accounts expected for Taking Escrow
+ *
+ * for a dutch auction the taker's main A/c must be writable, the current price is
+ * transferred from it to the initialiser and fails if it is above max_price
+ *
+ * asset_id is the asset the taker gives for a collection bid, 0 for the escrow's asset_id,
+ * arbiter and oracle must be the escrow's, which come from the Market A/c the initialiser
+ * chose, so the taker agrees to who can resolve a dispute and who confirms delivery,
+ * the data can stop after any field and the fields left out read as zero
+ */
+export function take_escrow(
+    program_id: PublicKey,
+    accounts: TakeEscrowAccounts,
+    args: TakeEscrowArgs,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.taker_main_acc, isSigner: true, isWritable: true },
+        { pubkey: accounts.escrow_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.initialiser_game_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.taker_game_acc, isSigner: false, isWritable: true },
+    ];
+    if (accounts.extra_acc) {
+        keys.push({ pubkey: accounts.extra_acc, isSigner: false, isWritable: true });
+    }
+    keys.push({ pubkey: new PublicKey("11111111111111111111111111111111"), isSigner: false, isWritable: false });
+
+    const writer = new Writer();
+    writer.u8(1);
+    writer.u64(args.asset_id);
+    writer.u64(args.max_price);
+    writer.publicKey(args.arbiter);
+    writer.publicKey(args.oracle);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface RevertAccounts {
+    /** initialiser's main A/c to transfer back rent fees */
+    initialiser_main_acc: PublicKey;
+    /** Escrow A/c */
+    escrow_acc: PublicKey;
+    /** initialisers Game Program A/c */
+    initialiser_game_acc: PublicKey;
+    /** Taker's Game Program A/c */
+    taker_game_acc: PublicKey;
+    /** taker's main A/c, paid the rent if the escrow holds it till the rental ends */
+    taker_main_acc: PublicKey;
+    /** keeper's A/c that cranks the revert, paid KEEPER_BOUNTY unless it is the initialiser's main A/c */
+    keeper_acc: PublicKey;
+}
+
+/**
+ * anyone can revert once the rental time has passed, a disputed escrow can only be
+ * reverted DISPUTE_TIMEOUT after the dispute was raised
+ */
+export function revert(
+    program_id: PublicKey,
+    accounts: RevertAccounts,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.initialiser_main_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.escrow_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.initialiser_game_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.taker_game_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.taker_main_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.keeper_acc, isSigner: true, isWritable: true },
+    ];
+
+    const writer = new Writer();
+    writer.u8(2);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface InitGameAccAccounts {
+    /** game_account where we will assign user_space ownership */
+    game_acc: PublicKey;
+    /** game owners main acc to assign him user_space ownership */
+    main_acc: PublicKey;
+}
+
+export function init_game_acc(
+    program_id: PublicKey,
+    accounts: InitGameAccAccounts,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.game_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.main_acc, isSigner: false, isWritable: false },
+    ];
+
+    const writer = new Writer();
+    writer.u8(3);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface AddAssetAccounts {
+    /** game_account to which asset is to be added */
+    game_acc: PublicKey;
+}
+
+export interface AddAssetArgs {
+    asset_id: Numberish;
+}
+
+/**
+ * currently restrictions on adding asset are not implemented
+ * u64 is the asset id and it should not be 0 since 0 will be used to represent Null
+ */
+export function add_asset(
+    program_id: PublicKey,
+    accounts: AddAssetAccounts,
+    args: AddAssetArgs,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.game_acc, isSigner: false, isWritable: true },
+    ];
+
+    const writer = new Writer();
+    writer.u8(4);
+    writer.u64(args.asset_id);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface CancelEscrowAccounts {
+    /** Escrow A/c */
+    escrow_acc: PublicKey;
+    /** initialisers main A/c */
+    initialiser_main_acc: PublicKey;
+    /** sellers Game Program A/c for auctions, to give back the asset held since listing, or the escrow's Market A/c, only if a rent request is listed in one */
+    extra_acc?: PublicKey;
+    /** initialisers Reputation A/c, only if the escrow hasn't expired */
+    initialiser_reputation_acc?: PublicKey;
+    initialiser_signs: boolean;
+}
+
+/**
+ * Cancels an escrow that has not been taken and refunds all lamports to the initialiser
+ *
+ * initialisers main A/c must sign unless the escrow has expired,
+ * after which anyone can close it. A cancel before expiry counts against the initialiser
+ */
+export function cancel_escrow(
+    program_id: PublicKey,
+    accounts: CancelEscrowAccounts,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.escrow_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.initialiser_main_acc, isSigner: accounts.initialiser_signs, isWritable: true },
+    ];
+    if (accounts.extra_acc) {
+        keys.push({ pubkey: accounts.extra_acc, isSigner: false, isWritable: true });
+    }
+    if (accounts.initialiser_reputation_acc) {
+        keys.push({ pubkey: accounts.initialiser_reputation_acc, isSigner: false, isWritable: true });
+    }
+
+    const writer = new Writer();
+    writer.u8(5);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface InitAuctionAccounts {
+    /** Escrow A/c */
+    escrow_acc: PublicKey;
+    /** sellers Game Program A/c, the asset is held in its lent till the auction ends */
+    seller_game_acc: PublicKey;
+    /** sellers main A/c */
+    seller_main_acc: PublicKey;
+}
+
+export interface InitAuctionArgs {
+    reserve_price: Numberish;
+    min_increment: Numberish;
+    time: Numberish;
+    asset_id: Numberish;
+    end_time: Numberish;
+    min_reputation: Numberish;
+}
+
+/** Lists an asset in an english auction, the Escrow A/c should hold only the rent-exempt lamports */
+export function init_auction(
+    program_id: PublicKey,
+    accounts: InitAuctionAccounts,
+    args: InitAuctionArgs,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.escrow_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.seller_game_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.seller_main_acc, isSigner: true, isWritable: false },
+    ];
+
+    const writer = new Writer();
+    writer.u8(6);
+    writer.u64(args.reserve_price);
+    writer.u64(args.min_increment);
+    writer.u64(args.time);
+    writer.u64(args.asset_id);
+    writer.i64(args.end_time);
+    writer.u64(args.min_reputation);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface PlaceBidAccounts {
+    /** bidder's main A/c */
+    bidder_main_acc: PublicKey;
+    /** Escrow A/c */
+    escrow_acc: PublicKey;
+    /** bidder's Game Program A/c */
+    bidder_game_acc: PublicKey;
+    /** previous highest bidder's main A/c, only needed if there is a bid already */
+    prev_bidder_main_acc?: PublicKey;
+}
+
+export interface PlaceBidArgs {
+    bid: Numberish;
+}
+
+/**
+ * Bids the given lamports, the previous highest bidder is refunded,
+ * the first bid must be at least the reserve price, later ones must be above the highest bid
+ * and at least min_increment more than it
+ */
+export function place_bid(
+    program_id: PublicKey,
+    accounts: PlaceBidAccounts,
+    args: PlaceBidArgs,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.bidder_main_acc, isSigner: true, isWritable: true },
+        { pubkey: accounts.escrow_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.bidder_game_acc, isSigner: false, isWritable: false },
+        { pubkey: new PublicKey("11111111111111111111111111111111"), isSigner: false, isWritable: false },
+    ];
+    if (accounts.prev_bidder_main_acc) {
+        keys.push({ pubkey: accounts.prev_bidder_main_acc, isSigner: false, isWritable: true });
+    }
+
+    const writer = new Writer();
+    writer.u8(7);
+    writer.u64(args.bid);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface SettleAuctionAccounts {
+    /** Escrow A/c */
+    escrow_acc: PublicKey;
+    /** sellers main A/c */
+    seller_main_acc: PublicKey;
+    /** sellers Game Program A/c */
+    seller_game_acc: PublicKey;
+    /** highest bidder's Game Program A/c, only needed if there are bids */
+    winner_game_acc?: PublicKey;
+    /** highest bidder's main A/c, refunded if the asset can't be transferred */
+    winner_main_acc?: PublicKey;
+}
+
+/**
+ * Settles an auction after its end_time, can be called by anyone
+ * if there are no bids the Escrow A/c is closed, lamports and the held asset go back to the seller
+ *
+ * a rented asset comes back to the seller with Revert
+ */
+export function settle_auction(
+    program_id: PublicKey,
+    accounts: SettleAuctionAccounts,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.escrow_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.seller_main_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.seller_game_acc, isSigner: false, isWritable: true },
+    ];
+    if (accounts.winner_game_acc) {
+        keys.push({ pubkey: accounts.winner_game_acc, isSigner: false, isWritable: true });
+    }
+    if (accounts.winner_main_acc) {
+        keys.push({ pubkey: accounts.winner_main_acc, isSigner: false, isWritable: true });
+    }
+
+    const writer = new Writer();
+    writer.u8(8);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface InitDutchAuctionAccounts {
+    /** Escrow A/c */
+    escrow_acc: PublicKey;
+    /** sellers Game Program A/c, the asset is held in its lent till the auction ends */
+    seller_game_acc: PublicKey;
+    /** sellers main A/c */
+    seller_main_acc: PublicKey;
+}
+
+export interface InitDutchAuctionArgs {
+    start_price: Numberish;
+    end_price: Numberish;
+    time: Numberish;
+    asset_id: Numberish;
+    start_time: Numberish;
+    end_time: Numberish;
+    min_reputation: Numberish;
+}
+
+/**
+ * Lists an asset in a dutch auction whose price declines linearly from start_price to end_price
+ *
+ * it is taken with TakeEscrow and a rented asset comes back to the seller with Revert
+ */
+export function init_dutch_auction(
+    program_id: PublicKey,
+    accounts: InitDutchAuctionAccounts,
+    args: InitDutchAuctionArgs,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.escrow_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.seller_game_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.seller_main_acc, isSigner: true, isWritable: false },
+    ];
+
+    const writer = new Writer();
+    writer.u8(9);
+    writer.u64(args.start_price);
+    writer.u64(args.end_price);
+    writer.u64(args.time);
+    writer.u64(args.asset_id);
+    writer.i64(args.start_time);
+    writer.i64(args.end_time);
+    writer.u64(args.min_reputation);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface MakeOfferAccounts {
+    /** Offer A/c */
+    offer_acc: PublicKey;
+    /** Escrow A/c */
+    escrow_acc: PublicKey;
+    /** offerer's main A/c */
+    offerer_main_acc: PublicKey;
+    /** offerer's Game Program A/c holding the escrow's asset */
+    offerer_game_acc: PublicKey;
+}
+
+export interface MakeOfferArgs {
+    amount: Numberish;
+    time: Numberish;
+    asset_id: Numberish;
+    arbiter: PublicKey;
+    oracle: PublicKey;
+}
+
+/**
+ * Proposes a different amount or time for an open rent request escrow
+ *
+ * arbiter and oracle must be the escrow's as for TakeEscrow, they can be left out
+ * of the data for an escrow without them
+ */
+export function make_offer(
+    program_id: PublicKey,
+    accounts: MakeOfferAccounts,
+    args: MakeOfferArgs,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.offer_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.escrow_acc, isSigner: false, isWritable: false },
+        { pubkey: accounts.offerer_main_acc, isSigner: true, isWritable: false },
+        { pubkey: accounts.offerer_game_acc, isSigner: false, isWritable: false },
+    ];
+
+    const writer = new Writer();
+    writer.u8(10);
+    writer.u64(args.amount);
+    writer.u64(args.time);
+    writer.u64(args.asset_id);
+    writer.publicKey(args.arbiter);
+    writer.publicKey(args.oracle);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface AcceptOfferAccounts {
+    /** initialisers main A/c */
+    initialiser_main_acc: PublicKey;
+    /** Escrow A/c */
+    escrow_acc: PublicKey;
+    /** Offer A/c */
+    offer_acc: PublicKey;
+    /** initialisers Game Program A/c */
+    initialiser_game_acc: PublicKey;
+    /** offerer's Game Program A/c */
+    offerer_game_acc: PublicKey;
+    /** offerer's main A/c */
+    offerer_main_acc: PublicKey;
+    /** the escrow's Market A/c, only if it is listed in one */
+    market_acc?: PublicKey;
+}
+
+/**
+ * Amends the escrow to the offer's terms and takes it in the same step,
+ * the Offer A/c is closed and its lamports go back to the offerer
+ *
+ * if the offer's amount is more than the escrow holds the initialiser deposits the difference
+ * in the Escrow A/c earlier in the same transaction, anything above the amount is refunded
+ */
+export function accept_offer(
+    program_id: PublicKey,
+    accounts: AcceptOfferAccounts,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.initialiser_main_acc, isSigner: true, isWritable: true },
+        { pubkey: accounts.escrow_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.offer_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.initialiser_game_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.offerer_game_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.offerer_main_acc, isSigner: false, isWritable: true },
+    ];
+    if (accounts.market_acc) {
+        keys.push({ pubkey: accounts.market_acc, isSigner: false, isWritable: true });
+    }
+
+    const writer = new Writer();
+    writer.u8(11);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface InitMarketAccounts {
+    /** Market A/c */
+    market_acc: PublicKey;
+    /** market authority's main A/c */
+    authority: PublicKey;
+    /** optional arbiter's main A/c, resolves disputes of rent requests initialised with this market */
+    arbiter?: PublicKey;
+    /** optional game server's oracle key, confirms delivery of assets rented through this market */
+    oracle?: PublicKey;
+}
+
+/**
+ * Creates a Market A/c that indexes open rent request escrows, english and dutch auctions
+ * aren't listed in one. a market isn't tied to a game and any main A/c can create one as
+ * its authority, clients choose which Market A/c's they trust by address and authority
+ *
+ * pass Pubkey::default() as the arbiter to have an oracle without an arbiter
+ */
+export function init_market(
+    program_id: PublicKey,
+    accounts: InitMarketAccounts,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.market_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.authority, isSigner: true, isWritable: false },
+    ];
+    if (accounts.arbiter) {
+        keys.push({ pubkey: accounts.arbiter, isSigner: false, isWritable: false });
+    }
+    if (accounts.oracle) {
+        keys.push({ pubkey: accounts.oracle, isSigner: false, isWritable: false });
+    }
+
+    const writer = new Writer();
+    writer.u8(12);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface AssertOwnsAccounts {
+    /** player's Game Program A/c */
+    game_acc: PublicKey;
+    /** player's main A/c */
+    player_main_acc: PublicKey;
+}
+
+export interface AssertOwnsArgs {
+    asset_id: Numberish;
+}
+
+/**
+ * Succeeds only if the player can use the asset right now, i.e. it is owned or rented
+ * and not lent out, meant to be called by game programs through CPI
+ */
+export function assert_owns(
+    program_id: PublicKey,
+    accounts: AssertOwnsAccounts,
+    args: AssertOwnsArgs,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.game_acc, isSigner: false, isWritable: false },
+        { pubkey: accounts.player_main_acc, isSigner: false, isWritable: false },
+    ];
+
+    const writer = new Writer();
+    writer.u8(13);
+    writer.u64(args.asset_id);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface LockForMatchAccounts {
+    /** player's Game Program A/c */
+    game_acc: PublicKey;
+    /** player's main A/c */
+    player_main_acc: PublicKey;
+    /** game program's authority, usually a PDA signing through invoke_signed */
+    authority: PublicKey;
+}
+
+/**
+ * Locks the Game Program A/c for a match, no asset can be put on rent or sold until the
+ * same authority unlocks it. Reverts of expired rentals still go through
+ */
+export function lock_for_match(
+    program_id: PublicKey,
+    accounts: LockForMatchAccounts,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.game_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.player_main_acc, isSigner: true, isWritable: false },
+        { pubkey: accounts.authority, isSigner: true, isWritable: false },
+    ];
+
+    const writer = new Writer();
+    writer.u8(14);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface UnlockAfterMatchAccounts {
+    /** player's Game Program A/c */
+    game_acc: PublicKey;
+    /** the authority that locked it */
+    authority: PublicKey;
+}
+
+/** Unlocks a Game Program A/c locked with LockForMatch */
+export function unlock_after_match(
+    program_id: PublicKey,
+    accounts: UnlockAfterMatchAccounts,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.game_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.authority, isSigner: true, isWritable: false },
+    ];
+
+    const writer = new Writer();
+    writer.u8(15);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface MigrateAccountAccounts {
+    /** the account to migrate */
+    account: PublicKey;
+    /** payer of the extra rent for the larger account */
+    payer: PublicKey;
+}
+
+/**
+ * Upgrades an account of any type from an older layout version to the current one in place,
+ * can be called by anyone and does nothing if the account is already current
+ */
+export function migrate_account(
+    program_id: PublicKey,
+    accounts: MigrateAccountAccounts,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.account, isSigner: false, isWritable: true },
+        { pubkey: accounts.payer, isSigner: true, isWritable: true },
+        { pubkey: new PublicKey("11111111111111111111111111111111"), isSigner: false, isWritable: false },
+    ];
+
+    const writer = new Writer();
+    writer.u8(16);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface InitHistoryAccounts {
+    /** History A/c PDA */
+    history_acc: PublicKey;
+    /** payer of the History A/c's rent */
+    payer: PublicKey;
+}
+
+export interface InitHistoryArgs {
+    asset_id: Numberish;
+}
+
+/**
+ * Creates the History A/c of an asset at the PDA of [HISTORY_SEED, asset_id le bytes]
+ *
+ * TakeEscrow, Revert, SettleAuction with bids, AcceptOffer and ResolveDispute record
+ * an entry in it and fail unless it is passed after their other accounts,
+ * so it has to be created before the asset is first rented or sold
+ */
+export function init_history(
+    program_id: PublicKey,
+    accounts: InitHistoryAccounts,
+    args: InitHistoryArgs,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.history_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.payer, isSigner: true, isWritable: true },
+        { pubkey: new PublicKey("11111111111111111111111111111111"), isSigner: false, isWritable: false },
+    ];
+
+    const writer = new Writer();
+    writer.u8(17);
+    writer.u64(args.asset_id);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface InitReputationAccounts {
+    /** Reputation A/c PDA */
+    reputation_acc: PublicKey;
+    /** the wallet's main A/c */
+    wallet: PublicKey;
+    /** payer of the Reputation A/c's rent */
+    payer: PublicKey;
+}
+
+/**
+ * Creates the Reputation A/c of a wallet at the PDA of [REPUTATION_SEED, wallet]
+ *
+ * TakeEscrow, Revert and SettleAuction count a completed deal in the Reputation A/c's of
+ * the parties passed after their other accounts. CancelEscrow before expiry and
+ * ResolveDispute with a party at fault fail unless the Reputation A/c it counts against
+ * is passed there. TakeEscrow, MakeOffer and PlaceBid need the taker's Reputation A/c
+ * there when the escrow has a min_reputation
+ */
+export function init_reputation(
+    program_id: PublicKey,
+    accounts: InitReputationAccounts,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.reputation_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.wallet, isSigner: false, isWritable: false },
+        { pubkey: accounts.payer, isSigner: true, isWritable: true },
+        { pubkey: new PublicKey("11111111111111111111111111111111"), isSigner: false, isWritable: false },
+    ];
+
+    const writer = new Writer();
+    writer.u8(18);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface RaiseDisputeAccounts {
+    /** Escrow A/c */
+    escrow_acc: PublicKey;
+    /** initialisers or taker's main A/c */
+    raiser_main_acc: PublicKey;
+}
+
+/**
+ * Freezes a taken rent request escrow that has an arbiter, it can't be reverted
+ * till the arbiter resolves it or DISPUTE_TIMEOUT passes
+ */
+export function raise_dispute(
+    program_id: PublicKey,
+    accounts: RaiseDisputeAccounts,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.escrow_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.raiser_main_acc, isSigner: true, isWritable: false },
+    ];
+
+    const writer = new Writer();
+    writer.u8(19);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface ResolveDisputeAccounts {
+    /** the escrow's arbiter */
+    arbiter: PublicKey;
+    /** Escrow A/c */
+    escrow_acc: PublicKey;
+    /** initialisers main A/c */
+    initialiser_main_acc: PublicKey;
+    /** taker's main A/c */
+    taker_main_acc: PublicKey;
+    /** initialisers Game Program A/c */
+    initialiser_game_acc: PublicKey;
+    /** Taker's Game Program A/c */
+    taker_game_acc: PublicKey;
+    /** Reputation A/c of the party at fault, only if at_fault isn't DISPUTE_FAULT_NONE */
+    at_fault_reputation_acc?: PublicKey;
+}
+
+export interface ResolveDisputeArgs {
+    renter_refund: Numberish;
+    renter_keeps_asset: boolean;
+    at_fault: number;
+}
+
+/**
+ * Splits the held rent between renter and lender and decides where the asset ends up,
+ * the Escrow A/c is closed and its rent-exempt lamports go to the initialiser
+ */
+export function resolve_dispute(
+    program_id: PublicKey,
+    accounts: ResolveDisputeAccounts,
+    args: ResolveDisputeArgs,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.arbiter, isSigner: true, isWritable: false },
+        { pubkey: accounts.escrow_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.initialiser_main_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.taker_main_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.initialiser_game_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.taker_game_acc, isSigner: false, isWritable: true },
+    ];
+    if (accounts.at_fault_reputation_acc) {
+        keys.push({ pubkey: accounts.at_fault_reputation_acc, isSigner: false, isWritable: true });
+    }
+
+    const writer = new Writer();
+    writer.u8(20);
+    writer.u64(args.renter_refund);
+    writer.bool(args.renter_keeps_asset);
+    writer.u8(args.at_fault);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface ConfirmDeliveryAccounts {
+    /** Escrow A/c */
+    escrow_acc: PublicKey;
+    /** taker's main A/c */
+    taker_main_acc: PublicKey;
+    /** the escrow's oracle, or the Instructions sysvar when the instruction before this one is an Ed25519 program instruction verifying the oracle's signature over Escrow::delivery_attestation */
+    oracle: PublicKey;
+}
+
+/**
+ * Confirms the rented asset was delivered to the renter in-game and releases the held rent
+ * to the lender, needed for rent requests initialised with a market that has an oracle
+ *
+ * if delivery is never confirmed Revert refunds the rent to the initialiser
+ */
+export function confirm_delivery(
+    program_id: PublicKey,
+    accounts: ConfirmDeliveryAccounts,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.escrow_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.taker_main_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.oracle, isSigner: true, isWritable: false },
+    ];
+
+    const writer = new Writer();
+    writer.u8(21);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface CloseGameAccAccounts {
+    /** Game Program A/c to close */
+    game_acc: PublicKey;
+    /** userspace owner's main A/c */
+    owner_main_acc: PublicKey;
+    /** optional Game Program A/c of the same owner that gets the remaining owned assets */
+    assets_to_game_acc?: PublicKey;
+}
+
+/**
+ * Closes a Game Program A/c that has no rented assets and none lent out,
+ * its lamports go back to the userspace owner
+ *
+ * without the last A/c the owned assets are given up with the account
+ */
+export function close_game_acc(
+    program_id: PublicKey,
+    accounts: CloseGameAccAccounts,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.game_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.owner_main_acc, isSigner: true, isWritable: true },
+    ];
+    if (accounts.assets_to_game_acc) {
+        keys.push({ pubkey: accounts.assets_to_game_acc, isSigner: false, isWritable: true });
+    }
+
+    const writer = new Writer();
+    writer.u8(22);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export interface CancelOfferAccounts {
+    /** Offer A/c */
+    offer_acc: PublicKey;
+    /** offerer's main A/c */
+    offerer_main_acc: PublicKey;
+    /** the offer's Escrow A/c */
+    escrow_acc: PublicKey;
+    offerer_signs: boolean;
+}
+
+/**
+ * Withdraws an offer, the Offer A/c is closed and its lamports go back to the offerer
+ *
+ * offerer's main A/c must sign unless the escrow is gone, i.e. closed, taken or expired,
+ * after which anyone can close the offer
+ */
+export function cancel_offer(
+    program_id: PublicKey,
+    accounts: CancelOfferAccounts,
+): TransactionInstruction {
+    const keys: AccountMeta[] = [
+        { pubkey: accounts.offer_acc, isSigner: false, isWritable: true },
+        { pubkey: accounts.offerer_main_acc, isSigner: accounts.offerer_signs, isWritable: true },
+        { pubkey: accounts.escrow_acc, isSigner: false, isWritable: false },
+    ];
+
+    const writer = new Writer();
+    writer.u8(23);
+
+    return new TransactionInstruction({ programId: program_id, keys, data: writer.toBuffer() });
+}
+
+export enum EscrowError {
+    InvalidInstruction = 0,
+    MissingPermission = 1,
+    IncorrectAcc = 2,
+    AmountOverflow = 3,
+    EscrowAlreadyTaken = 4,
+    AssetSpaceFull = 5,
+    TakerNotAllowed = 6,
+    EscrowExpired = 7,
+    BidTooLow = 8,
+    AuctionEnded = 9,
+    AuctionNotEnded = 10,
+    AccountLocked = 11,
+    AssetLentOut = 12,
+    AccountOutdated = 13,
+    ReputationTooLow = 14,
+    EscrowDisputed = 15,
+    AccountClosed = 16,
+    AssetRented = 17,
+    AssetNotOwned = 18,
+    RentalNotExpired = 19,
+    NotEscrowAccount = 20,
+    NotGameAccount = 21,
+    OwnerMismatch = 22,
+    AccountMismatch = 23,
+    AssetNotAccepted = 24,
+    OwnEscrow = 25,
+    EscrowNotTaken = 26,
+    WrongEscrowKind = 27,
+    EscrowNotDisputable = 28,
+    EscrowNotDisputed = 29,
+    DeliveryNotPending = 30,
+    NotEscrowParty = 31,
+    IncorrectPda = 32,
+    PriceTooHigh = 33,
+    MissingHistory = 34,
+    MissingReputation = 35,
+    ArbiterMismatch = 36,
+    OracleMismatch = 37,
+    InvalidAttestation = 38,
+    NotArbiter = 39,
+    NotLockAuthority = 40,
+}
+
+// messages of the program's custom error codes, found in a failed transaction's logs
+export const ESCROW_ERROR_MESSAGES: { [code: number]: string } = {
+    0: "Invalid Instruction",
+    1: "Missing Required Permissions",
+    2: "Incorrect Acc",
+    3: "Amount Overflow",
+    4: "Escrow Taken",
+    5: "Asset Space Filled",
+    6: "Taker Not Allowed",
+    7: "Escrow Expired",
+    8: "Bid Too Low",
+    9: "Auction Ended",
+    10: "Auction Not Ended",
+    11: "Account Locked",
+    12: "Asset Lent Out",
+    13: "Account Outdated",
+    14: "Reputation Too Low",
+    15: "Escrow Disputed",
+    16: "Account Closed",
+    17: "Asset Rented",
+    18: "Asset Not Owned",
+    19: "Rental Not Expired",
+    20: "Not An Escrow Account",
+    21: "Not A Game Account",
+    22: "Owner Mismatch",
+    23: "Account Does Not Match",
+    24: "Asset Not Accepted",
+    25: "Can't Take Own Escrow",
+    26: "Escrow Not Taken",
+    27: "Wrong Escrow Kind",
+    28: "Escrow Not Disputable",
+    29: "Escrow Not Disputed",
+    30: "Delivery Not Pending",
+    31: "Not An Escrow Party",
+    32: "Incorrect PDA",
+    33: "Price Above Maximum",
+    34: "History Account Missing",
+    35: "Reputation Account Missing",
+    36: "Arbiter Does Not Match",
+    37: "Oracle Does Not Match",
+    38: "Attestation Invalid",
+    39: "Signer Is Not The Arbiter",
+    40: "Signer Is Not The Lock Authority",
+};
@@ -1,6 +1,6 @@
-import { Keypair, Connection, PublicKey, SystemProgram, Transaction, TransactionInstruction } from "@solana/web3.js";
-import BN from "bn.js";
-import { EscrowAcc_Data_Layout, EscrowAcc_Layout,GameAcc_Data_Layout, GameAcc_Layout, program_id, Asset_Space} from "./layout";
+import { Keypair, Connection, PublicKey, SystemProgram, Transaction } from "@solana/web3.js";
+import * as game_nest from "./game_nest";
+import { program_id } from "./program_id";
 
 const connection = new Connection("https://api.devnet.solana.com", 'singleGossip');
 
@@ -26,19 +26,23 @@ export const init_escrow_acc = async(
         programId : program_id,
         fromPubkey : main_keypair.publicKey,
         newAccountPubkey : escrow_acc_keypair.publicKey,
-        space : EscrowAcc_Data_Layout.span,
-        lamports : await connection.getMinimumBalanceForRentExemption(EscrowAcc_Data_Layout.span, 'singleGossip') + amount,
+        space : game_nest.ESCROW_LEN,
+        lamports : await connection.getMinimumBalanceForRentExemption(game_nest.ESCROW_LEN, 'singleGossip') + amount,
     });
 
-    const initialiseIx = new TransactionInstruction({
-        programId : program_id,
-        keys : [
-            {pubkey : escrow_acc_keypair.publicKey, isSigner : false, isWritable : true},
-            {pubkey : game_acc_key, isSigner : false, isWritable : false},
-            {pubkey : main_keypair.publicKey, isSigner : false, isWritable : false},
-        ],
-        data : Buffer.from(Uint8Array.of(0,...new BN(amount).toArray("le",8),...new BN(time).toArray("le",8),...new BN(asset_id).toArray("le",8)))
-    })
+    const initialiseIx = game_nest.init_escrow(program_id, {
+        escrow_acc : escrow_acc_keypair.publicKey,
+        initialiser_game_acc : game_acc_key,
+        initialiser_main_acc : main_keypair.publicKey,
+    }, {
+        amount,
+        time,
+        asset_id,
+        allowed_takers : [PublicKey.default, PublicKey.default, PublicKey.default], // anyone can take
+        expires_at : 0,
+        asset_id_max : 0,
+        min_reputation : 0,
+    });
 
     const tx = new Transaction().add(createIx,initialiseIx);
 
@@ -47,25 +51,20 @@ export const init_escrow_acc = async(
     await new Promise((resolve) => setTimeout(resolve, 1000));
 
     const encodedEscrowState = (await connection.getAccountInfo(escrow_acc_keypair.publicKey, 'singleGossip'))!.data;
-    const decodedEscrowState = EscrowAcc_Data_Layout.decode(encodedEscrowState) as EscrowAcc_Layout;
+    const decodedEscrowState = game_nest.decode_escrow(encodedEscrowState);
 
     const encodedGameAccState = (await connection.getAccountInfo(game_acc_key, 'singleGossip'))!.data;
-    const decodedGameAccState = GameAcc_Data_Layout.decode(encodedGameAccState) as GameAcc_Layout;
-
-    var initialiser_rented_asset = [];
-    var initialiser_asset = [];
+    const decodedGameAccState = game_nest.decode_game_acc(encodedGameAccState);
 
-    for(var i=0; i < Asset_Space;i++){
-        initialiser_rented_asset[i] = new BN(decodedGameAccState.rented.slice(i*8,(i+1)*8)).toNumber();
-        initialiser_asset[i] = new BN(decodedGameAccState.owned.slice(i*8,(i+1)*8)).toNumber();
-    }
+    const initialiser_rented_asset = decodedGameAccState.rented.map((asset) => asset.toNumber());
+    const initialiser_asset = decodedGameAccState.owned.map((asset) => asset.toNumber());
 
     return {
         escrow_acc_pubkey : escrow_acc_keypair.publicKey.toBase58(),
-        is_taken : decodedEscrowState.is_taken === 1? 'Taken' : 'Not Taken',
-        amount : new BN(decodedEscrowState.amount,10,"le").toNumber(),
-        time : new BN(decodedEscrowState.time,10,"le").toNumber(),
-        asset_id : new BN(decodedEscrowState.asset_id,10,"le").toNumber(),
+        is_taken : decodedEscrowState.is_taken ? 'Taken' : 'Not Taken',
+        amount : decodedEscrowState.amount.toNumber(),
+        time : decodedEscrowState.time.toNumber(),
+        asset_id : decodedEscrowState.asset_id.toNumber(),
         initialiser_asset: initialiser_asset.toString(),
         initialiser_rented_asset : initialiser_rented_asset.toString()
     };
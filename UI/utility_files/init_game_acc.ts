@@ -1,5 +1,6 @@
-import { Keypair, Connection, SystemProgram, Transaction, TransactionInstruction } from "@solana/web3.js";
-import { GameAcc_Data_Layout, program_id} from "./layout";
+import { Keypair, Connection, SystemProgram, Transaction } from "@solana/web3.js";
+import * as game_nest from "./game_nest";
+import { program_id } from "./program_id";
 
 const connection = new Connection("https://api.devnet.solana.com", 'singleGossip');
 
@@ -19,20 +20,16 @@ export const init_game_acc = async(
         programId : program_id,
         fromPubkey : main_keypair.publicKey,
         newAccountPubkey : game_acc_keypair.publicKey,
-        space : GameAcc_Data_Layout.span,
-        lamports : await connection.getMinimumBalanceForRentExemption(GameAcc_Data_Layout.span, 'singleGossip'),
+        space : game_nest.GAME_ACC_LEN,
+        lamports : await connection.getMinimumBalanceForRentExemption(game_nest.GAME_ACC_LEN, 'singleGossip'),
     });
 
     // initialise game account 
 
-    const initialiseIx = new TransactionInstruction({
-        programId : program_id,
-        keys : [
-            {pubkey : game_acc_keypair.publicKey, isSigner : false, isWritable : true},
-            {pubkey : main_keypair.publicKey, isSigner : false, isWritable : false},
-        ],
-        data : Buffer.from(Uint8Array.of(3))
-    })
+    const initialiseIx = game_nest.init_game_acc(program_id, {
+        game_acc : game_acc_keypair.publicKey,
+        main_acc : main_keypair.publicKey,
+    });
 
     const tx = new Transaction().add(createIx,initialiseIx);
 
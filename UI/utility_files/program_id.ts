@@ -0,0 +1,3 @@
+import { PublicKey } from "@solana/web3.js";
+
+export const program_id = new PublicKey('GA1ca3KjdsHeXGoikYcd3zn8gKHb1q3WVmSjSXG6Bu2e'); // write program_id here as public key object
@@ -1,6 +1,6 @@
-import { Keypair, Connection, PublicKey, Transaction, TransactionInstruction } from "@solana/web3.js";
-import BN from "bn.js";
-import { EscrowAcc_Data_Layout, EscrowAcc_Layout,GameAcc_Data_Layout,GameAcc_Layout,Asset_Space, program_id} from "./layout";
+import { Connection, PublicKey, Transaction } from "@solana/web3.js";
+import * as game_nest from "./game_nest";
+import { program_id } from "./program_id";
 
 const connection = new Connection("https://api.devnet.solana.com", 'singleGossip');
 
@@ -10,22 +10,24 @@ export const revert = async(
     const escrow_acc_key = new PublicKey(escrow_acc);
 
     const encodedEscrowState = (await connection.getAccountInfo(escrow_acc_key, 'singleGossip'))!.data;
-    const decodedEscrowState = EscrowAcc_Data_Layout.decode(encodedEscrowState) as EscrowAcc_Layout;
-
-    const initialiser_main_acc_key = new PublicKey(decodedEscrowState.initialiser_main_acc);
-    const initialiser_game_acc_key = new PublicKey(decodedEscrowState.initialiser_game_acc);
-    const taker_game_acc_key = new PublicKey(decodedEscrowState.taker_game_acc);
-
-    const revertIX = new TransactionInstruction({
-        programId : program_id,
-        keys : [
-            {pubkey: initialiser_main_acc_key, isSigner: false, isWritable:true},
-            {pubkey: escrow_acc_key, isSigner: false, isWritable:true},
-            {pubkey: initialiser_game_acc_key, isSigner: false, isWritable:true},
-            {pubkey: taker_game_acc_key, isSigner: false, isWritable:true},
-        ],
-        data : Buffer.from(Uint8Array.of(2))
-    })
+    const decodedEscrowState = game_nest.decode_escrow(encodedEscrowState);
+
+    const initialiser_main_acc_key = decodedEscrowState.initialiser_main_acc;
+    const initialiser_game_acc_key = decodedEscrowState.initialiser_game_acc;
+    const taker_game_acc_key = decodedEscrowState.taker_game_acc;
+
+    // a rent request with an arbiter or oracle holds the rent till the rental ends, it is paid to the taker on revert
+    const holds_rent = decodedEscrowState.kind === game_nest.ESCROW_KIND_RENT_REQUEST
+        && !(decodedEscrowState.arbiter.equals(PublicKey.default) && decodedEscrowState.oracle.equals(PublicKey.default))
+        && decodedEscrowState.delivered_at.isZero();
+
+    const revertIX = game_nest.revert(program_id, {
+        initialiser_main_acc : initialiser_main_acc_key,
+        escrow_acc : escrow_acc_key,
+        initialiser_game_acc : initialiser_game_acc_key,
+        taker_game_acc : taker_game_acc_key,
+        taker_main_acc : holds_rent ? decodedEscrowState.taker_main_acc : undefined,
+    });
 
     const tx = new Transaction().add(revertIX);
 
@@ -34,24 +36,16 @@ export const revert = async(
     await new Promise((resolve) => setTimeout(resolve, 1000));
 
     const encodediGameAccState = (await connection.getAccountInfo(initialiser_game_acc_key, 'singleGossip'))!.data;
-    const decodediGameAccState = GameAcc_Data_Layout.decode(encodediGameAccState) as GameAcc_Layout;
+    const decodediGameAccState = game_nest.decode_game_acc(encodediGameAccState);
 
     const encodedtGameAccState = (await connection.getAccountInfo(taker_game_acc_key, 'singleGossip'))!.data;
-    const decodedtGameAccState = GameAcc_Data_Layout.decode(encodedtGameAccState) as GameAcc_Layout;
-
-    var initialiser_asset = [];
-    var initialiser_rented_asset = [];
-
-    var taker_asset = [];
-    var taker_rented_asset = [];
+    const decodedtGameAccState = game_nest.decode_game_acc(encodedtGameAccState);
 
+    const initialiser_asset = decodediGameAccState.owned.map((asset) => asset.toNumber());
+    const initialiser_rented_asset = decodediGameAccState.rented.map((asset) => asset.toNumber());
 
-    for(var i=0; i < Asset_Space;i++){
-        initialiser_rented_asset[i] = new BN(decodediGameAccState.rented.slice(i*8,(i+1)*8)).toNumber();
-        initialiser_asset[i] = new BN(decodediGameAccState.owned.slice(i*8,(i+1)*8)).toNumber();
-        taker_rented_asset[i] = new BN(decodedtGameAccState.rented.slice(i*8,(i+1)*8)).toNumber();
-        taker_asset[i] = new BN(decodedtGameAccState.owned.slice(i*8,(i+1)*8)).toNumber();
-    }
+    const taker_asset = decodedtGameAccState.owned.map((asset) => asset.toNumber());
+    const taker_rented_asset = decodedtGameAccState.rented.map((asset) => asset.toNumber());
 
     return{
         initialiser_asset : initialiser_asset.toString(),
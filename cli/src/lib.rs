@@ -0,0 +1,2 @@
+// decoding of the program's accounts for display, kept out of main so it can be tested offline
+pub mod show;
@@ -0,0 +1,557 @@
+use clap::{Parser, Subcommand};
+use game_nest::{
+    error::EscrowError,
+    instruction::{self, InitEscData, TakeEscData},
+    state::{
+        Escrow, GameAcc, History, Reputation, ALLOWED_TAKERS_LEN, ESCROW_KIND_DUTCH_AUCTION,
+        ESCROW_KIND_ENGLISH_AUCTION,
+    },
+};
+use gamenest_cli::show;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::from_account,
+    clock::Clock,
+    commitment_config::CommitmentConfig,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    system_instruction, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use std::error::Error;
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+fn default_keypair_path() -> String {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    format!("{}/.config/solana/id.json", home)
+}
+
+#[derive(Parser)]
+#[command(
+    name = "gamenest-cli",
+    about = "Sends GameNest instructions and decodes its accounts"
+)]
+struct Cli {
+    /// keypair file of the wallet that signs and pays for the transactions
+    #[arg(long, short, default_value_t = default_keypair_path())]
+    keypair: String,
+
+    /// RPC URL of the cluster
+    #[arg(long, short, default_value = "http://127.0.0.1:8899")]
+    url: String,
+
+    /// address the program is deployed at
+    #[arg(long)]
+    program_id: Pubkey,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Creates a Game Program A/c owned by the wallet
+    InitGameAcc,
+
+    /// Adds an asset to a Game Program A/c
+    AddAsset {
+        #[arg(long)]
+        game_acc: Pubkey,
+
+        /// should not be 0, which stands for no asset
+        asset_id: u64,
+    },
+
+    /// Opens a rent request, the rent is paid into a new Escrow A/c
+    InitEscrow {
+        /// the wallet's Game Program A/c that gets the asset
+        #[arg(long)]
+        game_acc: Pubkey,
+
+        #[arg(long)]
+        asset_id: u64,
+
+        /// rent in lamports
+        #[arg(long)]
+        amount: u64,
+
+        /// rental time in minutes
+        #[arg(long)]
+        time: u64,
+
+        /// unix timestamp after which the escrow can't be taken, 0 if it never expires
+        #[arg(long, default_value_t = 0)]
+        expires_at: i64,
+
+        /// main A/c allowed to take the escrow, can be given up to 3 times, anyone if not given
+        #[arg(long)]
+        allowed_taker: Vec<Pubkey>,
+
+        /// non 0 for a collection bid accepting any asset from asset_id to asset_id_max
+        #[arg(long, default_value_t = 0)]
+        asset_id_max: u64,
+
+        #[arg(long, default_value_t = 0)]
+        min_reputation: u64,
+
+        /// Market A/c to list the rent request in
+        #[arg(long)]
+        market: Option<Pubkey>,
+    },
+
+    /// Takes a rent request or buys from a dutch auction
+    Take {
+        #[arg(long)]
+        escrow: Pubkey,
+
+        /// the wallet's Game Program A/c the asset comes from, or goes to when buying
+        #[arg(long)]
+        game_acc: Pubkey,
+
+        /// asset given for a collection bid, 0 for the escrow's asset
+        #[arg(long, default_value_t = 0)]
+        asset_id: u64,
+
+        /// most lamports the wallet pays for a dutch auction, the take fails if the price is above it
+        #[arg(long)]
+        max_price: Option<u64>,
+
+        /// the escrow's arbiter the wallet agrees to, needed if the escrow has one
+        #[arg(long)]
+        arbiter: Option<Pubkey>,
+
+        /// the escrow's oracle the wallet agrees to, needed if the escrow has one
+        #[arg(long)]
+        oracle: Option<Pubkey>,
+    },
+
+    /// Returns a rented asset once the rental is over, the wallet gets the keeper bounty
+    /// when it is not the initialiser's
+    Revert {
+        #[arg(long)]
+        escrow: Pubkey,
+    },
+
+    /// Cancels an escrow that has not been taken, the wallet must be the initialiser's
+    /// unless the escrow has expired
+    Cancel {
+        #[arg(long)]
+        escrow: Pubkey,
+    },
+
+    /// Decodes an Escrow or Game Program A/c
+    Show {
+        address: Pubkey,
+
+        /// print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+struct Client {
+    rpc: RpcClient,
+    payer: Keypair,
+    program_id: Pubkey,
+}
+
+impl Client {
+    fn new(rpc: RpcClient, keypair: &str, program_id: Pubkey) -> CliResult<Client> {
+        let payer = read_keypair_file(keypair)
+            .map_err(|err| format!("can't read keypair {}: {}", keypair, err))?;
+
+        Ok(Client {
+            rpc,
+            payer,
+            program_id,
+        })
+    }
+
+    fn send(&self, instructions: &[Instruction], signers: &[&Keypair]) -> CliResult<Signature> {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.rpc.get_latest_blockhash()?,
+        );
+
+        self.rpc
+            .send_and_confirm_transaction(&transaction)
+            .map_err(|err| {
+                // names the program's error instead of its bare code
+                match err.get_transaction_error() {
+                    Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+                        match EscrowError::from_code(code) {
+                            Some(error) => format!("{} (error {})", error, code).into(),
+                            None => err.into(),
+                        }
+                    }
+                    _ => err.into(),
+                }
+            })
+    }
+
+    // the cluster's time, as the program reads it from the Clock sysvar
+    fn unix_timestamp(&self) -> CliResult<i64> {
+        let account = self.rpc.get_account(&sysvar::clock::id())?;
+        let clock: Clock = from_account(&account).ok_or("can't decode the Clock sysvar")?;
+
+        Ok(clock.unix_timestamp)
+    }
+
+    fn fetch_escrow(&self, address: &Pubkey) -> CliResult<Escrow> {
+        Ok(Escrow::unpack(&self.rpc.get_account_data(address)?)?)
+    }
+
+    // instruction creating a new program owned account holding rent-exempt lamports plus extra
+    fn create_program_acc(
+        &self,
+        acc: &Keypair,
+        space: usize,
+        extra_lamports: u64,
+    ) -> CliResult<Instruction> {
+        let lamports = self
+            .rpc
+            .get_minimum_balance_for_rent_exemption(space)?
+            .checked_add(extra_lamports)
+            .ok_or("lamports overflow")?;
+
+        Ok(system_instruction::create_account(
+            &self.payer.pubkey(),
+            &acc.pubkey(),
+            lamports,
+            space as u64,
+            &self.program_id,
+        ))
+    }
+
+    // every transfer of an asset is recorded in its History A/c,
+    // the wallet creates it first if the asset was never rented or sold
+    fn init_history_if_missing(&self, asset_id: u64) -> CliResult<Option<Instruction>> {
+        let (history_acc, _) = History::find_address(&self.program_id, asset_id);
+
+        let history = self
+            .rpc
+            .get_account_with_commitment(&history_acc, self.rpc.commitment())?
+            .value;
+
+        Ok(match history {
+            Some(_) => None,
+            None => Some(instruction::init_history(
+                &self.program_id,
+                &self.payer.pubkey(),
+                asset_id,
+            )),
+        })
+    }
+
+    fn init_reputation_if_missing(&self, wallet: &Pubkey) -> CliResult<Option<Instruction>> {
+        let (reputation_acc, _) = Reputation::find_address(&self.program_id, wallet);
+
+        let reputation = self
+            .rpc
+            .get_account_with_commitment(&reputation_acc, self.rpc.commitment())?
+            .value;
+
+        Ok(match reputation {
+            Some(_) => None,
+            None => Some(instruction::init_reputation(
+                &self.program_id,
+                wallet,
+                &self.payer.pubkey(),
+            )),
+        })
+    }
+
+    fn init_game_acc(&self) -> CliResult<()> {
+        let game_acc = Keypair::new();
+
+        let instructions = [
+            self.create_program_acc(&game_acc, GameAcc::LEN, 0)?,
+            instruction::init_game_acc(&self.program_id, &game_acc.pubkey(), &self.payer.pubkey()),
+        ];
+        let signature = self.send(&instructions, &[&game_acc])?;
+
+        println!("Game Program A/c {}", game_acc.pubkey());
+        println!("Signature {}", signature);
+
+        Ok(())
+    }
+
+    fn add_asset(&self, game_acc: &Pubkey, asset_id: u64) -> CliResult<()> {
+        let signature = self.send(
+            &[instruction::add_asset(&self.program_id, game_acc, asset_id)],
+            &[],
+        )?;
+
+        println!("Signature {}", signature);
+
+        Ok(())
+    }
+
+    fn init_escrow(
+        &self,
+        game_acc: &Pubkey,
+        market: Option<&Pubkey>,
+        allowed_taker: &[Pubkey],
+        data: InitEscData,
+    ) -> CliResult<()> {
+        if allowed_taker.len() > ALLOWED_TAKERS_LEN {
+            return Err(format!("at most {} allowed takers", ALLOWED_TAKERS_LEN).into());
+        }
+
+        let mut data = data;
+        data.allowed_takers[..allowed_taker.len()].copy_from_slice(allowed_taker);
+
+        let escrow_acc = Keypair::new();
+
+        let instructions = [
+            self.create_program_acc(&escrow_acc, Escrow::LEN, data.amount)?,
+            instruction::init_escrow(
+                &self.program_id,
+                &escrow_acc.pubkey(),
+                game_acc,
+                &self.payer.pubkey(),
+                market,
+                data,
+            ),
+        ];
+        let signature = self.send(&instructions, &[&escrow_acc])?;
+
+        println!("Escrow A/c {}", escrow_acc.pubkey());
+        println!("Signature {}", signature);
+
+        Ok(())
+    }
+
+    fn take(
+        &self,
+        escrow_acc: &Pubkey,
+        game_acc: &Pubkey,
+        asset_id: u64,
+        max_price: Option<u64>,
+        arbiter: Option<Pubkey>,
+        oracle: Option<Pubkey>,
+    ) -> CliResult<()> {
+        let escrow = self.fetch_escrow(escrow_acc)?;
+
+        // the taker agrees to the arbiter and oracle the initialiser's market named
+        let arbiter = agreed_key("arbiter", &escrow.arbiter, arbiter)?;
+        let oracle = agreed_key("oracle", &escrow.oracle, oracle)?;
+
+        let (extra_acc, max_price) = match escrow.kind {
+            ESCROW_KIND_ENGLISH_AUCTION => {
+                return Err("english auctions are taken by bidding".into());
+            }
+            ESCROW_KIND_DUTCH_AUCTION => {
+                let max_price = max_price.ok_or("--max-price is needed for a dutch auction")?;
+
+                (Some(escrow.initialiser_main_acc), max_price)
+            }
+            _ if escrow.market != Pubkey::default() => (Some(escrow.market), 0),
+            _ => (None, 0),
+        };
+
+        // a collection bid is taken with the given asset
+        let taken_asset_id = if asset_id != 0 {
+            asset_id
+        } else {
+            escrow.asset_id
+        };
+
+        let take = instruction::take_escrow(
+            &self.program_id,
+            &self.payer.pubkey(),
+            escrow_acc,
+            &escrow.initialiser_game_acc,
+            game_acc,
+            extra_acc.as_ref(),
+            TakeEscData {
+                asset_id,
+                max_price,
+                arbiter,
+                oracle,
+            },
+        );
+
+        let mut instructions: Vec<Instruction> = self
+            .init_history_if_missing(taken_asset_id)?
+            .into_iter()
+            .collect();
+        let take = instruction::with_history(take, taken_asset_id);
+
+        // the program reads the taker's score from their Reputation A/c
+        let take = if escrow.min_reputation > 0 {
+            instruction::with_reputation(take, &self.payer.pubkey())
+        } else {
+            take
+        };
+        instructions.push(take);
+
+        let signature = self.send(&instructions, &[])?;
+
+        println!("Signature {}", signature);
+
+        Ok(())
+    }
+
+    fn revert(&self, escrow_acc: &Pubkey) -> CliResult<()> {
+        let escrow = self.fetch_escrow(escrow_acc)?;
+
+        let keeper_acc = self.payer.pubkey();
+
+        // escrows taken before the taker's main A/c was recorded accept any A/c there
+        let taker_main_acc = if escrow.taker_main_acc != Pubkey::default() {
+            escrow.taker_main_acc
+        } else {
+            keeper_acc
+        };
+
+        let revert = instruction::revert(
+            &self.program_id,
+            &escrow.initialiser_main_acc,
+            escrow_acc,
+            &escrow.initialiser_game_acc,
+            &escrow.taker_game_acc,
+            &taker_main_acc,
+            &keeper_acc,
+        );
+
+        let signature = self.send(&[instruction::with_history(revert, escrow.asset_id)], &[])?;
+
+        println!("Signature {}", signature);
+
+        Ok(())
+    }
+
+    fn cancel(&self, escrow_acc: &Pubkey) -> CliResult<()> {
+        let escrow = self.fetch_escrow(escrow_acc)?;
+
+        let extra_acc = if !escrow.initialiser_is_renter() {
+            Some(&escrow.initialiser_game_acc)
+        } else if escrow.market != Pubkey::default() {
+            Some(&escrow.market)
+        } else {
+            None
+        };
+
+        let initialiser_signs = self.payer.pubkey() == escrow.initialiser_main_acc;
+
+        // a cancel before expiry counts against the initialiser's Reputation A/c, so it must exist
+        let mut instructions = Vec::new();
+        if initialiser_signs && !escrow.is_expired(self.unix_timestamp()?) {
+            instructions.extend(self.init_reputation_if_missing(&escrow.initialiser_main_acc)?);
+        }
+        instructions.push(instruction::cancel_escrow(
+            &self.program_id,
+            escrow_acc,
+            &escrow.initialiser_main_acc,
+            initialiser_signs,
+            extra_acc,
+        ));
+
+        let signature = self.send(&instructions, &[])?;
+
+        println!("Signature {}", signature);
+
+        Ok(())
+    }
+}
+
+// the key the wallet agreed to for the escrow's arbiter or oracle, it must be the escrow's
+fn agreed_key(name: &str, escrow_key: &Pubkey, given: Option<Pubkey>) -> CliResult<Pubkey> {
+    let given = given.unwrap_or_default();
+
+    if given == *escrow_key {
+        Ok(given)
+    } else if *escrow_key == Pubkey::default() {
+        Err(format!("the escrow has no {0}, leave out --{0}", name).into())
+    } else {
+        Err(format!(
+            "the escrow's {0} is {1}, pass --{0} {1} to agree to it",
+            name, escrow_key
+        )
+        .into())
+    }
+}
+
+fn show(rpc: &RpcClient, program_id: &Pubkey, address: &Pubkey, json: bool) -> CliResult<()> {
+    let account = rpc.get_account(address)?;
+
+    print!("{}", show::render(program_id, address, &account, json)?);
+
+    Ok(())
+}
+
+fn run(cli: Cli) -> CliResult<()> {
+    let Cli {
+        keypair,
+        url,
+        program_id,
+        command,
+    } = cli;
+
+    let rpc = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+
+    // decoding an account doesn't need the wallet, every other command signs with it
+    let client = |rpc| Client::new(rpc, &keypair, program_id);
+
+    match command {
+        Command::Show { address, json } => show(&rpc, &program_id, &address, json),
+
+        Command::InitGameAcc => client(rpc)?.init_game_acc(),
+
+        Command::AddAsset { game_acc, asset_id } => client(rpc)?.add_asset(&game_acc, asset_id),
+
+        Command::InitEscrow {
+            game_acc,
+            asset_id,
+            amount,
+            time,
+            expires_at,
+            allowed_taker,
+            asset_id_max,
+            min_reputation,
+            market,
+        } => client(rpc)?.init_escrow(
+            &game_acc,
+            market.as_ref(),
+            &allowed_taker,
+            InitEscData {
+                amount,
+                time,
+                asset_id,
+                allowed_takers: [Pubkey::default(); ALLOWED_TAKERS_LEN],
+                expires_at,
+                asset_id_max,
+                min_reputation,
+            },
+        ),
+
+        Command::Take {
+            escrow,
+            game_acc,
+            asset_id,
+            max_price,
+            arbiter,
+            oracle,
+        } => client(rpc)?.take(&escrow, &game_acc, asset_id, max_price, arbiter, oracle),
+
+        Command::Revert { escrow } => client(rpc)?.revert(&escrow),
+
+        Command::Cancel { escrow } => client(rpc)?.cancel(&escrow),
+    }
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,161 @@
+use game_nest::state::{
+    Escrow, GameAcc, ESCROW_KIND_DUTCH_AUCTION, ESCROW_KIND_ENGLISH_AUCTION,
+    ESCROW_KIND_RENT_REQUEST,
+};
+use serde_json::{json, Map, Value};
+use solana_sdk::{account::Account, program_pack::Pack, pubkey::Pubkey};
+use std::error::Error;
+
+// fields in the order they are printed, a Pubkey::default() or 0 that means "not set" is null
+type Fields = Vec<(&'static str, Value)>;
+
+fn key(key: &Pubkey) -> Value {
+    if *key == Pubkey::default() {
+        Value::Null
+    } else {
+        json!(key.to_string())
+    }
+}
+
+fn timestamp(unix_timestamp: i64) -> Value {
+    if unix_timestamp == 0 {
+        Value::Null
+    } else {
+        json!(unix_timestamp)
+    }
+}
+
+fn assets(assets: &[u64]) -> Value {
+    json!(assets
+        .iter()
+        .filter(|&&asset_id| asset_id != 0)
+        .collect::<Vec<_>>())
+}
+
+fn kind(kind: u8) -> &'static str {
+    match kind {
+        ESCROW_KIND_RENT_REQUEST => "rent request",
+        ESCROW_KIND_ENGLISH_AUCTION => "english auction",
+        ESCROW_KIND_DUTCH_AUCTION => "dutch auction",
+        _ => "unknown",
+    }
+}
+
+fn escrow_fields(escrow: &Escrow) -> Fields {
+    let allowed_takers: Vec<Value> = escrow
+        .allowed_takers
+        .iter()
+        .map(key)
+        .filter(|key| !key.is_null())
+        .collect();
+
+    let mut fields = vec![
+        ("kind", json!(kind(escrow.kind))),
+        ("is_taken", json!(escrow.is_taken)),
+        ("initialiser_main_acc", key(&escrow.initialiser_main_acc)),
+        ("initialiser_game_acc", key(&escrow.initialiser_game_acc)),
+        ("taker_main_acc", key(&escrow.taker_main_acc)),
+        ("taker_game_acc", key(&escrow.taker_game_acc)),
+        ("asset_id", json!(escrow.asset_id)),
+        ("asset_id_max", json!(escrow.asset_id_max)),
+        ("amount", json!(escrow.amount)),
+        ("time", json!(escrow.time)),
+        ("esc_taken_time", timestamp(escrow.esc_taken_time)),
+        ("expires_at", timestamp(escrow.expires_at)),
+        ("allowed_takers", Value::Array(allowed_takers)),
+        ("min_reputation", json!(escrow.min_reputation)),
+        ("market", key(&escrow.market)),
+        ("arbiter", key(&escrow.arbiter)),
+        ("disputed_at", timestamp(escrow.disputed_at)),
+        ("oracle", key(&escrow.oracle)),
+        ("delivered_at", timestamp(escrow.delivered_at)),
+    ];
+
+    if escrow.is_taken && escrow.kind == ESCROW_KIND_RENT_REQUEST {
+        fields.push(("due_at", json!(escrow.due_at(escrow.esc_taken_time))));
+    }
+
+    match escrow.kind {
+        ESCROW_KIND_ENGLISH_AUCTION => {
+            fields.push(("min_increment", json!(escrow.min_increment)));
+            fields.push(("end_time", json!(escrow.end_time)));
+        }
+        ESCROW_KIND_DUTCH_AUCTION => {
+            fields.push(("start_price", json!(escrow.start_price)));
+            fields.push(("end_price", json!(escrow.end_price)));
+            fields.push(("start_time", json!(escrow.start_time)));
+            fields.push(("end_time", json!(escrow.end_time)));
+        }
+        _ => {}
+    }
+
+    fields
+}
+
+fn game_acc_fields(game_acc: &GameAcc) -> Fields {
+    vec![
+        ("userspace_owner", key(&game_acc.userspace_owner)),
+        ("owned", assets(&game_acc.owned)),
+        ("rented", assets(&game_acc.rented)),
+        ("lent", assets(&game_acc.lent)),
+        ("locked_by", key(&game_acc.locked_by)),
+    ]
+}
+
+fn text_value(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        Value::Array(values) if values.is_empty() => "-".to_string(),
+        Value::Array(values) => values.iter().map(text_value).collect::<Vec<_>>().join(", "),
+        value => value.to_string(),
+    }
+}
+
+// decodes an Escrow or GameAcc account of the program as text, or as JSON when json is set
+pub fn render(
+    program_id: &Pubkey,
+    address: &Pubkey,
+    account: &Account,
+    json: bool,
+) -> Result<String, Box<dyn Error>> {
+    if account.owner != *program_id {
+        return Err(format!("{} is not owned by the program", address).into());
+    }
+
+    let (acc_type, fields) = match account.data.first() {
+        Some(1) => ("escrow", escrow_fields(&Escrow::unpack(&account.data)?)),
+        Some(2) => (
+            "game_acc",
+            game_acc_fields(&GameAcc::unpack(&account.data)?),
+        ),
+        _ => return Err(format!("{} is not an Escrow or Game Program A/c", address).into()),
+    };
+
+    if json {
+        let mut map = Map::new();
+        map.insert("address".to_string(), json!(address.to_string()));
+        map.insert("type".to_string(), json!(acc_type));
+        map.insert("lamports".to_string(), json!(account.lamports));
+        map.extend(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value)),
+        );
+
+        return Ok(serde_json::to_string_pretty(&Value::Object(map))?);
+    }
+
+    let title = if acc_type == "escrow" {
+        "Escrow A/c"
+    } else {
+        "Game Program A/c"
+    };
+
+    let mut text = format!("{} {}\n  lamports: {}\n", title, address, account.lamports);
+    for (name, value) in fields.iter() {
+        text.push_str(&format!("  {}: {}\n", name, text_value(value)));
+    }
+
+    Ok(text)
+}
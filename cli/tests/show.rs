@@ -0,0 +1,142 @@
+use game_nest::state::{Escrow, GameAcc, ESCROW_KIND_RENT_REQUEST};
+use gamenest_cli::show::render;
+use serde_json::Value;
+use solana_sdk::{account::Account, program_pack::Pack, pubkey::Pubkey};
+
+fn key(n: u8) -> Pubkey {
+    Pubkey::new_from_array([n; 32])
+}
+
+fn account<T: Pack>(program_id: &Pubkey, state: &T) -> Account {
+    let mut data = vec![0; T::LEN];
+    state.pack_into_slice(&mut data);
+
+    Account {
+        lamports: 5000,
+        data,
+        owner: *program_id,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// a rent request for asset 5 taken at 1000 for 60 minutes, with no market
+fn taken_rent_request() -> Escrow {
+    Escrow {
+        acc_type: 1,
+        kind: ESCROW_KIND_RENT_REQUEST,
+        is_taken: true,
+        initialiser_main_acc: key(1),
+        initialiser_game_acc: key(11),
+        taker_main_acc: key(2),
+        taker_game_acc: key(12),
+        esc_taken_time: 1000,
+        amount: 700,
+        time: 60,
+        asset_id: 5,
+        ..Escrow::default()
+    }
+}
+
+#[test]
+fn escrow_renders_as_text() {
+    let program_id = key(9);
+    let escrow = taken_rent_request();
+
+    let text = render(&program_id, &key(21), &account(&program_id, &escrow), false).unwrap();
+
+    assert!(text.starts_with(&format!("Escrow A/c {}\n  lamports: 5000\n", key(21))));
+    assert!(text.contains("  kind: rent request\n"));
+    assert!(text.contains(&format!("  taker_main_acc: {}\n", key(2))));
+    assert!(text.contains("  amount: 700\n"));
+    assert!(text.contains("  market: -\n"));
+    assert!(text.contains("  allowed_takers: -\n"));
+    assert!(text.contains(&format!("  due_at: {}\n", escrow.due_at(1000))));
+}
+
+#[test]
+fn escrow_renders_as_json() {
+    let program_id = key(9);
+    let escrow = taken_rent_request();
+
+    let json = render(&program_id, &key(21), &account(&program_id, &escrow), true).unwrap();
+    let value: Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(value["address"], key(21).to_string());
+    assert_eq!(value["type"], "escrow");
+    assert_eq!(value["lamports"], 5000);
+    assert_eq!(value["initialiser_main_acc"], key(1).to_string());
+    assert_eq!(value["asset_id"], 5);
+    assert_eq!(value["esc_taken_time"], 1000);
+    assert_eq!(value["arbiter"], Value::Null);
+    assert_eq!(value["expires_at"], Value::Null);
+    assert_eq!(value["due_at"], escrow.due_at(1000));
+}
+
+#[test]
+fn game_acc_lists_only_held_assets() {
+    let program_id = key(9);
+    let mut game_acc = GameAcc {
+        acc_type: 2,
+        userspace_owner: key(1),
+        ..GameAcc::default()
+    };
+    game_acc.owned[0] = 3;
+    game_acc.owned[4] = 8;
+    game_acc.lent[1] = 5;
+
+    let text = render(
+        &program_id,
+        &key(11),
+        &account(&program_id, &game_acc),
+        false,
+    )
+    .unwrap();
+
+    assert!(text.starts_with(&format!("Game Program A/c {}\n", key(11))));
+    assert!(text.contains("  owned: 3, 8\n"));
+    assert!(text.contains("  rented: -\n"));
+    assert!(text.contains("  lent: 5\n"));
+    assert!(text.contains("  locked_by: -\n"));
+
+    let json = render(
+        &program_id,
+        &key(11),
+        &account(&program_id, &game_acc),
+        true,
+    )
+    .unwrap();
+    let value: Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(value["type"], "game_acc");
+    assert_eq!(value["owned"], serde_json::json!([3, 8]));
+}
+
+#[test]
+fn account_of_another_program_is_rejected() {
+    let escrow = taken_rent_request();
+
+    let err = render(&key(9), &key(21), &account(&key(8), &escrow), false).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        format!("{} is not owned by the program", key(21))
+    );
+}
+
+// an Offer A/c, whose header says acc_type 3
+#[test]
+fn other_account_types_are_rejected() {
+    let program_id = key(9);
+    let offer = Account {
+        data: vec![3; 16],
+        ..account(&program_id, &taken_rent_request())
+    };
+
+    let err = render(&program_id, &key(31), &offer, false).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        format!("{} is not an Escrow or Game Program A/c", key(31))
+    );
+}
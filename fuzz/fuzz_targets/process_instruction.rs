@@ -0,0 +1,171 @@
+#![no_main]
+
+use game_nest::processor::Processor;
+use libfuzzer_sys::{arbitrary, fuzz_target};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{deserialize, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER, SUCCESS},
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program,
+};
+use std::{
+    collections::HashSet,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Once,
+    },
+};
+
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([0xff; 32]);
+
+// accounts are drawn from a small pool of keys so the fuzzer can easily
+// find the keys stored in account data, key 0 is the System Program's
+const KEY_POOL: u8 = 16;
+
+static UNIX_TIMESTAMP: AtomicI64 = AtomicI64::new(0);
+static STUBS: Once = Once::new();
+
+// stands in for the runtime, sysvars come from the fuzz input and CPIs do nothing
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_log_data(&self, _fields: &[&[u8]]) {}
+
+    fn sol_invoke_signed(
+        &self,
+        _instruction: &Instruction,
+        _account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> Result<(), ProgramError> {
+        Ok(())
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: UNIX_TIMESTAMP.load(Ordering::Relaxed),
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+}
+
+#[derive(arbitrary::Arbitrary, Debug)]
+struct FuzzAccount {
+    key: u8,
+    owned_by_program: bool,
+    is_signer: bool,
+    is_writable: bool,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+#[derive(arbitrary::Arbitrary, Debug)]
+struct FuzzInput {
+    unix_timestamp: i64,
+    accounts: Vec<FuzzAccount>,
+    instruction_data: Vec<u8>,
+}
+
+// lays the accounts out the way the loader passes them to the entrypoint, so that
+// realloc has room to grow into, a key given twice becomes a duplicate of the first
+fn serialize(input: &FuzzInput) -> Vec<u64> {
+    let mut buf = Vec::new();
+    let mut keys = Vec::new();
+
+    buf.extend_from_slice(&(input.accounts.len() as u64).to_le_bytes());
+
+    for account in input.accounts.iter() {
+        let key = Pubkey::new_from_array([account.key % KEY_POOL; 32]);
+
+        if let Some(index) = keys.iter().position(|k| *k == key) {
+            buf.push(index as u8);
+            buf.extend_from_slice(&[0; 7]);
+            keys.push(key);
+            continue;
+        }
+        keys.push(key);
+
+        let owner = if account.owned_by_program {
+            PROGRAM_ID
+        } else {
+            system_program::id()
+        };
+
+        buf.push(NON_DUP_MARKER);
+        buf.push(account.is_signer as u8);
+        buf.push(account.is_writable as u8);
+        buf.push(0); // executable
+        buf.extend_from_slice(&[0; 4]); // original data len, filled by deserialize
+        buf.extend_from_slice(key.as_ref());
+        buf.extend_from_slice(owner.as_ref());
+        buf.extend_from_slice(&account.lamports.to_le_bytes());
+        buf.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+        buf.extend_from_slice(&account.data);
+        buf.resize(buf.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+        buf.resize(buf.len().div_ceil(8) * 8, 0);
+        buf.extend_from_slice(&0u64.to_le_bytes()); // rent epoch
+    }
+
+    buf.extend_from_slice(&(input.instruction_data.len() as u64).to_le_bytes());
+    buf.extend_from_slice(&input.instruction_data);
+    buf.extend_from_slice(PROGRAM_ID.as_ref());
+
+    // the loader's input is 8 byte aligned
+    buf.chunks(8)
+        .map(|chunk| {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u64::from_ne_bytes(word)
+        })
+        .collect()
+}
+
+// lamports held by the distinct accounts, duplicates share the same lamports
+fn total_lamports(accounts: &[AccountInfo]) -> u128 {
+    let mut seen = HashSet::new();
+
+    accounts
+        .iter()
+        .filter(|acc| seen.insert(Rc::as_ptr(&acc.lamports)))
+        .map(|acc| acc.lamports() as u128)
+        .sum()
+}
+
+fuzz_target!(|input: FuzzInput| {
+    // the loader can't pass more accounts than the dup marker can index
+    if input.accounts.len() >= NON_DUP_MARKER as usize {
+        return;
+    }
+
+    STUBS.call_once(|| {
+        set_syscall_stubs(Box::new(Stubs));
+    });
+    UNIX_TIMESTAMP.store(input.unix_timestamp, Ordering::Relaxed);
+
+    let mut buf = serialize(&input);
+    let (program_id, accounts, instruction_data) =
+        unsafe { deserialize(buf.as_mut_ptr() as *mut u8) };
+
+    let before = total_lamports(&accounts);
+
+    let result = Processor::process_instruction(program_id, &accounts, instruction_data);
+
+    // lamports only ever move between the instruction's accounts, as the runtime enforces
+    if result.is_ok() {
+        assert_eq!(total_lamports(&accounts), before);
+    }
+});
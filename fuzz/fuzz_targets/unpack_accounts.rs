@@ -0,0 +1,26 @@
+#![no_main]
+
+use game_nest::state::{Escrow, GameAcc, History, Market, Offer, Reputation};
+use libfuzzer_sys::fuzz_target;
+use solana_program::program_pack::Pack;
+
+// whatever unpacks must pack back to the same account
+fn round_trip<T: Pack + PartialEq + std::fmt::Debug>(data: &[u8]) {
+    let _ = T::unpack_unchecked(data);
+
+    if let Ok(account) = T::unpack_from_slice(data) {
+        let mut packed = vec![0; T::LEN];
+        account.pack_into_slice(&mut packed);
+
+        assert_eq!(T::unpack_from_slice(&packed).unwrap(), account);
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    round_trip::<Escrow>(data);
+    round_trip::<GameAcc>(data);
+    round_trip::<Offer>(data);
+    round_trip::<Market>(data);
+    round_trip::<History>(data);
+    round_trip::<Reputation>(data);
+});
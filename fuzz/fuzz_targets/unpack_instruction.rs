@@ -0,0 +1,13 @@
+#![no_main]
+
+use game_nest::instruction::InstructionType;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(instruction) = InstructionType::unpack(data) {
+        assert_eq!(
+            InstructionType::unpack(&instruction.pack()),
+            Ok(instruction)
+        );
+    }
+});
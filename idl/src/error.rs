@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum IdlError {
+    #[error("Can't read {0}: {1}")]
+    Io(String, std::io::Error),
+
+    #[error("Can't parse {0}: {1}")]
+    Syntax(String, syn::Error),
+
+    #[error("Unsupported {0}")]
+    Unsupported(String),
+}
@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+
+// the program's interface as clients see it, everything in it is read from the program's sources
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Idl {
+    pub name: String,
+    pub version: String,
+    pub account_header: AccountHeader,
+    pub constants: Vec<Constant>,
+    pub instructions: Vec<Instruction>,
+    pub accounts: Vec<Account>,
+    pub types: Vec<TypeDef>,
+    pub errors: Vec<ErrorCode>,
+}
+
+// every account starts with [acc_type, version] followed by its Borsh serialized fields
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountHeader {
+    pub len: usize,
+    pub version: u8,
+    pub closed_acc_type: u8,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Constant {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String, // the Rust type, "bytes" for a byte string
+    pub value: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Instruction {
+    pub name: String,
+    pub discriminant: u8,
+    pub docs: Vec<String>,
+    pub accounts: Vec<InstructionAccount>,
+    pub args: Vec<Field>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InstructionAccount {
+    pub name: String,
+    pub writable: bool,
+    pub signer: Signer,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub optional: bool, // only passed when given, after the accounts that are always passed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>, // fixed address like the System Program's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Signer {
+    Fixed(bool),
+    Flag(String), // name of the builder argument deciding if the account signs
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Account {
+    pub name: String,
+    pub discriminant: u8, // acc_type in the account header
+    pub size: usize,      // header included
+    pub fields: Vec<Field>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TypeDef {
+    pub name: String,
+    pub size: usize,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Field {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: Type,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Type {
+    Primitive(Primitive),
+    Array { array: (Box<Type>, usize) },
+    Defined { defined: String },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Primitive {
+    Bool,
+    U8,
+    U64,
+    I64,
+    PublicKey,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ErrorCode {
+    pub code: u32,
+    pub name: String,
+    pub msg: String,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl Primitive {
+    pub fn size(self) -> usize {
+        match self {
+            Primitive::Bool | Primitive::U8 => 1,
+            Primitive::U64 | Primitive::I64 => 8,
+            Primitive::PublicKey => 32,
+        }
+    }
+}
+
+impl Type {
+    // serialized size, types are looked up by name for Defined
+    pub fn size(&self, types: &[TypeDef]) -> usize {
+        match self {
+            Type::Primitive(primitive) => primitive.size(),
+            Type::Array { array: (item, len) } => item.size(types) * len,
+            Type::Defined { defined } => types
+                .iter()
+                .find(|ty| ty.name == *defined)
+                .map_or(0, |ty| ty.size),
+        }
+    }
+}
+
+pub fn fields_size(fields: &[Field], types: &[TypeDef]) -> usize {
+    fields.iter().map(|field| field.ty.size(types)).sum()
+}
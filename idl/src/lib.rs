@@ -0,0 +1,35 @@
+// reads the program's interface from its Rust sources into an IDL, and generates the
+// TypeScript client of the UI from it so the two can't drift from the on-chain format
+pub mod error;
+pub mod idl;
+pub mod parse;
+pub mod typescript;
+
+use std::path::Path;
+
+pub use error::IdlError;
+pub use idl::Idl;
+
+// paths of the generated files relative to the repository root
+pub const IDL_PATH: &str = "idl/game_nest.json";
+pub const CLIENT_PATH: &str = "UI/utility_files/game_nest.ts";
+
+pub struct Generated {
+    pub idl: Idl,
+    pub json: String,
+    pub typescript: String,
+}
+
+pub fn generate(root: &Path) -> Result<Generated, IdlError> {
+    let idl = parse::parse(root)?;
+    let mut json =
+        serde_json::to_string_pretty(&idl).map_err(|err| IdlError::Unsupported(err.to_string()))?;
+    json.push('\n');
+    let typescript = typescript::generate(&idl);
+
+    Ok(Generated {
+        idl,
+        json,
+        typescript,
+    })
+}
@@ -0,0 +1,29 @@
+use std::fs;
+use std::path::Path;
+use std::process;
+
+// regenerates the IDL and the TypeScript client from the program's sources
+fn main() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("idl crate is in the repository");
+
+    let generated = match gamenest_idl::generate(root) {
+        Ok(generated) => generated,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        }
+    };
+
+    for (path, contents) in [
+        (gamenest_idl::IDL_PATH, &generated.json),
+        (gamenest_idl::CLIENT_PATH, &generated.typescript),
+    ] {
+        if let Err(err) = fs::write(root.join(path), contents) {
+            eprintln!("Error: Can't write {}: {}", path, err);
+            process::exit(1);
+        }
+        println!("wrote {}", path);
+    }
+}
@@ -0,0 +1,751 @@
+use solana_program::{system_program, sysvar};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+use syn::{
+    punctuated::Punctuated,
+    visit::{self, Visit},
+    Attribute, BinOp, Expr, ExprLit, Fields, File, ImplItem, Item, ItemStruct, Lit, Meta, Pat,
+    ReturnType, Stmt, Token, Type as SynType, UnOp,
+};
+
+use crate::error::IdlError;
+use crate::idl::{
+    fields_size, Account, AccountHeader, Constant, ErrorCode, Field, Idl, Instruction,
+    InstructionAccount, Primitive, Signer, Type, TypeDef,
+};
+
+const PROGRAM_NAME: &str = "game_nest";
+
+fn unsupported(what: String) -> IdlError {
+    IdlError::Unsupported(what)
+}
+
+fn read_file(path: &Path) -> Result<String, IdlError> {
+    fs::read_to_string(path).map_err(|err| IdlError::Io(path.display().to_string(), err))
+}
+
+fn parse_file(path: &Path) -> Result<File, IdlError> {
+    syn::parse_file(&read_file(path)?)
+        .map_err(|err| IdlError::Syntax(path.display().to_string(), err))
+}
+
+// the [package] version of the program's Cargo.toml
+fn package_version(manifest: &str) -> Option<String> {
+    manifest
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("version"))
+        .and_then(|line| line.split('"').nth(1))
+        .map(str::to_string)
+}
+
+fn eval(expr: &Expr, consts: &HashMap<String, i128>) -> Option<i128> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) => int.base10_parse().ok(),
+        Expr::Path(path) => path
+            .path
+            .get_ident()
+            .and_then(|ident| consts.get(&ident.to_string()).copied()),
+        Expr::Paren(paren) => eval(&paren.expr, consts),
+        Expr::Binary(binary) => {
+            let left = eval(&binary.left, consts)?;
+            let right = eval(&binary.right, consts)?;
+            match binary.op {
+                BinOp::Add(_) => left.checked_add(right),
+                BinOp::Sub(_) => left.checked_sub(right),
+                BinOp::Mul(_) => left.checked_mul(right),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn last_ident(path: &syn::Path) -> String {
+    path.segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+        .unwrap_or_default()
+}
+
+fn derives_borsh(item: &ItemStruct) -> bool {
+    item.attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .any(|paths| {
+            paths
+                .iter()
+                .any(|path| last_ident(path) == "BorshSerialize")
+        })
+}
+
+// #[borsh(skip)] fields like acc_type live in the account header instead
+fn borsh_skipped(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("borsh")
+            && attr
+                .meta
+                .require_list()
+                .is_ok_and(|list| list.tokens.to_string() == "skip")
+    })
+}
+
+fn doc_lines(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(doc) if doc.path.is_ident("doc") => match &doc.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(line),
+                    ..
+                }) => Some(line.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| {
+            line.strip_prefix(' ')
+                .unwrap_or(&line)
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+// an "N. [flags] text" line documenting the N-th account of an instruction
+fn account_line(line: &str) -> Option<(usize, String)> {
+    let line = line.trim_start();
+    let dot = line.find('.')?;
+    let index = line[..dot].parse().ok()?;
+    let rest = line[dot + 1..].trim_start().strip_prefix('[')?;
+    let close = rest.find(']')?;
+
+    Some((index, rest[close + 1..].trim().to_string()))
+}
+
+// splits an instruction's doc comment into its description and the text of each account line,
+// an indented line right after an account line continues it
+fn split_docs(lines: Vec<String>) -> (Vec<String>, HashMap<usize, String>) {
+    let mut description: Vec<String> = Vec::new();
+    let mut accounts: HashMap<usize, String> = HashMap::new();
+    let mut last_account = None;
+
+    for line in lines {
+        if let Some((index, text)) = account_line(&line) {
+            accounts.insert(index, text);
+            last_account = Some(index);
+            continue;
+        }
+
+        if let Some(index) = last_account {
+            if line.starts_with(' ') && !line.trim().is_empty() {
+                if let Some(text) = accounts.get_mut(&index) {
+                    text.push(' ');
+                    text.push_str(line.trim());
+                }
+                continue;
+            }
+        }
+
+        last_account = None;
+        description.push(line);
+    }
+
+    while description.first().is_some_and(|line| line.is_empty()) {
+        description.remove(0);
+    }
+    while description.last().is_some_and(|line| line.is_empty()) {
+        description.pop();
+    }
+    description.dedup_by(|line, prev| line.is_empty() && prev.is_empty());
+
+    (description, accounts)
+}
+
+struct Context {
+    consts: HashMap<String, i128>,
+    structs: HashMap<String, ItemStruct>, // Borsh structs of state.rs
+}
+
+impl Context {
+    fn idl_type(&self, ty: &SynType) -> Result<Type, IdlError> {
+        match ty {
+            SynType::Path(path) => {
+                let ident = last_ident(&path.path);
+                Ok(match ident.as_str() {
+                    "bool" => Type::Primitive(Primitive::Bool),
+                    "u8" => Type::Primitive(Primitive::U8),
+                    "u64" => Type::Primitive(Primitive::U64),
+                    "i64" => Type::Primitive(Primitive::I64),
+                    "Pubkey" => Type::Primitive(Primitive::PublicKey),
+                    _ if self.structs.contains_key(&ident) => Type::Defined { defined: ident },
+                    _ => return Err(unsupported(format!("type {}", ident))),
+                })
+            }
+            SynType::Array(array) => {
+                let len = eval(&array.len, &self.consts)
+                    .and_then(|len| usize::try_from(len).ok())
+                    .ok_or_else(|| unsupported("array length".to_string()))?;
+                Ok(Type::Array {
+                    array: (Box::new(self.idl_type(&array.elem)?), len),
+                })
+            }
+            _ => Err(unsupported("type".to_string())),
+        }
+    }
+
+    fn fields(&self, item: &ItemStruct) -> Result<Vec<Field>, IdlError> {
+        let fields = match &item.fields {
+            Fields::Named(fields) => fields,
+            _ => return Err(unsupported(format!("struct {}", item.ident))),
+        };
+
+        fields
+            .named
+            .iter()
+            .filter(|field| !borsh_skipped(&field.attrs))
+            .map(|field| {
+                Ok(Field {
+                    name: field
+                        .ident
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                    ty: self.idl_type(&field.ty)?,
+                })
+            })
+            .collect()
+    }
+
+    fn state_fields(&self, name: &str) -> Result<Vec<Field>, IdlError> {
+        let item = self
+            .structs
+            .get(name)
+            .ok_or_else(|| unsupported(format!("account {}", name)))?;
+        self.fields(item)
+    }
+
+    // adds the Defined types used by fields, the ones they use first so their size is known
+    fn add_types(&self, fields: &[Field], types: &mut Vec<TypeDef>) -> Result<(), IdlError> {
+        for field in fields {
+            let mut ty = &field.ty;
+            while let Type::Array { array: (item, _) } = ty {
+                ty = item;
+            }
+
+            if let Type::Defined { defined } = ty {
+                if types.iter().any(|ty| ty.name == *defined) {
+                    continue;
+                }
+
+                let fields = self.state_fields(defined)?;
+                self.add_types(&fields, types)?;
+                types.push(TypeDef {
+                    name: defined.clone(),
+                    size: fields_size(&fields, types),
+                    fields,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn constants(state: &File, consts: &mut HashMap<String, i128>) -> Result<Vec<Constant>, IdlError> {
+    let mut constants = Vec::new();
+
+    for item in state.items.iter() {
+        let item = match item {
+            Item::Const(item) if matches!(item.vis, syn::Visibility::Public(_)) => item,
+            _ => continue,
+        };
+        let name = item.ident.to_string();
+
+        let (ty, value) = match (&*item.ty, &*item.expr) {
+            (
+                SynType::Reference(_),
+                Expr::Lit(ExprLit {
+                    lit: Lit::ByteStr(bytes),
+                    ..
+                }),
+            ) => (
+                "bytes".to_string(),
+                String::from_utf8(bytes.value())
+                    .map_err(|_| unsupported(format!("constant {}", name)))?,
+            ),
+            (SynType::Path(path), expr) => {
+                let value =
+                    eval(expr, consts).ok_or_else(|| unsupported(format!("constant {}", name)))?;
+                consts.insert(name.clone(), value);
+                (last_ident(&path.path), value.to_string())
+            }
+            _ => return Err(unsupported(format!("constant {}", name))),
+        };
+
+        constants.push(Constant { name, ty, value });
+    }
+
+    Ok(constants)
+}
+
+fn errors(error: &File) -> Result<Vec<ErrorCode>, IdlError> {
+    let item = error
+        .items
+        .iter()
+        .find_map(|item| match item {
+            Item::Enum(item) => Some(item),
+            _ => None,
+        })
+        .ok_or_else(|| unsupported("error.rs without an error enum".to_string()))?;
+
+    item.variants
+        .iter()
+        .enumerate()
+        .map(|(code, variant)| {
+            if variant.discriminant.is_some() {
+                return Err(unsupported(format!(
+                    "error discriminant of {}",
+                    variant.ident
+                )));
+            }
+
+            let msg = variant
+                .attrs
+                .iter()
+                .find(|attr| attr.path().is_ident("error"))
+                .and_then(|attr| attr.parse_args::<syn::LitStr>().ok())
+                .map(|msg| msg.value())
+                .unwrap_or_default();
+
+            Ok(ErrorCode {
+                code: code as u32,
+                name: variant.ident.to_string(),
+                msg,
+            })
+        })
+        .collect()
+}
+
+// acc_type of every ProgramAccount impl, in the order they are implemented
+fn acc_types(validation: &File, consts: &HashMap<String, i128>) -> Vec<(String, u8)> {
+    validation
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Impl(item) => Some(item),
+            _ => None,
+        })
+        .filter(|item| {
+            item.trait_
+                .as_ref()
+                .is_some_and(|(_, path, _)| last_ident(path) == "ProgramAccount")
+        })
+        .filter_map(|item| {
+            let name = match &*item.self_ty {
+                SynType::Path(path) => last_ident(&path.path),
+                _ => return None,
+            };
+            let acc_type = item.items.iter().find_map(|item| match item {
+                ImplItem::Const(item) if item.ident == "ACC_TYPE" => eval(&item.expr, consts),
+                _ => None,
+            })?;
+
+            Some((name, u8::try_from(acc_type).ok()?))
+        })
+        .collect()
+}
+
+// the first path starting with `prefix::` in what is visited
+struct VariantOf {
+    prefix: &'static str,
+    variant: Option<String>,
+}
+
+impl<'ast> Visit<'ast> for VariantOf {
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        if self.variant.is_none()
+            && path.segments.len() == 2
+            && path.segments[0].ident == self.prefix
+        {
+            self.variant = Some(path.segments[1].ident.to_string());
+        }
+        visit::visit_path(self, path);
+    }
+}
+
+fn variant_of(prefix: &'static str, visit: impl FnOnce(&mut VariantOf)) -> Option<String> {
+    let mut visitor = VariantOf {
+        prefix,
+        variant: None,
+    };
+    visit(&mut visitor);
+    visitor.variant
+}
+
+// tags of the `N => Self::Variant` arms of InstructionType::unpack
+#[derive(Default)]
+struct Discriminants(Vec<(String, u8)>);
+
+impl<'ast> Visit<'ast> for Discriminants {
+    fn visit_arm(&mut self, arm: &'ast syn::Arm) {
+        if let Pat::Lit(ExprLit {
+            lit: Lit::Int(tag), ..
+        }) = &arm.pat
+        {
+            let variant = variant_of("Self", |visitor| visitor.visit_expr(&arm.body));
+            if let (Some(variant), Ok(tag)) = (variant, tag.base10_parse()) {
+                if !self.0.iter().any(|(name, _)| *name == variant) {
+                    self.0.push((variant, tag));
+                }
+            }
+        }
+        visit::visit_arm(self, arm);
+    }
+}
+
+// names the `Self::Variant(value) =>` arms of InstructionType::pack give a single value
+#[derive(Default)]
+struct Bindings(HashMap<String, String>);
+
+impl<'ast> Visit<'ast> for Bindings {
+    fn visit_arm(&mut self, arm: &'ast syn::Arm) {
+        if let Pat::TupleStruct(pat) = &arm.pat {
+            if let (2, Some(Pat::Ident(binding))) = (pat.path.segments.len(), pat.elems.first()) {
+                if pat.path.segments[0].ident == "Self" && pat.elems.len() == 1 {
+                    self.0.insert(
+                        pat.path.segments[1].ident.to_string(),
+                        binding.ident.to_string(),
+                    );
+                }
+            }
+        }
+        visit::visit_arm(self, arm);
+    }
+}
+
+fn expr_ident(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Path(path) => path.path.get_ident().map(ToString::to_string),
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Deref(_)) => expr_ident(&unary.expr),
+        _ => None,
+    }
+}
+
+fn path_string(path: &syn::Path) -> String {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+// AccountMeta::new(*key, signer) or AccountMeta::new_readonly(*key, signer)
+fn account_meta(
+    call: &syn::ExprCall,
+    optional: bool,
+) -> Result<Option<InstructionAccount>, IdlError> {
+    let func = match &*call.func {
+        Expr::Path(func) => path_string(&func.path),
+        _ => return Ok(None),
+    };
+    let writable = match func.as_str() {
+        "AccountMeta::new" => true,
+        "AccountMeta::new_readonly" => false,
+        _ => return Ok(None),
+    };
+
+    let (key, signer) = match (call.args.first(), call.args.get(1)) {
+        (Some(key), Some(signer)) => (key, signer),
+        _ => return Err(unsupported(func)),
+    };
+
+    // accounts at a fixed address are named after it, the others after the builder's argument
+    let (name, address) = match key {
+        Expr::Call(id) => {
+            let (name, address) = match &*id.func {
+                Expr::Path(path) => match path_string(&path.path).as_str() {
+                    "system_program::id" => ("system_program", system_program::id()),
+                    "sysvar::instructions::id" => {
+                        ("instructions_sysvar", sysvar::instructions::id())
+                    }
+                    path => return Err(unsupported(format!("account {}", path))),
+                },
+                _ => return Err(unsupported("account".to_string())),
+            };
+            (name.to_string(), Some(address.to_string()))
+        }
+        key => (
+            expr_ident(key).ok_or_else(|| unsupported("account".to_string()))?,
+            None,
+        ),
+    };
+
+    let signer = match signer {
+        Expr::Lit(ExprLit {
+            lit: Lit::Bool(signer),
+            ..
+        }) => Signer::Fixed(signer.value),
+        signer => Signer::Flag(
+            expr_ident(signer).ok_or_else(|| unsupported(format!("signer of {}", name)))?,
+        ),
+    };
+
+    Ok(Some(InstructionAccount {
+        name,
+        writable,
+        signer,
+        optional,
+        address,
+        docs: None,
+    }))
+}
+
+// the accounts a builder passes, in order, the ones pushed inside an if are optional
+fn collect_stmts(
+    stmts: &[Stmt],
+    optional: bool,
+    accounts: &mut Vec<InstructionAccount>,
+) -> Result<(), IdlError> {
+    for stmt in stmts {
+        match stmt {
+            Stmt::Local(local) => {
+                if let Some(init) = &local.init {
+                    collect_expr(&init.expr, optional, accounts)?;
+                }
+            }
+            Stmt::Expr(expr, _) => collect_expr(expr, optional, accounts)?,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn collect_expr(
+    expr: &Expr,
+    optional: bool,
+    accounts: &mut Vec<InstructionAccount>,
+) -> Result<(), IdlError> {
+    match expr {
+        Expr::If(expr) => collect_stmts(&expr.then_branch.stmts, true, accounts)?,
+        Expr::Macro(expr) if expr.mac.path.is_ident("vec") => {
+            let items = expr
+                .mac
+                .parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+                .map_err(|err| IdlError::Syntax("vec!".to_string(), err))?;
+            for item in items.iter() {
+                collect_expr(item, optional, accounts)?;
+            }
+        }
+        Expr::MethodCall(expr) if expr.method == "push" => {
+            for arg in expr.args.iter() {
+                collect_expr(arg, optional, accounts)?;
+            }
+        }
+        Expr::Struct(expr) => {
+            for field in expr.fields.iter() {
+                collect_expr(&field.expr, optional, accounts)?;
+            }
+        }
+        Expr::Call(call) => {
+            if let Some(account) = account_meta(call, optional)? {
+                accounts.push(account);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+// accounts of each instruction as its first builder passes them
+fn builder_accounts(
+    instruction: &File,
+) -> Result<HashMap<String, Vec<InstructionAccount>>, IdlError> {
+    let mut builders = HashMap::new();
+
+    for item in instruction.items.iter() {
+        let item = match item {
+            Item::Fn(item) => item,
+            _ => continue,
+        };
+        let returns_instruction = match &item.sig.output {
+            ReturnType::Type(_, ty) => {
+                matches!(&**ty, SynType::Path(path) if path.path.is_ident("Instruction"))
+            }
+            ReturnType::Default => false,
+        };
+        if !returns_instruction {
+            continue;
+        }
+
+        let variant = match variant_of("InstructionType", |visitor| visitor.visit_item_fn(item)) {
+            Some(variant) => variant,
+            None => continue, // appends accounts to another builder's instruction
+        };
+        if builders.contains_key(&variant) {
+            continue;
+        }
+
+        let mut accounts = Vec::new();
+        collect_stmts(&item.block.stmts, false, &mut accounts)?;
+        builders.insert(variant, accounts);
+    }
+
+    Ok(builders)
+}
+
+fn instructions(instruction: &File, ctx: &Context) -> Result<Vec<Instruction>, IdlError> {
+    let mut data_structs = HashMap::new();
+    let mut instruction_type = None;
+    let mut discriminants = Discriminants::default();
+    let mut bindings = Bindings::default();
+
+    for item in instruction.items.iter() {
+        match item {
+            Item::Struct(item) => {
+                data_structs.insert(item.ident.to_string(), item);
+            }
+            Item::Enum(item) if item.ident == "InstructionType" => instruction_type = Some(item),
+            Item::Impl(item) if matches!(&*item.self_ty, SynType::Path(path) if path.path.is_ident("InstructionType")) => {
+                for item in item.items.iter() {
+                    match item {
+                        ImplItem::Fn(item) if item.sig.ident == "unpack" => {
+                            discriminants.visit_impl_item_fn(item)
+                        }
+                        ImplItem::Fn(item) if item.sig.ident == "pack" => {
+                            bindings.visit_impl_item_fn(item)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let instruction_type = instruction_type
+        .ok_or_else(|| unsupported("instruction.rs without InstructionType".to_string()))?;
+    let mut builders = builder_accounts(instruction)?;
+
+    instruction_type
+        .variants
+        .iter()
+        .map(|variant| {
+            let name = variant.ident.to_string();
+            let discriminant = discriminants
+                .0
+                .iter()
+                .find(|(variant, _)| *variant == name)
+                .map(|(_, tag)| *tag)
+                .ok_or_else(|| unsupported(format!("instruction {} without a tag", name)))?;
+
+            let args = match &variant.fields {
+                Fields::Unit => Vec::new(),
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    let ty = &fields.unnamed[0].ty;
+                    let data = match ty {
+                        SynType::Path(path) => data_structs.get(&last_ident(&path.path)),
+                        _ => None,
+                    };
+
+                    match data {
+                        Some(data) => ctx.fields(data)?,
+                        None => vec![Field {
+                            name: bindings.0.get(&name).cloned().ok_or_else(|| {
+                                unsupported(format!("argument of instruction {}", name))
+                            })?,
+                            ty: ctx.idl_type(ty)?,
+                        }],
+                    }
+                }
+                _ => return Err(unsupported(format!("instruction {}", name))),
+            };
+
+            let (docs, mut account_docs) = split_docs(doc_lines(&variant.attrs));
+            let mut accounts = builders
+                .remove(&name)
+                .ok_or_else(|| unsupported(format!("instruction {} without a builder", name)))?;
+            for (index, account) in accounts.iter_mut().enumerate() {
+                account.docs = account_docs.remove(&index);
+            }
+
+            Ok(Instruction {
+                name,
+                discriminant,
+                docs,
+                accounts,
+                args,
+            })
+        })
+        .collect()
+}
+
+// builds the IDL from the program's Cargo.toml and src/ under root
+pub fn parse(root: &Path) -> Result<Idl, IdlError> {
+    let src = root.join("src");
+    let version = package_version(&read_file(&root.join("Cargo.toml"))?)
+        .ok_or_else(|| unsupported("Cargo.toml without a version".to_string()))?;
+
+    let state = parse_file(&src.join("state.rs"))?;
+    let mut consts = HashMap::new();
+    let constants = constants(&state, &mut consts)?;
+
+    let header_const = |name: &str| {
+        consts
+            .get(name)
+            .copied()
+            .ok_or_else(|| unsupported(format!("state.rs without {}", name)))
+    };
+    let account_header = AccountHeader {
+        len: header_const("ACCOUNT_HEADER_LEN")? as usize,
+        version: header_const("ACCOUNT_VERSION")? as u8,
+        closed_acc_type: header_const("CLOSED_ACC_TYPE")? as u8,
+    };
+
+    let structs = state
+        .items
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::Struct(item) if derives_borsh(&item) => Some((item.ident.to_string(), item)),
+            _ => None,
+        })
+        .collect();
+    let ctx = Context { consts, structs };
+
+    let mut types = Vec::new();
+    let accounts = acc_types(&parse_file(&src.join("validation.rs"))?, &ctx.consts)
+        .into_iter()
+        .map(|(name, discriminant)| {
+            let fields = ctx.state_fields(&name)?;
+            ctx.add_types(&fields, &mut types)?;
+
+            Ok(Account {
+                size: account_header.len + fields_size(&fields, &types),
+                name,
+                discriminant,
+                fields,
+            })
+        })
+        .collect::<Result<Vec<_>, IdlError>>()?;
+
+    Ok(Idl {
+        name: PROGRAM_NAME.to_string(),
+        version,
+        account_header,
+        constants,
+        instructions: instructions(&parse_file(&src.join("instruction.rs"))?, &ctx)?,
+        accounts,
+        types,
+        errors: errors(&parse_file(&src.join("error.rs"))?)?,
+    })
+}
@@ -0,0 +1,485 @@
+use std::fmt::Write;
+
+use crate::idl::{Field, Idl, Instruction, Primitive, Signer, Type, TypeDef};
+
+// largest integer a JS number holds exactly
+const MAX_SAFE_INTEGER: i128 = (1 << 53) - 1;
+
+const RUNTIME: &str = r#"export type Numberish = BN | number;
+
+class Reader {
+    private offset: number;
+
+    constructor(private readonly data: Buffer, offset: number) {
+        this.offset = offset;
+    }
+
+    u8(): number {
+        const value = this.data.readUInt8(this.offset);
+        this.offset += 1;
+        return value;
+    }
+
+    bool(): boolean {
+        return this.u8() !== 0;
+    }
+
+    u64(): BN {
+        const value = new BN(this.data.slice(this.offset, this.offset + 8), "le");
+        this.offset += 8;
+        return value;
+    }
+
+    i64(): BN {
+        return this.u64().fromTwos(64);
+    }
+
+    publicKey(): PublicKey {
+        const value = new PublicKey(this.data.slice(this.offset, this.offset + 32));
+        this.offset += 32;
+        return value;
+    }
+
+    array<T>(len: number, item: () => T): T[] {
+        const items: T[] = [];
+        for (let i = 0; i < len; i++) {
+            items.push(item());
+        }
+        return items;
+    }
+}
+
+class Writer {
+    private readonly bytes: number[] = [];
+
+    u8(value: number): void {
+        if (value < 0 || value > 0xff) {
+            throw new Error(`${value} doesn't fit in a u8`);
+        }
+        this.bytes.push(value);
+    }
+
+    bool(value: boolean): void {
+        this.u8(value ? 1 : 0);
+    }
+
+    u64(value: Numberish): void {
+        const bn = new BN(value);
+        if (bn.isNeg()) {
+            throw new Error(`${bn.toString()} doesn't fit in a u64`);
+        }
+        this.push(bn.toArray("le", 8));
+    }
+
+    i64(value: Numberish): void {
+        this.push(new BN(value).toTwos(64).toArray("le", 8));
+    }
+
+    publicKey(value: PublicKey): void {
+        this.push(value.toBytes());
+    }
+
+    array<T>(items: T[], len: number, item: (value: T) => void): void {
+        if (items.length !== len) {
+            throw new Error(`expected ${len} items, got ${items.length}`);
+        }
+        items.forEach((value) => item(value));
+    }
+
+    toBuffer(): Buffer {
+        return Buffer.from(this.bytes);
+    }
+
+    private push(bytes: ArrayLike<number>): void {
+        for (let i = 0; i < bytes.length; i++) {
+            this.bytes.push(bytes[i]);
+        }
+    }
+}
+"#;
+
+// GameAcc -> game_acc
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+fn string_literal(value: &str) -> String {
+    serde_json::Value::String(value.to_string()).to_string()
+}
+
+// the TS type of a field, u64 and i64 arguments also take a number
+fn ts_type(ty: &Type, arg: bool) -> String {
+    match ty {
+        Type::Primitive(Primitive::Bool) => "boolean".to_string(),
+        Type::Primitive(Primitive::U8) => "number".to_string(),
+        Type::Primitive(Primitive::U64) | Type::Primitive(Primitive::I64) if arg => {
+            "Numberish".to_string()
+        }
+        Type::Primitive(Primitive::U64) | Type::Primitive(Primitive::I64) => "BN".to_string(),
+        Type::Primitive(Primitive::PublicKey) => "PublicKey".to_string(),
+        Type::Array { array: (item, _) } => format!("{}[]", ts_type(item, arg)),
+        Type::Defined { defined } => defined.clone(),
+    }
+}
+
+fn primitive_method(primitive: Primitive) -> &'static str {
+    match primitive {
+        Primitive::Bool => "bool",
+        Primitive::U8 => "u8",
+        Primitive::U64 => "u64",
+        Primitive::I64 => "i64",
+        Primitive::PublicKey => "publicKey",
+    }
+}
+
+fn read_expr(ty: &Type) -> String {
+    match ty {
+        Type::Primitive(primitive) => format!("reader.{}()", primitive_method(*primitive)),
+        Type::Array { array: (item, len) } => {
+            format!("reader.array({}, () => {})", len, read_expr(item))
+        }
+        Type::Defined { defined } => format!("read_{}(reader)", snake_case(defined)),
+    }
+}
+
+fn write_stmt(ty: &Type, value: &str) -> String {
+    match ty {
+        Type::Primitive(primitive) => {
+            format!("writer.{}({});", primitive_method(*primitive), value)
+        }
+        Type::Array { array: (item, len) } => format!(
+            "writer.array({}, {}, (item) => {{ {} }});",
+            value,
+            len,
+            write_stmt(item, "item")
+        ),
+        Type::Defined { defined } => format!("write_{}(writer, {});", snake_case(defined), value),
+    }
+}
+
+fn uses_type(ty: &Type, name: &str, types: &[TypeDef]) -> bool {
+    match ty {
+        Type::Primitive(_) => false,
+        Type::Array { array: (item, _) } => uses_type(item, name, types),
+        Type::Defined { defined } => {
+            defined == name
+                || types
+                    .iter()
+                    .filter(|ty| ty.name == *defined)
+                    .flat_map(|ty| ty.fields.iter())
+                    .any(|field| uses_type(&field.ty, name, types))
+        }
+    }
+}
+
+fn doc_comment(out: &mut String, indent: &str, lines: &[String]) {
+    match lines {
+        [] => {}
+        [line] => writeln!(out, "{}/** {} */", indent, line).unwrap(),
+        lines => {
+            writeln!(out, "{}/**", indent).unwrap();
+            for line in lines {
+                if line.is_empty() {
+                    writeln!(out, "{} *", indent).unwrap();
+                } else {
+                    writeln!(out, "{} * {}", indent, line).unwrap();
+                }
+            }
+            writeln!(out, "{} */", indent).unwrap();
+        }
+    }
+}
+
+fn interface(out: &mut String, name: &str, fields: &[Field], arg: bool) {
+    writeln!(out, "export interface {} {{", name).unwrap();
+    for field in fields {
+        writeln!(out, "    {}: {};", field.name, ts_type(&field.ty, arg)).unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+}
+
+fn reader_body(out: &mut String, fields: &[Field]) {
+    writeln!(out, "    return {{").unwrap();
+    for field in fields {
+        writeln!(out, "        {}: {},", field.name, read_expr(&field.ty)).unwrap();
+    }
+    writeln!(out, "    }};").unwrap();
+}
+
+fn constants(out: &mut String, idl: &Idl) {
+    writeln!(
+        out,
+        "export const ACCOUNT_HEADER_LEN = {};",
+        idl.account_header.len
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "export const ACCOUNT_VERSION = {};",
+        idl.account_header.version
+    )
+    .unwrap();
+
+    for constant in idl.constants.iter() {
+        match constant.name.as_str() {
+            "ACCOUNT_HEADER_LEN" | "ACCOUNT_VERSION" | "CLOSED_ACC_TYPE" => continue,
+            _ => {}
+        }
+
+        let value = match (constant.ty.as_str(), constant.value.parse::<i128>()) {
+            ("bytes", _) => format!("Buffer.from({})", string_literal(&constant.value)),
+            (_, Ok(value)) if value.abs() > MAX_SAFE_INTEGER => {
+                format!("new BN({})", string_literal(&constant.value))
+            }
+            _ => constant.value.clone(),
+        };
+        writeln!(out, "export const {} = {};", constant.name, value).unwrap();
+    }
+    writeln!(out).unwrap();
+}
+
+fn accounts(out: &mut String, idl: &Idl) {
+    writeln!(out, "export enum AccType {{").unwrap();
+    for account in idl.accounts.iter() {
+        writeln!(out, "    {} = {},", account.name, account.discriminant).unwrap();
+    }
+    writeln!(out, "    Closed = {},", idl.account_header.closed_acc_type).unwrap();
+    writeln!(out, "}}\n").unwrap();
+
+    out.push_str(
+        r#"// checks the account header before the fields after it are read
+function account_reader(data: Buffer, acc_type: AccType, len: number, name: string): Reader {
+    if (data.length > 0 && data[0] === AccType.Closed) {
+        throw new Error(`the ${name} account is closed`);
+    }
+    if (data.length < ACCOUNT_HEADER_LEN || data[0] !== acc_type) {
+        throw new Error(`not a ${name} account`);
+    }
+    if (data[1] !== ACCOUNT_VERSION) {
+        throw new Error(`the ${name} account is outdated, MigrateAccount upgrades it to version ${ACCOUNT_VERSION}`);
+    }
+    if (data.length !== len) {
+        throw new Error(`the ${name} account is ${data.length} bytes instead of ${len}`);
+    }
+    return new Reader(data, ACCOUNT_HEADER_LEN);
+}
+
+"#,
+    );
+
+    for ty in idl.types.iter() {
+        let snake = snake_case(&ty.name);
+        interface(out, &ty.name, &ty.fields, false);
+
+        writeln!(
+            out,
+            "function read_{}(reader: Reader): {} {{",
+            snake, ty.name
+        )
+        .unwrap();
+        reader_body(out, &ty.fields);
+        writeln!(out, "}}\n").unwrap();
+
+        let written = idl.instructions.iter().any(|instruction| {
+            instruction
+                .args
+                .iter()
+                .any(|arg| uses_type(&arg.ty, &ty.name, &idl.types))
+        });
+        if written {
+            writeln!(
+                out,
+                "function write_{}(writer: Writer, value: {}): void {{",
+                snake, ty.name
+            )
+            .unwrap();
+            for field in ty.fields.iter() {
+                let value = format!("value.{}", field.name);
+                writeln!(out, "    {}", write_stmt(&field.ty, &value)).unwrap();
+            }
+            writeln!(out, "}}\n").unwrap();
+        }
+    }
+
+    for account in idl.accounts.iter() {
+        let snake = snake_case(&account.name);
+        let len = format!("{}_LEN", snake.to_uppercase());
+
+        writeln!(out, "export const {} = {};\n", len, account.size).unwrap();
+        interface(out, &account.name, &account.fields, false);
+
+        writeln!(
+            out,
+            "export function decode_{}(data: Buffer): {} {{",
+            snake, account.name
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "    const reader = account_reader(data, AccType.{}, {}, {});",
+            account.name,
+            len,
+            string_literal(&account.name)
+        )
+        .unwrap();
+        reader_body(out, &account.fields);
+        writeln!(out, "}}\n").unwrap();
+    }
+}
+
+fn instruction(out: &mut String, instruction: &Instruction) {
+    let accounts_name = format!("{}Accounts", instruction.name);
+    let args_name = format!("{}Args", instruction.name);
+
+    writeln!(out, "export interface {} {{", accounts_name).unwrap();
+    for account in instruction.accounts.iter() {
+        if account.address.is_some() {
+            continue;
+        }
+        if let Some(docs) = &account.docs {
+            doc_comment(out, "    ", std::slice::from_ref(docs));
+        }
+        let optional = if account.optional { "?" } else { "" };
+        writeln!(out, "    {}{}: PublicKey;", account.name, optional).unwrap();
+    }
+    for account in instruction.accounts.iter() {
+        if let Signer::Flag(flag) = &account.signer {
+            writeln!(out, "    {}: boolean;", flag).unwrap();
+        }
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    if !instruction.args.is_empty() {
+        interface(out, &args_name, &instruction.args, true);
+    }
+
+    doc_comment(out, "", &instruction.docs);
+    writeln!(
+        out,
+        "export function {}(\n    program_id: PublicKey,\n    accounts: {},",
+        snake_case(&instruction.name),
+        accounts_name
+    )
+    .unwrap();
+    if !instruction.args.is_empty() {
+        writeln!(out, "    args: {},", args_name).unwrap();
+    }
+    writeln!(out, "): TransactionInstruction {{").unwrap();
+
+    let meta = |account: &crate::idl::InstructionAccount| {
+        let pubkey = match &account.address {
+            Some(address) => format!("new PublicKey({})", string_literal(address)),
+            None => format!("accounts.{}", account.name),
+        };
+        let signer = match &account.signer {
+            Signer::Fixed(signer) => signer.to_string(),
+            Signer::Flag(flag) => format!("accounts.{}", flag),
+        };
+        format!(
+            "{{ pubkey: {}, isSigner: {}, isWritable: {} }}",
+            pubkey, signer, account.writable
+        )
+    };
+
+    // accounts always passed come first, the optional ones are pushed when given
+    let required = instruction
+        .accounts
+        .iter()
+        .take_while(|account| !account.optional);
+    writeln!(out, "    const keys: AccountMeta[] = [").unwrap();
+    for account in required.clone() {
+        writeln!(out, "        {},", meta(account)).unwrap();
+    }
+    writeln!(out, "    ];").unwrap();
+    for account in instruction.accounts.iter().skip(required.count()) {
+        if account.optional {
+            writeln!(out, "    if (accounts.{}) {{", account.name).unwrap();
+            writeln!(out, "        keys.push({});", meta(account)).unwrap();
+            writeln!(out, "    }}").unwrap();
+        } else {
+            writeln!(out, "    keys.push({});", meta(account)).unwrap();
+        }
+    }
+
+    writeln!(out, "\n    const writer = new Writer();").unwrap();
+    writeln!(out, "    writer.u8({});", instruction.discriminant).unwrap();
+    for arg in instruction.args.iter() {
+        let value = format!("args.{}", arg.name);
+        writeln!(out, "    {}", write_stmt(&arg.ty, &value)).unwrap();
+    }
+    writeln!(
+        out,
+        "\n    return new TransactionInstruction({{ programId: program_id, keys, data: writer.toBuffer() }});"
+    )
+    .unwrap();
+    writeln!(out, "}}\n").unwrap();
+}
+
+fn errors(out: &mut String, idl: &Idl) {
+    writeln!(out, "export enum EscrowError {{").unwrap();
+    for error in idl.errors.iter() {
+        writeln!(out, "    {} = {},", error.name, error.code).unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    writeln!(
+        out,
+        "// messages of the program's custom error codes, found in a failed transaction's logs"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "export const ESCROW_ERROR_MESSAGES: {{ [code: number]: string }} = {{"
+    )
+    .unwrap();
+    for error in idl.errors.iter() {
+        writeln!(out, "    {}: {},", error.code, string_literal(&error.msg)).unwrap();
+    }
+    writeln!(out, "}};").unwrap();
+}
+
+// a client with the program's constants, account decoders, instruction builders and errors
+pub fn generate(idl: &Idl) -> String {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "// Generated by gamenest-idl from idl/{}.json, do not edit.",
+        idl.name
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "// Run `cargo run -p gamenest-idl` after changing the program's instructions, accounts or errors.\n"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "import {{ AccountMeta, PublicKey, TransactionInstruction }} from \"@solana/web3.js\";"
+    )
+    .unwrap();
+    writeln!(out, "import BN from \"bn.js\";\n").unwrap();
+
+    out.push_str(RUNTIME);
+    writeln!(out).unwrap();
+
+    constants(&mut out, idl);
+    accounts(&mut out, idl);
+    for ix in idl.instructions.iter() {
+        instruction(&mut out, ix);
+    }
+    errors(&mut out, idl);
+
+    out
+}
@@ -0,0 +1,89 @@
+use game_nest::error::EscrowError;
+use game_nest::instruction::InstructionType;
+use game_nest::state::{Escrow, GameAcc, History, Market, Offer, Reputation};
+use gamenest_idl::{generate, Generated, CLIENT_PATH, IDL_PATH};
+use solana_program::program_pack::Pack;
+use std::fs;
+use std::path::Path;
+
+fn root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap()
+}
+
+fn generated() -> Generated {
+    generate(root()).unwrap()
+}
+
+#[test]
+fn committed_idl_and_client_are_up_to_date() {
+    let generated = generated();
+
+    for (path, contents) in [
+        (IDL_PATH, &generated.json),
+        (CLIENT_PATH, &generated.typescript),
+    ] {
+        let committed = fs::read_to_string(root().join(path)).unwrap();
+        assert!(
+            committed == *contents,
+            "{} is outdated, run `cargo run -p gamenest-idl`",
+            path
+        );
+    }
+}
+
+#[test]
+fn account_sizes_match_pack_len() {
+    let idl = generated().idl;
+
+    let sizes: Vec<(&str, usize)> = idl
+        .accounts
+        .iter()
+        .map(|account| (account.name.as_str(), account.size))
+        .collect();
+
+    assert_eq!(
+        sizes,
+        vec![
+            ("Escrow", Escrow::LEN),
+            ("GameAcc", GameAcc::LEN),
+            ("Offer", Offer::LEN),
+            ("Market", Market::LEN),
+            ("History", History::LEN),
+            ("Reputation", Reputation::LEN),
+        ]
+    );
+}
+
+// every argument byte set to 1 is a valid value of any type, so an instruction laid out as
+// the IDL says must unpack to its variant and pack back to the same bytes
+#[test]
+fn instruction_args_round_trip_through_unpack() {
+    let idl = generated().idl;
+
+    assert_eq!(idl.instructions.len(), 24);
+    for instruction in idl.instructions.iter() {
+        let args_len = gamenest_idl::idl::fields_size(&instruction.args, &idl.types);
+        let mut data = vec![instruction.discriminant];
+        data.resize(1 + args_len, 1);
+
+        let unpacked = InstructionType::unpack(&data).unwrap();
+
+        assert!(format!("{:?}", unpacked).starts_with(&instruction.name));
+        assert_eq!(unpacked.pack(), data, "{}", instruction.name);
+    }
+}
+
+// every code the program knows, from 0 till from_code has no error for it
+#[test]
+fn error_codes_match_the_program() {
+    let idl = generated().idl;
+
+    let errors: Vec<EscrowError> = (0..).map_while(EscrowError::from_code).collect();
+    assert_eq!(idl.errors.len(), errors.len());
+
+    for (idl_error, error) in idl.errors.iter().zip(errors) {
+        assert_eq!(idl_error.code, error as u32);
+        assert_eq!(idl_error.name, format!("{:?}", error));
+        assert_eq!(idl_error.msg, error.to_string());
+    }
+}
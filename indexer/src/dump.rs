@@ -0,0 +1,84 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use solana_program::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::error::IndexerError;
+
+// an account as it was read from the cluster
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawAccount {
+    pub pubkey: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+}
+
+// the keyed accounts getProgramAccounts returns with base64 encoding,
+// `solana account --output json` prints the same shape for a single account
+#[derive(Deserialize)]
+struct KeyedAccount {
+    pubkey: String,
+    account: UiAccount,
+}
+
+#[derive(Deserialize)]
+struct UiAccount {
+    lamports: u64,
+    owner: String,
+    data: (String, String), // [data, encoding]
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Dump {
+    Response { result: Vec<KeyedAccount> },
+    Accounts(Vec<KeyedAccount>),
+}
+
+fn pubkey(key: &str) -> Result<Pubkey, IndexerError> {
+    Pubkey::from_str(key).map_err(|_| IndexerError::InvalidPubkey(key.to_string()))
+}
+
+impl RawAccount {
+    fn from_keyed(keyed: KeyedAccount) -> Result<Self, IndexerError> {
+        let KeyedAccount {
+            pubkey: key,
+            account,
+        } = keyed;
+        let (data, encoding) = account.data;
+
+        if encoding != "base64" {
+            return Err(IndexerError::InvalidData(key));
+        }
+
+        let data = STANDARD
+            .decode(data)
+            .map_err(|_| IndexerError::InvalidData(key.clone()))?;
+
+        Ok(RawAccount {
+            pubkey: pubkey(&key)?,
+            owner: pubkey(&account.owner)?,
+            lamports: account.lamports,
+            data,
+        })
+    }
+}
+
+// parses a dump of program accounts, either a getProgramAccounts response or its result array,
+// or a snapshot file with one keyed account per line
+pub fn parse_accounts(input: &str) -> Result<Vec<RawAccount>, IndexerError> {
+    let keyed = match serde_json::from_str::<Dump>(input) {
+        Ok(Dump::Response { result }) | Ok(Dump::Accounts(result)) => result,
+        Err(err) => {
+            let lines = input.lines().filter(|line| !line.trim().is_empty());
+
+            let keyed: Result<Vec<KeyedAccount>, _> = lines.map(serde_json::from_str).collect();
+
+            // report why the whole input didn't parse when it isn't one account per line either
+            keyed.map_err(|_| err)?
+        }
+    };
+
+    keyed.into_iter().map(RawAccount::from_keyed).collect()
+}
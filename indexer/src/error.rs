@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum IndexerError {
+    #[error("Invalid Dump: {0}")]
+    InvalidDump(#[from] serde_json::Error),
+
+    #[error("Invalid Pubkey: {0}")]
+    InvalidPubkey(String),
+
+    #[error("Invalid Account Data of {0}")]
+    InvalidData(String),
+}
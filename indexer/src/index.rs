@@ -0,0 +1,191 @@
+use game_nest::state::{Escrow, GameAcc, ESCROW_KIND_ENGLISH_AUCTION};
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+
+use crate::dump::RawAccount;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscrowStatus {
+    Open,    // can be taken, or bid on for an english auction
+    Active,  // taken and the rental is still running
+    Expired, // can't be taken anymore, or the rental is over and waits for a Revert
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexedEscrow {
+    pub address: Pubkey,
+    pub escrow: Escrow,
+    pub status: EscrowStatus,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexedGameAcc {
+    pub address: Pubkey,
+    pub game_acc: GameAcc,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RentedAsset {
+    pub asset_id: u64,
+    pub game_acc: Pubkey,       // renter's Game Program A/c holding it
+    pub escrow: Option<Pubkey>, // None if the rental's Escrow A/c wasn't in the dump
+    pub due_at: Option<i64>,    // unix timestamp the asset can be reverted after
+}
+
+fn status(escrow: &Escrow, unix_timestamp: i64) -> EscrowStatus {
+    if escrow.is_taken {
+        // Revert only goes through once the due time has passed
+        if unix_timestamp <= escrow.due_at(escrow.esc_taken_time) {
+            EscrowStatus::Active
+        } else {
+            EscrowStatus::Expired
+        }
+    } else if escrow.is_expired(unix_timestamp)
+        || (escrow.kind == ESCROW_KIND_ENGLISH_AUCTION && unix_timestamp >= escrow.end_time)
+    {
+        EscrowStatus::Expired
+    } else {
+        EscrowStatus::Open
+    }
+}
+
+// the game account an escrow's asset is rented into
+fn renter_game_acc(escrow: &Escrow) -> &Pubkey {
+    if escrow.initialiser_is_renter() {
+        &escrow.initialiser_game_acc
+    } else {
+        &escrow.taker_game_acc
+    }
+}
+
+// Escrow and GameAcc accounts of the program as of unix_timestamp
+#[derive(Clone, Debug, Default)]
+pub struct Index {
+    pub unix_timestamp: i64,
+    escrows: Vec<IndexedEscrow>,
+    game_accs: Vec<IndexedGameAcc>,
+    skipped: Vec<Pubkey>,
+}
+
+impl Index {
+    // accounts of other programs, of other types or closed are left out, version 1 accounts
+    // not migrated yet are decoded in their old layout, any other Escrow or GameAcc A/c that
+    // doesn't decode is reported by skipped()
+    pub fn build<I>(program_id: &Pubkey, accounts: I, unix_timestamp: i64) -> Self
+    where
+        I: IntoIterator<Item = RawAccount>,
+    {
+        let mut index = Index {
+            unix_timestamp,
+            ..Index::default()
+        };
+
+        for account in accounts {
+            if account.owner != *program_id {
+                continue;
+            }
+
+            match account.data.first() {
+                Some(1) => match Escrow::unpack(&account.data)
+                    .or_else(|_| Escrow::unpack_v1(&account.data))
+                {
+                    Ok(escrow) => index.escrows.push(IndexedEscrow {
+                        address: account.pubkey,
+                        status: status(&escrow, unix_timestamp),
+                        escrow,
+                    }),
+                    Err(_) => index.skipped.push(account.pubkey),
+                },
+                Some(2) => match GameAcc::unpack(&account.data)
+                    .or_else(|_| GameAcc::unpack_v1(&account.data))
+                {
+                    Ok(game_acc) => index.game_accs.push(IndexedGameAcc {
+                        address: account.pubkey,
+                        game_acc,
+                    }),
+                    Err(_) => index.skipped.push(account.pubkey),
+                },
+                _ => {}
+            }
+        }
+
+        index
+    }
+
+    pub fn escrows(&self) -> &[IndexedEscrow] {
+        &self.escrows
+    }
+
+    pub fn game_accs(&self) -> &[IndexedGameAcc] {
+        &self.game_accs
+    }
+
+    // Escrow and GameAcc A/cs of the program that couldn't be decoded
+    pub fn skipped(&self) -> &[Pubkey] {
+        &self.skipped
+    }
+
+    pub fn escrow(&self, address: &Pubkey) -> Option<&IndexedEscrow> {
+        self.escrows
+            .iter()
+            .find(|escrow| escrow.address == *address)
+    }
+
+    pub fn game_acc(&self, address: &Pubkey) -> Option<&IndexedGameAcc> {
+        self.game_accs
+            .iter()
+            .find(|game_acc| game_acc.address == *address)
+    }
+
+    pub fn escrows_with_status(&self, status: EscrowStatus) -> Vec<&IndexedEscrow> {
+        self.escrows
+            .iter()
+            .filter(|escrow| escrow.status == status)
+            .collect()
+    }
+
+    // open escrows the asset can be taken into, including collection bids covering it
+    pub fn open_offers_for_asset(&self, asset_id: u64) -> Vec<&IndexedEscrow> {
+        self.escrows
+            .iter()
+            .filter(|escrow| {
+                escrow.status == EscrowStatus::Open && escrow.escrow.accepts_asset(asset_id)
+            })
+            .collect()
+    }
+
+    pub fn game_accs_of(&self, wallet: &Pubkey) -> Vec<&IndexedGameAcc> {
+        self.game_accs
+            .iter()
+            .filter(|game_acc| game_acc.game_acc.userspace_owner == *wallet)
+            .collect()
+    }
+
+    // assets in the rented slots of the wallet's game accounts, joined to the escrows renting them
+    pub fn assets_rented_by(&self, wallet: &Pubkey) -> Vec<RentedAsset> {
+        let mut rented = Vec::new();
+
+        for game_acc in self.game_accs_of(wallet) {
+            for &asset_id in game_acc.game_acc.rented.iter() {
+                if asset_id == 0 {
+                    continue;
+                }
+
+                let escrow = self.escrows.iter().find(|escrow| {
+                    escrow.escrow.is_taken
+                        && escrow.escrow.asset_id == asset_id
+                        && *renter_game_acc(&escrow.escrow) == game_acc.address
+                });
+
+                rented.push(RentedAsset {
+                    asset_id,
+                    game_acc: game_acc.address,
+                    escrow: escrow.map(|indexed| indexed.address),
+                    due_at: escrow
+                        .map(|indexed| indexed.escrow.due_at(indexed.escrow.esc_taken_time)),
+                });
+            }
+        }
+
+        rented
+    }
+}
@@ -0,0 +1,9 @@
+// rebuilds the marketplace from raw program accounts so listings and rentals can be
+// queried off-chain without decoding and joining the accounts by hand
+pub mod dump;
+pub mod error;
+pub mod index;
+
+pub use dump::{parse_accounts, RawAccount};
+pub use error::IndexerError;
+pub use index::{EscrowStatus, Index, IndexedEscrow, IndexedGameAcc, RentedAsset};
@@ -0,0 +1,155 @@
+use gamenest_indexer::{parse_accounts, EscrowStatus, Index, IndexerError, RentedAsset};
+use solana_program::pubkey::Pubkey;
+
+// the fixtures hold the same dump of a program at [9; 32] as a getProgramAccounts response
+// and as a snapshot with one account per line, wallet [1; 32] with Game Program A/c [11; 32]
+// lent asset 5 to wallet [2; 32] with Game Program A/c [12; 32], escrows are [21; 32] onwards.
+// wallet [3; 32] has a version 1 Game Program A/c [13; 32] and a version 1 rent request [28; 32]
+// for asset 9 that were never migrated, [29; 32] is an Escrow A/c cut short
+const PROGRAM_ACCOUNTS: &str = include_str!("fixtures/program_accounts.json");
+const SNAPSHOT: &str = include_str!("fixtures/snapshot.jsonl");
+
+const NOW: i64 = 2000;
+
+fn key(n: u8) -> Pubkey {
+    Pubkey::new_from_array([n; 32])
+}
+
+fn index(dump: &str) -> Index {
+    Index::build(&key(9), parse_accounts(dump).unwrap(), NOW)
+}
+
+fn addresses<'a>(
+    escrows: impl IntoIterator<Item = &'a gamenest_indexer::IndexedEscrow>,
+) -> Vec<Pubkey> {
+    escrows.into_iter().map(|escrow| escrow.address).collect()
+}
+
+#[test]
+fn both_dump_formats_parse_the_same() {
+    let program_accounts = parse_accounts(PROGRAM_ACCOUNTS).unwrap();
+
+    assert_eq!(program_accounts.len(), 12);
+    assert_eq!(program_accounts, parse_accounts(SNAPSHOT).unwrap());
+}
+
+#[test]
+fn only_the_programs_escrows_and_game_accs_are_indexed() {
+    let index = index(PROGRAM_ACCOUNTS);
+
+    // the closed account and the other program's account are left out
+    assert_eq!(
+        addresses(index.escrows()),
+        vec![key(21), key(22), key(23), key(24), key(25), key(28)]
+    );
+    assert_eq!(index.game_accs().len(), 3);
+    assert!(index.escrow(&key(26)).is_none());
+    assert!(index.escrow(&key(27)).is_none());
+
+    // the account that doesn't decode is reported rather than dropped
+    assert!(index.escrow(&key(29)).is_none());
+    assert_eq!(index.skipped(), [key(29)]);
+}
+
+#[test]
+fn version_1_accounts_are_indexed() {
+    let index = index(SNAPSHOT);
+
+    let escrow = index.escrow(&key(28)).unwrap();
+    assert_eq!(escrow.status, EscrowStatus::Open);
+    assert_eq!(escrow.escrow.initialiser_game_acc, key(13));
+    assert_eq!(escrow.escrow.amount, 500_000);
+    assert_eq!(escrow.escrow.asset_id, 9);
+    assert_eq!(
+        addresses(index.open_offers_for_asset(9)),
+        vec![key(24), key(28)]
+    );
+
+    let game_accs = index.game_accs_of(&key(3));
+    assert_eq!(game_accs.len(), 1);
+    assert_eq!(game_accs[0].address, key(13));
+    assert_eq!(game_accs[0].game_acc.owned[0], 8);
+}
+
+#[test]
+fn escrows_are_classified() {
+    let index = index(PROGRAM_ACCOUNTS);
+
+    let status = |n| index.escrow(&key(n)).unwrap().status;
+
+    assert_eq!(status(21), EscrowStatus::Open);
+    assert_eq!(status(22), EscrowStatus::Active);
+    assert_eq!(status(23), EscrowStatus::Expired);
+    assert_eq!(status(24), EscrowStatus::Open);
+    assert_eq!(status(25), EscrowStatus::Expired);
+
+    // the rental of asset 5 is due an hour after it was taken at 1000,
+    // it is still running at the due time and can only be reverted after it
+    let due = Index::build(&key(9), parse_accounts(SNAPSHOT).unwrap(), 4600);
+    assert_eq!(due.escrow(&key(22)).unwrap().status, EscrowStatus::Active);
+
+    let later = Index::build(&key(9), parse_accounts(SNAPSHOT).unwrap(), 4601);
+    assert_eq!(
+        later.escrow(&key(22)).unwrap().status,
+        EscrowStatus::Expired
+    );
+    assert_eq!(
+        addresses(later.escrows_with_status(EscrowStatus::Expired)),
+        vec![key(22), key(23), key(25)]
+    );
+}
+
+#[test]
+fn open_offers_for_asset_include_collection_bids() {
+    let index = index(PROGRAM_ACCOUNTS);
+
+    assert_eq!(
+        addresses(index.open_offers_for_asset(7)),
+        vec![key(21), key(24)]
+    );
+    assert_eq!(addresses(index.open_offers_for_asset(3)), vec![key(24)]);
+    assert!(index.open_offers_for_asset(11).is_empty());
+}
+
+#[test]
+fn assets_rented_by_wallet_are_joined_to_their_escrow() {
+    let index = index(SNAPSHOT);
+
+    assert_eq!(
+        index.assets_rented_by(&key(2)),
+        vec![RentedAsset {
+            asset_id: 5,
+            game_acc: key(12),
+            escrow: Some(key(22)),
+            due_at: Some(1000 + 60 * 60),
+        }]
+    );
+
+    // the lender has the asset out on loan, not rented
+    assert!(index.assets_rented_by(&key(1)).is_empty());
+    assert_eq!(index.game_accs_of(&key(1))[0].game_acc.lent[0], 5);
+}
+
+#[test]
+fn malformed_dumps_are_rejected() {
+    assert!(matches!(
+        parse_accounts("not json"),
+        Err(IndexerError::InvalidDump(_))
+    ));
+
+    let bad_key = SNAPSHOT.lines().next().unwrap().replacen("k7Fa", "0OIl", 1);
+    assert!(matches!(
+        parse_accounts(&bad_key),
+        Err(IndexerError::InvalidPubkey(_))
+    ));
+
+    let bad_data = SNAPSHOT
+        .lines()
+        .next()
+        .unwrap()
+        .replacen("base64", "base58", 1);
+    assert!(matches!(
+        parse_accounts(&bad_data),
+        Err(IndexerError::InvalidData(_))
+    ));
+}
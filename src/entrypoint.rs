@@ -1,8 +1,9 @@
 use solana_program::{
-    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, msg, pubkey::Pubkey,
+    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, msg,
+    program_error::PrintProgramError, pubkey::Pubkey,
 };
 
-use crate::processor::Processor;
+use crate::{error::EscrowError, processor::Processor};
 
 entrypoint!(process_instruction);
 fn process_instruction(
@@ -11,5 +12,10 @@ fn process_instruction(
     instruction_data: &[u8],
 ) -> ProgramResult {
     msg!("entrypoint");
-    Processor::process_instruction(program_id, accounts, instruction_data)
+    if let Err(error) = Processor::process_instruction(program_id, accounts, instruction_data) {
+        // logs the message of the error next to its code
+        error.print::<EscrowError>();
+        return Err(error);
+    }
+    Ok(())
 }
@@ -1,8 +1,16 @@
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive as FromCode;
 use thiserror::Error;
 
-use solana_program::program_error::ProgramError;
+use solana_program::{
+    decode_error::DecodeError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+};
 
-#[derive(Error, Debug, Clone, Copy)]
+// the code of an error is its position in the enum and clients match on it, so new
+// variants are only ever appended and existing ones are never reordered or removed
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
 
 pub enum EscrowError {
     #[error("Invalid Instruction")]
@@ -22,6 +30,118 @@ pub enum EscrowError {
 
     #[error("Asset Space Filled")]
     AssetSpaceFull,
+
+    #[error("Taker Not Allowed")]
+    TakerNotAllowed,
+
+    #[error("Escrow Expired")]
+    EscrowExpired,
+
+    #[error("Bid Too Low")]
+    BidTooLow,
+
+    #[error("Auction Ended")]
+    AuctionEnded,
+
+    #[error("Auction Not Ended")]
+    AuctionNotEnded,
+
+    #[error("Account Locked")]
+    AccountLocked,
+
+    #[error("Asset Lent Out")]
+    AssetLentOut,
+
+    #[error("Account Outdated")]
+    AccountOutdated,
+
+    #[error("Reputation Too Low")]
+    ReputationTooLow,
+
+    #[error("Escrow Disputed")]
+    EscrowDisputed,
+
+    #[error("Account Closed")]
+    AccountClosed,
+
+    #[error("Asset Rented")]
+    AssetRented,
+
+    #[error("Asset Not Owned")]
+    AssetNotOwned,
+
+    #[error("Rental Not Expired")]
+    RentalNotExpired,
+
+    #[error("Not An Escrow Account")]
+    NotEscrowAccount,
+
+    #[error("Not A Game Account")]
+    NotGameAccount,
+
+    #[error("Owner Mismatch")]
+    OwnerMismatch,
+
+    #[error("Account Does Not Match")]
+    AccountMismatch,
+
+    #[error("Asset Not Accepted")]
+    AssetNotAccepted,
+
+    #[error("Can't Take Own Escrow")]
+    OwnEscrow,
+
+    #[error("Escrow Not Taken")]
+    EscrowNotTaken,
+
+    #[error("Wrong Escrow Kind")]
+    WrongEscrowKind,
+
+    #[error("Escrow Not Disputable")]
+    EscrowNotDisputable,
+
+    #[error("Escrow Not Disputed")]
+    EscrowNotDisputed,
+
+    #[error("Delivery Not Pending")]
+    DeliveryNotPending,
+
+    #[error("Not An Escrow Party")]
+    NotEscrowParty,
+
+    #[error("Incorrect PDA")]
+    IncorrectPda,
+
+    #[error("Price Above Maximum")]
+    PriceTooHigh,
+
+    #[error("History Account Missing")]
+    MissingHistory,
+
+    #[error("Reputation Account Missing")]
+    MissingReputation,
+
+    #[error("Arbiter Does Not Match")]
+    ArbiterMismatch,
+
+    #[error("Oracle Does Not Match")]
+    OracleMismatch,
+
+    #[error("Attestation Invalid")]
+    InvalidAttestation,
+
+    #[error("Signer Is Not The Arbiter")]
+    NotArbiter,
+
+    #[error("Signer Is Not The Lock Authority")]
+    NotLockAuthority,
+}
+
+impl EscrowError {
+    // the error a ProgramError::Custom code stands for, if the program knows it
+    pub fn from_code(code: u32) -> Option<Self> {
+        FromCode::from_u32(code)
+    }
 }
 
 impl From<EscrowError> for ProgramError {
@@ -29,3 +149,18 @@ impl From<EscrowError> for ProgramError {
         ProgramError::Custom(e as u32)
     }
 }
+
+impl<T> DecodeError<T> for EscrowError {
+    fn type_of() -> &'static str {
+        "EscrowError"
+    }
+}
+
+impl PrintProgramError for EscrowError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromCode,
+    {
+        msg!("Error: {}", self);
+    }
+}
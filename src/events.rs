@@ -0,0 +1,167 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{log::sol_log_data, pubkey::Pubkey};
+
+// prefix the runtime puts before every sol_log_data entry in the transaction logs
+pub const LOG_DATA_PREFIX: &str = "Program data: ";
+
+// put before every event so a log entry from another program isn't mistaken for one of ours
+pub const EVENT_TAG: &[u8; 8] = b"gamenest";
+
+// events are Borsh serialized after EVENT_TAG, the first byte is the variant index
+// so new events must only ever be added at the end
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub enum Event {
+    EscrowCreated {
+        escrow: Pubkey,
+        kind: u8,
+        initialiser_main_acc: Pubkey,
+        initialiser_game_acc: Pubkey,
+        asset_id: u64,
+        asset_id_max: u64,
+        amount: u64, // rent offered, or the reserve or start price for auctions
+        time: u64,   // in minutes
+        expires_at: i64,
+        timestamp: i64,
+    },
+    EscrowTaken {
+        escrow: Pubkey,
+        taker_main_acc: Pubkey,
+        taker_game_acc: Pubkey,
+        asset_id: u64,
+        amount: u64, // lamports paid for the asset
+        timestamp: i64,
+    },
+    EscrowReverted {
+        escrow: Pubkey,
+        renter_game_acc: Pubkey,
+        lender_game_acc: Pubkey,
+        asset_id: u64,
+        keeper: Option<Pubkey>,
+        timestamp: i64,
+    },
+    EscrowCancelled {
+        escrow: Pubkey,
+        initialiser_main_acc: Pubkey,
+        refund: u64,
+        timestamp: i64,
+    },
+    BidPlaced {
+        escrow: Pubkey,
+        bidder_main_acc: Pubkey,
+        amount: u64,
+        timestamp: i64,
+    },
+    AuctionSettled {
+        escrow: Pubkey,
+        winner_main_acc: Option<Pubkey>, // None if there were no bids
+        asset_id: u64,
+        amount: u64,
+        timestamp: i64,
+    },
+    OfferMade {
+        offer: Pubkey,
+        escrow: Pubkey,
+        offerer_main_acc: Pubkey,
+        asset_id: u64,
+        amount: u64,
+        time: u64,
+    },
+    OfferAccepted {
+        offer: Pubkey,
+        escrow: Pubkey,
+        offerer_main_acc: Pubkey,
+        asset_id: u64,
+        amount: u64,
+        time: u64,
+        timestamp: i64,
+    },
+    MarketCreated {
+        market: Pubkey,
+        authority: Pubkey,
+    },
+    GameAccCreated {
+        game_acc: Pubkey,
+        userspace_owner: Pubkey,
+    },
+    AssetAdded {
+        game_acc: Pubkey,
+        asset_id: u64,
+    },
+    GameAccLocked {
+        game_acc: Pubkey,
+        authority: Pubkey,
+    },
+    GameAccUnlocked {
+        game_acc: Pubkey,
+        authority: Pubkey,
+    },
+    AccountMigrated {
+        account: Pubkey,
+        acc_type: u8,
+        version: u8,
+    },
+    DisputeRaised {
+        escrow: Pubkey,
+        raiser_main_acc: Pubkey,
+        arbiter: Pubkey,
+        timestamp: i64,
+    },
+    DisputeResolved {
+        escrow: Pubkey,
+        arbiter: Pubkey,
+        renter_refund: u64,
+        lender_share: u64,
+        renter_keeps_asset: bool,
+        at_fault: u8,
+        timestamp: i64,
+    },
+    DeliveryConfirmed {
+        escrow: Pubkey,
+        oracle: Pubkey,
+        amount: u64,
+        timestamp: i64,
+    },
+    GameAccClosed {
+        game_acc: Pubkey,
+        userspace_owner: Pubkey,
+        assets_moved_to: Option<Pubkey>,
+    },
+    OfferCancelled {
+        offer: Pubkey,
+        escrow: Pubkey,
+        offerer_main_acc: Pubkey,
+        timestamp: i64,
+    },
+}
+
+impl Event {
+    pub fn emit(&self) {
+        sol_log_data(&[&self.encode()]);
+    }
+
+    // the bytes of the sol_log_data entry, EVENT_TAG followed by the Borsh serialized event
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = EVENT_TAG.to_vec();
+        self.serialize(&mut data).unwrap_or_default();
+
+        data
+    }
+
+    // decodes the bytes of a sol_log_data entry, None if it is not one of our events
+    pub fn decode(data: &[u8]) -> Option<Event> {
+        let body = data.strip_prefix(EVENT_TAG.as_ref())?;
+
+        Event::try_from_slice(body).ok()
+    }
+
+    // decodes a "Program data: <base64>" line from the transaction logs,
+    // None for lines logged by other programs in the same transaction
+    pub fn decode_log(line: &str) -> Option<Event> {
+        let encoded = line.strip_prefix(LOG_DATA_PREFIX)?;
+
+        let data = STANDARD.decode(encoded.split_whitespace().next()?).ok()?;
+
+        Event::decode(&data)
+    }
+}
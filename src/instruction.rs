@@ -1,32 +1,141 @@
-use solana_program::program_error::ProgramError;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
 use std::convert::TryInto;
+use std::mem::size_of;
 
 use crate::error::EscrowError::InvalidInstruction;
+use crate::state::{
+    History, Reputation, ALLOWED_TAKERS_LEN, DISPUTE_FAULT_LENDER, DISPUTE_FAULT_NONE,
+    DISPUTE_FAULT_RENTER,
+};
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct InitEscData {
     pub amount: u64, // in lamports
     pub time: u64,   // in minutes
     pub asset_id: u64,
+    pub allowed_takers: [Pubkey; ALLOWED_TAKERS_LEN], // leave all as Pubkey::default() for a public escrow
+    pub expires_at: i64,     // unix timestamp, 0 if escrow should never expire
+    pub asset_id_max: u64, // non 0 for a collection bid on any asset from asset_id to asset_id_max
+    pub min_reputation: u64, // Reputation score a taker needs, 0 if anyone can take
 }
 
+// where each field of InitEscData ends in the instruction data, only amount, time and asset_id
+// are required and any fields after them can be left out to be read as zero
+const INIT_ESC_FIELD_ENDS: [usize; 5] = [
+    24,
+    24 + 32 * ALLOWED_TAKERS_LEN,
+    32 + 32 * ALLOWED_TAKERS_LEN,
+    40 + 32 * ALLOWED_TAKERS_LEN,
+    48 + 32 * ALLOWED_TAKERS_LEN,
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TakeEscData {
+    pub asset_id: u64,  // asset given for a collection bid, 0 for the escrow's asset_id
+    pub max_price: u64, // in lamports, the most the taker pays for a dutch auction
+    pub arbiter: Pubkey, // the escrow's arbiter the taker agrees to, Pubkey::default() for none
+    pub oracle: Pubkey, // the escrow's oracle the taker agrees to, Pubkey::default() for none
+}
+
+// where each field of TakeEscData ends in the instruction data, all of them can be left out
+const TAKE_ESC_FIELD_ENDS: [usize; 5] = [0, 8, 16, 48, 80];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InitAuctionData {
+    pub reserve_price: u64, // in lamports
+    pub min_increment: u64, // in lamports
+    pub time: u64,          // in minutes, 0 if asset is to be sold instead of rented
+    pub asset_id: u64,
+    pub end_time: i64,       // unix timestamp
+    pub min_reputation: u64, // Reputation score a bidder needs, 0 if anyone can bid
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InitDutchAuctionData {
+    pub start_price: u64, // in lamports
+    pub end_price: u64,   // in lamports, the floor price
+    pub time: u64,        // in minutes, 0 if asset is to be sold instead of rented
+    pub asset_id: u64,
+    pub start_time: i64,     // unix timestamp
+    pub end_time: i64,       // unix timestamp
+    pub min_reputation: u64, // Reputation score a taker needs, 0 if anyone can take
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OfferData {
+    pub amount: u64,     // in lamports
+    pub time: u64,       // in minutes
+    pub asset_id: u64,   // asset offered for a collection bid, 0 for the escrow's asset_id
+    pub arbiter: Pubkey, // the escrow's arbiter the offerer agrees to, Pubkey::default() for none
+    pub oracle: Pubkey,  // the escrow's oracle the offerer agrees to, Pubkey::default() for none
+}
+
+// where each field of OfferData ends in the instruction data, arbiter and oracle can be left out
+const OFFER_FIELD_ENDS: [usize; 3] = [24, 56, 88];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ResolveDisputeData {
+    pub renter_refund: u64, // in lamports, the rest of the held rent goes to the lender
+    pub renter_keeps_asset: bool, // otherwise the asset goes back to the lender
+    pub at_fault: u8,       // DISPUTE_FAULT_NONE, DISPUTE_FAULT_RENTER or DISPUTE_FAULT_LENDER
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum InstructionType {
     /// Accounts expected for Init Escrow
     /// 0. [writable] Escrow A/c
     /// 1. [] initialisers Game Program A/c
     /// 2. [] initialisers main A/c
+    /// 3. [writable] optional Market A/c to list the rent request in
+    ///
+    /// the escrow takes the market's arbiter, if it has one the rent stays in the Escrow A/c
+    /// till the rental ends and is paid to the taker on Revert so it can be disputed
+    ///
+    /// if any allowed_takers are given then only those main A/c's can take the escrow
+    ///
+    /// if asset_id_max is non 0 it is a collection bid that can be taken with any asset
+    /// from asset_id to asset_id_max, so a category of assets should be given a range of ids,
+    /// asset_id can't be 0 and asset_id_max can't be below it
+    ///
+    /// only amount, time and asset_id are required, the data can stop after any later field
+    /// and the fields left out read as zero
     InitEscrow(InitEscData),
 
     /// Accounts expected for Taking Escrow
     /// 0. [signer] taker's main A/c (signed) to transfer assets from his game_acc to other game_acc
+    ///    it must be one of the escrow's allowed_takers when the escrow is private
     /// 1. [writable] Escrow A/c
     /// 2. [writable] initialisers Game Program A/c
     /// 3. [writable] Taker's Game Program A/c
-    TakeEscrow,
+    /// 4. [writable] initialisers main A/c for dutch auctions,
+    ///    or the escrow's Market A/c if a rent request is listed in one
+    /// 5. [] System Program, used only by dutch auctions to pay the price
+    ///
+    /// for a dutch auction the taker's main A/c must be writable, the current price is
+    /// transferred from it to the initialiser and fails if it is above max_price
+    ///
+    /// asset_id is the asset the taker gives for a collection bid, 0 for the escrow's asset_id,
+    /// arbiter and oracle must be the escrow's, which come from the Market A/c the initialiser
+    /// chose, so the taker agrees to who can resolve a dispute and who confirms delivery,
+    /// the data can stop after any field and the fields left out read as zero
+    TakeEscrow(TakeEscData),
 
     /// 0. [writable] initialiser's main A/c to transfer back rent fees
     /// 1. [writable] Escrow A/c
     /// 2. [writable] initialisers Game Program A/c
     /// 3. [writable] Taker's Game Program A/c
+    /// 4. [writable] taker's main A/c, paid the rent if the escrow holds it till the rental ends
+    /// 5. [writable, signer] keeper's A/c that cranks the revert, paid KEEPER_BOUNTY
+    ///    unless it is the initialiser's main A/c
+    /// 6. [writable] History A/c of the asset
+    ///
+    /// anyone can revert once the rental time has passed, a disputed escrow can only be
+    /// reverted DISPUTE_TIMEOUT after the dispute was raised
     Revert,
 
     /// 0.[writable] game_account where we will assign user_space ownership
@@ -37,6 +146,181 @@ pub enum InstructionType {
     /// currently restrictions on adding asset are not implemented
     /// u64 is the asset id and it should not be 0 since 0 will be used to represent Null
     AddAsset(u64),
+
+    /// Cancels an escrow that has not been taken and refunds all lamports to the initialiser
+    /// 0. [writable] Escrow A/c
+    /// 1. [writable] initialisers main A/c
+    /// 2. [writable] sellers Game Program A/c for auctions, to give back the asset held since listing,
+    ///    or the escrow's Market A/c, only if a rent request is listed in one
+    /// 3. [writable] initialisers Reputation A/c, only if the escrow hasn't expired
+    ///
+    /// initialisers main A/c must sign unless the escrow has expired,
+    /// after which anyone can close it. A cancel before expiry counts against the initialiser
+    CancelEscrow,
+
+    /// Lists an asset in an english auction, the Escrow A/c should hold only the rent-exempt lamports
+    /// 0. [writable] Escrow A/c
+    /// 1. [writable] sellers Game Program A/c, the asset is held in its lent till the auction ends
+    /// 2. [signer] sellers main A/c
+    InitAuction(InitAuctionData),
+
+    /// Bids the given lamports, the previous highest bidder is refunded,
+    /// the first bid must be at least the reserve price, later ones must be above the highest bid
+    /// and at least min_increment more than it
+    /// 0. [writable, signer] bidder's main A/c
+    /// 1. [writable] Escrow A/c
+    /// 2. [] bidder's Game Program A/c
+    /// 3. [] System Program
+    /// 4. [writable] previous highest bidder's main A/c, only needed if there is a bid already
+    PlaceBid(u64),
+
+    /// Settles an auction after its end_time, can be called by anyone
+    /// if there are no bids the Escrow A/c is closed, lamports and the held asset go back to the seller
+    /// 0. [writable] Escrow A/c
+    /// 1. [writable] sellers main A/c
+    /// 2. [writable] sellers Game Program A/c
+    /// 3. [writable] highest bidder's Game Program A/c, only needed if there are bids
+    /// 4. [writable] highest bidder's main A/c, refunded if the asset can't be transferred
+    ///
+    /// a rented asset comes back to the seller with Revert
+    SettleAuction,
+
+    /// Lists an asset in a dutch auction whose price declines linearly from start_price to end_price
+    /// 0. [writable] Escrow A/c
+    /// 1. [writable] sellers Game Program A/c, the asset is held in its lent till the auction ends
+    /// 2. [signer] sellers main A/c
+    ///
+    /// it is taken with TakeEscrow and a rented asset comes back to the seller with Revert
+    InitDutchAuction(InitDutchAuctionData),
+
+    /// Proposes a different amount or time for an open rent request escrow
+    /// 0. [writable] Offer A/c
+    /// 1. [] Escrow A/c
+    /// 2. [signer] offerer's main A/c
+    /// 3. [] offerer's Game Program A/c holding the escrow's asset
+    ///
+    /// arbiter and oracle must be the escrow's as for TakeEscrow, they can be left out
+    /// of the data for an escrow without them
+    MakeOffer(OfferData),
+
+    /// Amends the escrow to the offer's terms and takes it in the same step,
+    /// the Offer A/c is closed and its lamports go back to the offerer
+    /// 0. [writable, signer] initialisers main A/c
+    /// 1. [writable] Escrow A/c
+    /// 2. [writable] Offer A/c
+    /// 3. [writable] initialisers Game Program A/c
+    /// 4. [writable] offerer's Game Program A/c
+    /// 5. [writable] offerer's main A/c
+    /// 6. [writable] the escrow's Market A/c, only if it is listed in one
+    ///
+    /// if the offer's amount is more than the escrow holds the initialiser deposits the difference
+    /// in the Escrow A/c earlier in the same transaction, anything above the amount is refunded
+    AcceptOffer,
+
+    /// Creates a Market A/c that indexes open rent request escrows, english and dutch auctions
+    /// aren't listed in one. a market isn't tied to a game and any main A/c can create one as
+    /// its authority, clients choose which Market A/c's they trust by address and authority
+    /// 0. [writable] Market A/c
+    /// 1. [signer] market authority's main A/c
+    /// 2. [] optional arbiter's main A/c, resolves disputes of rent requests initialised with this market
+    /// 3. [] optional game server's oracle key, confirms delivery of assets rented through this market
+    ///
+    /// pass Pubkey::default() as the arbiter to have an oracle without an arbiter
+    InitMarket,
+
+    /// Succeeds only if the player can use the asset right now, i.e. it is owned or rented
+    /// and not lent out, meant to be called by game programs through CPI
+    /// 0. [] player's Game Program A/c
+    /// 1. [] player's main A/c
+    AssertOwns(u64),
+
+    /// Locks the Game Program A/c for a match, no asset can be put on rent or sold until the
+    /// same authority unlocks it. Reverts of expired rentals still go through
+    /// 0. [writable] player's Game Program A/c
+    /// 1. [signer] player's main A/c
+    /// 2. [signer] game program's authority, usually a PDA signing through invoke_signed
+    LockForMatch,
+
+    /// Unlocks a Game Program A/c locked with LockForMatch
+    /// 0. [writable] player's Game Program A/c
+    /// 1. [signer] the authority that locked it
+    UnlockAfterMatch,
+
+    /// Upgrades an account of any type from an older layout version to the current one in place,
+    /// can be called by anyone and does nothing if the account is already current
+    /// 0. [writable] the account to migrate
+    /// 1. [writable, signer] payer of the extra rent for the larger account
+    /// 2. [] System Program
+    MigrateAccount,
+
+    /// Creates the History A/c of an asset at the PDA of [HISTORY_SEED, asset_id le bytes]
+    /// 0. [writable] History A/c PDA
+    /// 1. [writable, signer] payer of the History A/c's rent
+    /// 2. [] System Program
+    ///
+    /// TakeEscrow, Revert, SettleAuction with bids, AcceptOffer and ResolveDispute record
+    /// an entry in it and fail unless it is passed after their other accounts,
+    /// so it has to be created before the asset is first rented or sold
+    InitHistory(u64),
+
+    /// Creates the Reputation A/c of a wallet at the PDA of [REPUTATION_SEED, wallet]
+    /// 0. [writable] Reputation A/c PDA
+    /// 1. [] the wallet's main A/c
+    /// 2. [writable, signer] payer of the Reputation A/c's rent
+    /// 3. [] System Program
+    ///
+    /// TakeEscrow, Revert and SettleAuction count a completed deal in the Reputation A/c's of
+    /// the parties passed after their other accounts. CancelEscrow before expiry and
+    /// ResolveDispute with a party at fault fail unless the Reputation A/c it counts against
+    /// is passed there. TakeEscrow, MakeOffer and PlaceBid need the taker's Reputation A/c
+    /// there when the escrow has a min_reputation
+    InitReputation,
+
+    /// Freezes a taken rent request escrow that has an arbiter, it can't be reverted
+    /// till the arbiter resolves it or DISPUTE_TIMEOUT passes
+    /// 0. [writable] Escrow A/c
+    /// 1. [signer] initialisers or taker's main A/c
+    RaiseDispute,
+
+    /// Splits the held rent between renter and lender and decides where the asset ends up,
+    /// the Escrow A/c is closed and its rent-exempt lamports go to the initialiser
+    /// 0. [signer] the escrow's arbiter
+    /// 1. [writable] Escrow A/c
+    /// 2. [writable] initialisers main A/c
+    /// 3. [writable] taker's main A/c
+    /// 4. [writable] initialisers Game Program A/c
+    /// 5. [writable] Taker's Game Program A/c
+    /// 6. [writable] Reputation A/c of the party at fault, only if at_fault isn't DISPUTE_FAULT_NONE
+    ResolveDispute(ResolveDisputeData),
+
+    /// Confirms the rented asset was delivered to the renter in-game and releases the held rent
+    /// to the lender, needed for rent requests initialised with a market that has an oracle
+    /// 0. [writable] Escrow A/c
+    /// 1. [writable] taker's main A/c
+    /// 2. [signer] the escrow's oracle, or the Instructions sysvar when the instruction before
+    ///    this one is an Ed25519 program instruction verifying the oracle's signature
+    ///    over Escrow::delivery_attestation
+    ///
+    /// if delivery is never confirmed Revert refunds the rent to the initialiser
+    ConfirmDelivery,
+
+    /// Closes a Game Program A/c that has no rented assets and none lent out,
+    /// its lamports go back to the userspace owner
+    /// 0. [writable] Game Program A/c to close
+    /// 1. [writable, signer] userspace owner's main A/c
+    /// 2. [writable] optional Game Program A/c of the same owner that gets the remaining owned assets
+    ///
+    /// without the last A/c the owned assets are given up with the account
+    CloseGameAcc,
+
+    /// Withdraws an offer, the Offer A/c is closed and its lamports go back to the offerer
+    /// 0. [writable] Offer A/c
+    /// 1. [writable] offerer's main A/c
+    /// 2. [] the offer's Escrow A/c
+    ///
+    /// offerer's main A/c must sign unless the escrow is gone, i.e. closed, taken or expired,
+    /// after which anyone can close the offer
+    CancelOffer,
 }
 
 impl InstructionType {
@@ -46,7 +330,7 @@ impl InstructionType {
         Ok(match mode {
             0 => Self::InitEscrow(Self::unpack_struct(rest)?),
 
-            1 => Self::TakeEscrow,
+            1 => Self::TakeEscrow(Self::unpack_take(rest)?),
 
             2 => Self::Revert,
 
@@ -54,11 +338,142 @@ impl InstructionType {
 
             4 => Self::AddAsset(Self::unpack_asset(rest)?),
 
+            5 => Self::CancelEscrow,
+
+            6 => Self::InitAuction(Self::unpack_auction(rest)?),
+
+            7 => Self::PlaceBid(Self::unpack_u64(rest, 0)?),
+
+            8 => Self::SettleAuction,
+
+            9 => Self::InitDutchAuction(Self::unpack_dutch_auction(rest)?),
+
+            10 => Self::MakeOffer(Self::unpack_offer(rest)?),
+
+            11 => Self::AcceptOffer,
+
+            12 => Self::InitMarket,
+
+            13 => Self::AssertOwns(Self::unpack_u64(rest, 0)?),
+
+            14 => Self::LockForMatch,
+
+            15 => Self::UnlockAfterMatch,
+
+            16 => Self::MigrateAccount,
+
+            17 => Self::InitHistory(Self::unpack_u64(rest, 0)?),
+
+            18 => Self::InitReputation,
+
+            19 => Self::RaiseDispute,
+
+            20 => Self::ResolveDispute(Self::unpack_resolve_dispute(rest)?),
+
+            21 => Self::ConfirmDelivery,
+
+            22 => Self::CloseGameAcc,
+
+            23 => Self::CancelOffer,
+
             _ => return Err(InvalidInstruction.into()),
         })
     }
 
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+
+        match self {
+            Self::InitEscrow(data) => {
+                buf.push(0);
+                buf.extend_from_slice(&data.amount.to_le_bytes());
+                buf.extend_from_slice(&data.time.to_le_bytes());
+                buf.extend_from_slice(&data.asset_id.to_le_bytes());
+                for key in data.allowed_takers.iter() {
+                    buf.extend_from_slice(key.as_ref());
+                }
+                buf.extend_from_slice(&data.expires_at.to_le_bytes());
+                buf.extend_from_slice(&data.asset_id_max.to_le_bytes());
+                buf.extend_from_slice(&data.min_reputation.to_le_bytes());
+            }
+            Self::TakeEscrow(data) => {
+                buf.push(1);
+                buf.extend_from_slice(&data.asset_id.to_le_bytes());
+                buf.extend_from_slice(&data.max_price.to_le_bytes());
+                buf.extend_from_slice(data.arbiter.as_ref());
+                buf.extend_from_slice(data.oracle.as_ref());
+            }
+            Self::Revert => buf.push(2),
+            Self::InitGameAcc => buf.push(3),
+            Self::AddAsset(asset_id) => {
+                buf.push(4);
+                buf.extend_from_slice(&asset_id.to_le_bytes());
+            }
+            Self::CancelEscrow => buf.push(5),
+            Self::InitAuction(data) => {
+                buf.push(6);
+                buf.extend_from_slice(&data.reserve_price.to_le_bytes());
+                buf.extend_from_slice(&data.min_increment.to_le_bytes());
+                buf.extend_from_slice(&data.time.to_le_bytes());
+                buf.extend_from_slice(&data.asset_id.to_le_bytes());
+                buf.extend_from_slice(&data.end_time.to_le_bytes());
+                buf.extend_from_slice(&data.min_reputation.to_le_bytes());
+            }
+            Self::PlaceBid(bid) => {
+                buf.push(7);
+                buf.extend_from_slice(&bid.to_le_bytes());
+            }
+            Self::SettleAuction => buf.push(8),
+            Self::InitDutchAuction(data) => {
+                buf.push(9);
+                buf.extend_from_slice(&data.start_price.to_le_bytes());
+                buf.extend_from_slice(&data.end_price.to_le_bytes());
+                buf.extend_from_slice(&data.time.to_le_bytes());
+                buf.extend_from_slice(&data.asset_id.to_le_bytes());
+                buf.extend_from_slice(&data.start_time.to_le_bytes());
+                buf.extend_from_slice(&data.end_time.to_le_bytes());
+                buf.extend_from_slice(&data.min_reputation.to_le_bytes());
+            }
+            Self::MakeOffer(data) => {
+                buf.push(10);
+                buf.extend_from_slice(&data.amount.to_le_bytes());
+                buf.extend_from_slice(&data.time.to_le_bytes());
+                buf.extend_from_slice(&data.asset_id.to_le_bytes());
+                buf.extend_from_slice(data.arbiter.as_ref());
+                buf.extend_from_slice(data.oracle.as_ref());
+            }
+            Self::AcceptOffer => buf.push(11),
+            Self::InitMarket => buf.push(12),
+            Self::AssertOwns(asset_id) => {
+                buf.push(13);
+                buf.extend_from_slice(&asset_id.to_le_bytes());
+            }
+            Self::LockForMatch => buf.push(14),
+            Self::UnlockAfterMatch => buf.push(15),
+            Self::MigrateAccount => buf.push(16),
+            Self::InitHistory(asset_id) => {
+                buf.push(17);
+                buf.extend_from_slice(&asset_id.to_le_bytes());
+            }
+            Self::InitReputation => buf.push(18),
+            Self::RaiseDispute => buf.push(19),
+            Self::ResolveDispute(data) => {
+                buf.push(20);
+                buf.extend_from_slice(&data.renter_refund.to_le_bytes());
+                buf.push(data.renter_keeps_asset as u8);
+                buf.push(data.at_fault);
+            }
+            Self::ConfirmDelivery => buf.push(21),
+            Self::CloseGameAcc => buf.push(22),
+            Self::CancelOffer => buf.push(23),
+        }
+
+        buf
+    }
+
     fn unpack_struct(input: &[u8]) -> Result<InitEscData, ProgramError> {
+        let input = &Self::with_optional_fields(input, &INIT_ESC_FIELD_ENDS)?;
+
         let amount: u64 = input
             .get(..8)
             .and_then(|slice| slice.try_into().ok()) // convert the slice into u8 array
@@ -77,15 +492,149 @@ impl InstructionType {
             .map(u64::from_le_bytes)
             .ok_or(InvalidInstruction)?;
 
+        let mut allowed_takers = [Pubkey::default(); ALLOWED_TAKERS_LEN];
+
+        for (i, key) in allowed_takers.iter_mut().enumerate() {
+            let start = 24 + i * 32;
+
+            *key = input
+                .get(start..(start + 32))
+                .and_then(|slice| slice.try_into().ok())
+                .map(Pubkey::new_from_array)
+                .ok_or(InvalidInstruction)?;
+        }
+
+        let expires_at: i64 = input
+            .get(120..128)
+            .and_then(|slice| slice.try_into().ok()) // convert the slice into u8 array
+            .map(i64::from_le_bytes)
+            .ok_or(InvalidInstruction)?;
+
+        let asset_id_max: u64 = input
+            .get(128..136)
+            .and_then(|slice| slice.try_into().ok()) // convert the slice into u8 array
+            .map(u64::from_le_bytes)
+            .ok_or(InvalidInstruction)?;
+
         let data = InitEscData {
-            amount: amount,
-            time: time,
-            asset_id: asset_id,
+            amount,
+            time,
+            asset_id,
+            allowed_takers,
+            expires_at,
+            asset_id_max,
+            min_reputation: Self::unpack_u64(input, 136)?,
+        };
+
+        Ok(data)
+    }
+
+    fn unpack_take(input: &[u8]) -> Result<TakeEscData, ProgramError> {
+        let input = &Self::with_optional_fields(input, &TAKE_ESC_FIELD_ENDS)?;
+
+        Ok(TakeEscData {
+            asset_id: Self::unpack_u64(input, 0)?,
+            max_price: Self::unpack_u64(input, 8)?,
+            arbiter: Self::unpack_pubkey(input, 16)?,
+            oracle: Self::unpack_pubkey(input, 48)?,
+        })
+    }
+
+    fn unpack_auction(input: &[u8]) -> Result<InitAuctionData, ProgramError> {
+        let data = InitAuctionData {
+            reserve_price: Self::unpack_u64(input, 0)?,
+            min_increment: Self::unpack_u64(input, 8)?,
+            time: Self::unpack_u64(input, 16)?,
+            asset_id: Self::unpack_u64(input, 24)?,
+            end_time: Self::unpack_u64(input, 32)? as i64,
+            min_reputation: Self::unpack_u64(input, 40)?,
         };
 
         Ok(data)
     }
 
+    fn unpack_dutch_auction(input: &[u8]) -> Result<InitDutchAuctionData, ProgramError> {
+        let data = InitDutchAuctionData {
+            start_price: Self::unpack_u64(input, 0)?,
+            end_price: Self::unpack_u64(input, 8)?,
+            time: Self::unpack_u64(input, 16)?,
+            asset_id: Self::unpack_u64(input, 24)?,
+            start_time: Self::unpack_u64(input, 32)? as i64,
+            end_time: Self::unpack_u64(input, 40)? as i64,
+            min_reputation: Self::unpack_u64(input, 48)?,
+        };
+
+        Ok(data)
+    }
+
+    fn unpack_resolve_dispute(input: &[u8]) -> Result<ResolveDisputeData, ProgramError> {
+        let renter_keeps_asset = match input.get(8) {
+            Some(0) => false,
+            Some(1) => true,
+            _ => return Err(InvalidInstruction.into()),
+        };
+
+        let at_fault = match input.get(9) {
+            Some(&fault) if fault <= DISPUTE_FAULT_LENDER => fault,
+            _ => return Err(InvalidInstruction.into()),
+        };
+
+        let data = ResolveDisputeData {
+            renter_refund: Self::unpack_u64(input, 0)?,
+            renter_keeps_asset,
+            at_fault,
+        };
+
+        Ok(data)
+    }
+
+    fn unpack_offer(input: &[u8]) -> Result<OfferData, ProgramError> {
+        let input = &Self::with_optional_fields(input, &OFFER_FIELD_ENDS)?;
+
+        let data = OfferData {
+            amount: Self::unpack_u64(input, 0)?,
+            time: Self::unpack_u64(input, 8)?,
+            asset_id: Self::unpack_u64(input, 16)?,
+            arbiter: Self::unpack_pubkey(input, 24)?,
+            oracle: Self::unpack_pubkey(input, 56)?,
+        };
+
+        Ok(data)
+    }
+
+    // data that stops at the end of one of the fields reads the fields after it as zero,
+    // so clients built before the later fields were appended keep working
+    fn with_optional_fields(input: &[u8], field_ends: &[usize]) -> Result<Vec<u8>, ProgramError> {
+        let full_len = field_ends.last().copied().unwrap_or(0);
+
+        if input.len() < full_len && !field_ends.contains(&input.len()) {
+            return Err(InvalidInstruction.into());
+        }
+
+        let mut data = input.to_vec();
+        if data.len() < full_len {
+            data.resize(full_len, 0);
+        }
+
+        Ok(data)
+    }
+
+    fn unpack_u64(input: &[u8], start: usize) -> Result<u64, ProgramError> {
+        input
+            .get(start..(start + 8))
+            .and_then(|slice| slice.try_into().ok()) // convert the slice into u8 array
+            .map(u64::from_le_bytes)
+            .ok_or_else(|| InvalidInstruction.into())
+    }
+
+    fn unpack_pubkey(input: &[u8], start: usize) -> Result<Pubkey, ProgramError> {
+        input
+            .get(start..(start + 32))
+            .and_then(|slice| slice.try_into().ok())
+            .map(Pubkey::new_from_array)
+            .ok_or_else(|| InvalidInstruction.into())
+    }
+
     fn unpack_asset(input: &[u8]) -> Result<u64, ProgramError> {
         let asset_id: u64 = input
             .get(..8)
@@ -96,3 +645,535 @@ impl InstructionType {
         Ok(asset_id)
     }
 }
+
+// builders for clients, the accounts follow the order documented on each InstructionType variant
+
+pub fn init_escrow(
+    program_id: &Pubkey,
+    escrow_acc: &Pubkey,
+    initialiser_game_acc: &Pubkey,
+    initialiser_main_acc: &Pubkey,
+    market_acc: Option<&Pubkey>,
+    data: InitEscData,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*escrow_acc, false),
+        AccountMeta::new_readonly(*initialiser_game_acc, false),
+        AccountMeta::new_readonly(*initialiser_main_acc, false),
+    ];
+    if let Some(market_acc) = market_acc {
+        accounts.push(AccountMeta::new(*market_acc, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: InstructionType::InitEscrow(data).pack(),
+    }
+}
+
+/// extra_acc is the initialisers main A/c for a dutch auction,
+/// or the escrow's Market A/c if a rent request is listed in one,
+/// a non 0 max_price takes a dutch auction and passes the System Program for the payment
+pub fn take_escrow(
+    program_id: &Pubkey,
+    taker_main_acc: &Pubkey,
+    escrow_acc: &Pubkey,
+    initialiser_game_acc: &Pubkey,
+    taker_game_acc: &Pubkey,
+    extra_acc: Option<&Pubkey>,
+    data: TakeEscData,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*taker_main_acc, true),
+        AccountMeta::new(*escrow_acc, false),
+        AccountMeta::new(*initialiser_game_acc, false),
+        AccountMeta::new(*taker_game_acc, false),
+    ];
+    if let Some(extra_acc) = extra_acc {
+        accounts.push(AccountMeta::new(*extra_acc, false));
+    }
+    // a dutch auction can end at a price of 0 and be taken with max_price 0,
+    // so the System Program is passed for every take
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: InstructionType::TakeEscrow(data).pack(),
+    }
+}
+
+pub fn revert(
+    program_id: &Pubkey,
+    initialiser_main_acc: &Pubkey,
+    escrow_acc: &Pubkey,
+    initialiser_game_acc: &Pubkey,
+    taker_game_acc: &Pubkey,
+    taker_main_acc: &Pubkey,
+    keeper_acc: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*initialiser_main_acc, false),
+            AccountMeta::new(*escrow_acc, false),
+            AccountMeta::new(*initialiser_game_acc, false),
+            AccountMeta::new(*taker_game_acc, false),
+            AccountMeta::new(*taker_main_acc, false),
+            AccountMeta::new(*keeper_acc, true),
+        ],
+        data: InstructionType::Revert.pack(),
+    }
+}
+
+pub fn init_game_acc(program_id: &Pubkey, game_acc: &Pubkey, main_acc: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*game_acc, false),
+            AccountMeta::new_readonly(*main_acc, false),
+        ],
+        data: InstructionType::InitGameAcc.pack(),
+    }
+}
+
+pub fn add_asset(program_id: &Pubkey, game_acc: &Pubkey, asset_id: u64) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![AccountMeta::new(*game_acc, false)],
+        data: InstructionType::AddAsset(asset_id).pack(),
+    }
+}
+
+/// initialiser_signs can be false once the escrow has expired,
+/// extra_acc is the sellers Game Program A/c for an auction,
+/// or the escrow's Market A/c if a rent request is listed in one
+pub fn cancel_escrow(
+    program_id: &Pubkey,
+    escrow_acc: &Pubkey,
+    initialiser_main_acc: &Pubkey,
+    initialiser_signs: bool,
+    extra_acc: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*escrow_acc, false),
+        AccountMeta::new(*initialiser_main_acc, initialiser_signs),
+    ];
+    if let Some(extra_acc) = extra_acc {
+        accounts.push(AccountMeta::new(*extra_acc, false));
+    }
+    // a cancel by the initialiser before expiry counts against their reputation
+    if initialiser_signs {
+        let (initialiser_reputation_acc, _) =
+            Reputation::find_address(program_id, initialiser_main_acc);
+        accounts.push(AccountMeta::new(initialiser_reputation_acc, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: InstructionType::CancelEscrow.pack(),
+    }
+}
+
+pub fn init_auction(
+    program_id: &Pubkey,
+    escrow_acc: &Pubkey,
+    seller_game_acc: &Pubkey,
+    seller_main_acc: &Pubkey,
+    data: InitAuctionData,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*escrow_acc, false),
+            AccountMeta::new(*seller_game_acc, false),
+            AccountMeta::new_readonly(*seller_main_acc, true),
+        ],
+        data: InstructionType::InitAuction(data).pack(),
+    }
+}
+
+pub fn place_bid(
+    program_id: &Pubkey,
+    bidder_main_acc: &Pubkey,
+    escrow_acc: &Pubkey,
+    bidder_game_acc: &Pubkey,
+    prev_bidder_main_acc: Option<&Pubkey>,
+    bid: u64,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*bidder_main_acc, true),
+        AccountMeta::new(*escrow_acc, false),
+        AccountMeta::new_readonly(*bidder_game_acc, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(prev_bidder_main_acc) = prev_bidder_main_acc {
+        accounts.push(AccountMeta::new(*prev_bidder_main_acc, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: InstructionType::PlaceBid(bid).pack(),
+    }
+}
+
+/// winner_accs are the highest bidder's Game Program A/c and main A/c, None if there are no bids
+pub fn settle_auction(
+    program_id: &Pubkey,
+    escrow_acc: &Pubkey,
+    seller_main_acc: &Pubkey,
+    seller_game_acc: &Pubkey,
+    winner_accs: Option<(&Pubkey, &Pubkey)>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*escrow_acc, false),
+        AccountMeta::new(*seller_main_acc, false),
+        AccountMeta::new(*seller_game_acc, false),
+    ];
+    if let Some((winner_game_acc, winner_main_acc)) = winner_accs {
+        accounts.push(AccountMeta::new(*winner_game_acc, false));
+        accounts.push(AccountMeta::new(*winner_main_acc, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: InstructionType::SettleAuction.pack(),
+    }
+}
+
+pub fn init_dutch_auction(
+    program_id: &Pubkey,
+    escrow_acc: &Pubkey,
+    seller_game_acc: &Pubkey,
+    seller_main_acc: &Pubkey,
+    data: InitDutchAuctionData,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*escrow_acc, false),
+            AccountMeta::new(*seller_game_acc, false),
+            AccountMeta::new_readonly(*seller_main_acc, true),
+        ],
+        data: InstructionType::InitDutchAuction(data).pack(),
+    }
+}
+
+pub fn make_offer(
+    program_id: &Pubkey,
+    offer_acc: &Pubkey,
+    escrow_acc: &Pubkey,
+    offerer_main_acc: &Pubkey,
+    offerer_game_acc: &Pubkey,
+    data: OfferData,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*offer_acc, false),
+            AccountMeta::new_readonly(*escrow_acc, false),
+            AccountMeta::new_readonly(*offerer_main_acc, true),
+            AccountMeta::new_readonly(*offerer_game_acc, false),
+        ],
+        data: InstructionType::MakeOffer(data).pack(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn accept_offer(
+    program_id: &Pubkey,
+    initialiser_main_acc: &Pubkey,
+    escrow_acc: &Pubkey,
+    offer_acc: &Pubkey,
+    initialiser_game_acc: &Pubkey,
+    offerer_game_acc: &Pubkey,
+    offerer_main_acc: &Pubkey,
+    market_acc: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*initialiser_main_acc, true),
+        AccountMeta::new(*escrow_acc, false),
+        AccountMeta::new(*offer_acc, false),
+        AccountMeta::new(*initialiser_game_acc, false),
+        AccountMeta::new(*offerer_game_acc, false),
+        AccountMeta::new(*offerer_main_acc, false),
+    ];
+    if let Some(market_acc) = market_acc {
+        accounts.push(AccountMeta::new(*market_acc, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: InstructionType::AcceptOffer.pack(),
+    }
+}
+
+pub fn init_market(
+    program_id: &Pubkey,
+    market_acc: &Pubkey,
+    authority: &Pubkey,
+    arbiter: Option<&Pubkey>,
+    oracle: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*market_acc, false),
+        AccountMeta::new_readonly(*authority, true),
+    ];
+    if arbiter.is_some() || oracle.is_some() {
+        let arbiter = arbiter.copied().unwrap_or_default();
+        accounts.push(AccountMeta::new_readonly(arbiter, false));
+    }
+    if let Some(oracle) = oracle {
+        accounts.push(AccountMeta::new_readonly(*oracle, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: InstructionType::InitMarket.pack(),
+    }
+}
+
+pub fn assert_owns(
+    program_id: &Pubkey,
+    game_acc: &Pubkey,
+    player_main_acc: &Pubkey,
+    asset_id: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*game_acc, false),
+            AccountMeta::new_readonly(*player_main_acc, false),
+        ],
+        data: InstructionType::AssertOwns(asset_id).pack(),
+    }
+}
+
+pub fn lock_for_match(
+    program_id: &Pubkey,
+    game_acc: &Pubkey,
+    player_main_acc: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*game_acc, false),
+            AccountMeta::new_readonly(*player_main_acc, true),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data: InstructionType::LockForMatch.pack(),
+    }
+}
+
+pub fn unlock_after_match(
+    program_id: &Pubkey,
+    game_acc: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*game_acc, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data: InstructionType::UnlockAfterMatch.pack(),
+    }
+}
+
+pub fn migrate_account(program_id: &Pubkey, account: &Pubkey, payer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: InstructionType::MigrateAccount.pack(),
+    }
+}
+
+pub fn init_history(program_id: &Pubkey, payer: &Pubkey, asset_id: u64) -> Instruction {
+    let (history_acc, _) = History::find_address(program_id, asset_id);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(history_acc, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: InstructionType::InitHistory(asset_id).pack(),
+    }
+}
+
+// appends the asset's History A/c that take_escrow, revert, settle_auction, accept_offer
+// and resolve_dispute instructions need
+pub fn with_history(mut instruction: Instruction, asset_id: u64) -> Instruction {
+    let (history_acc, _) = History::find_address(&instruction.program_id, asset_id);
+
+    instruction
+        .accounts
+        .push(AccountMeta::new(history_acc, false));
+
+    instruction
+}
+
+pub fn init_reputation(program_id: &Pubkey, wallet: &Pubkey, payer: &Pubkey) -> Instruction {
+    let (reputation_acc, _) = Reputation::find_address(program_id, wallet);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(reputation_acc, false),
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: InstructionType::InitReputation.pack(),
+    }
+}
+
+// appends a wallet's Reputation A/c to an instruction that checks or updates reputation
+pub fn with_reputation(mut instruction: Instruction, wallet: &Pubkey) -> Instruction {
+    let (reputation_acc, _) = Reputation::find_address(&instruction.program_id, wallet);
+
+    instruction
+        .accounts
+        .push(AccountMeta::new(reputation_acc, false));
+
+    instruction
+}
+
+pub fn raise_dispute(
+    program_id: &Pubkey,
+    escrow_acc: &Pubkey,
+    raiser_main_acc: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*escrow_acc, false),
+            AccountMeta::new_readonly(*raiser_main_acc, true),
+        ],
+        data: InstructionType::RaiseDispute.pack(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn resolve_dispute(
+    program_id: &Pubkey,
+    arbiter: &Pubkey,
+    escrow_acc: &Pubkey,
+    initialiser_main_acc: &Pubkey,
+    taker_main_acc: &Pubkey,
+    initialiser_game_acc: &Pubkey,
+    taker_game_acc: &Pubkey,
+    data: ResolveDisputeData,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*arbiter, true),
+        AccountMeta::new(*escrow_acc, false),
+        AccountMeta::new(*initialiser_main_acc, false),
+        AccountMeta::new(*taker_main_acc, false),
+        AccountMeta::new(*initialiser_game_acc, false),
+        AccountMeta::new(*taker_game_acc, false),
+    ];
+    // the dispute counts against the party at fault
+    if data.at_fault != DISPUTE_FAULT_NONE {
+        let at_fault_main_acc = if data.at_fault == DISPUTE_FAULT_RENTER {
+            initialiser_main_acc
+        } else {
+            taker_main_acc
+        };
+        let (at_fault_reputation_acc, _) = Reputation::find_address(program_id, at_fault_main_acc);
+        accounts.push(AccountMeta::new(at_fault_reputation_acc, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: InstructionType::ResolveDispute(data).pack(),
+    }
+}
+
+// the oracle signs the transaction itself
+pub fn confirm_delivery(
+    program_id: &Pubkey,
+    escrow_acc: &Pubkey,
+    taker_main_acc: &Pubkey,
+    oracle: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*escrow_acc, false),
+            AccountMeta::new(*taker_main_acc, false),
+            AccountMeta::new_readonly(*oracle, true),
+        ],
+        data: InstructionType::ConfirmDelivery.pack(),
+    }
+}
+
+// must come right after an Ed25519 program instruction with the oracle's signature
+pub fn confirm_delivery_with_attestation(
+    program_id: &Pubkey,
+    escrow_acc: &Pubkey,
+    taker_main_acc: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*escrow_acc, false),
+            AccountMeta::new(*taker_main_acc, false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+        data: InstructionType::ConfirmDelivery.pack(),
+    }
+}
+
+pub fn close_game_acc(
+    program_id: &Pubkey,
+    game_acc: &Pubkey,
+    owner_main_acc: &Pubkey,
+    assets_to_game_acc: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*game_acc, false),
+        AccountMeta::new(*owner_main_acc, true),
+    ];
+    if let Some(assets_to_game_acc) = assets_to_game_acc {
+        accounts.push(AccountMeta::new(*assets_to_game_acc, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: InstructionType::CloseGameAcc.pack(),
+    }
+}
+
+/// offerer_signs can be false once the escrow is closed, taken or expired
+pub fn cancel_offer(
+    program_id: &Pubkey,
+    offer_acc: &Pubkey,
+    offerer_main_acc: &Pubkey,
+    escrow_acc: &Pubkey,
+    offerer_signs: bool,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*offer_acc, false),
+            AccountMeta::new(*offerer_main_acc, offerer_signs),
+            AccountMeta::new_readonly(*escrow_acc, false),
+        ],
+        data: InstructionType::CancelOffer.pack(),
+    }
+}
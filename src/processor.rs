@@ -1,18 +1,35 @@
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    ed25519_program,
     entrypoint::ProgramResult,
     msg,
+    program::{invoke, invoke_signed},
     program_error::ProgramError,
-    program_pack::{IsInitialized, Pack},
+    program_pack::Pack,
     pubkey::Pubkey,
-    sysvar::{rent::Rent, Sysvar},
+    system_instruction,
+    sysvar::{
+        instructions::{load_current_index_checked, load_instruction_at_checked},
+        rent::Rent,
+        Sysvar,
+    },
 };
 
 use crate::error::EscrowError;
-use crate::instruction::{InitEscData, InstructionType};
-use crate::state::{Escrow, GameAcc};
+use crate::events::Event;
+use crate::instruction::{
+    InitAuctionData, InitDutchAuctionData, InitEscData, InstructionType, OfferData,
+    ResolveDisputeData, TakeEscData,
+};
+use crate::state::{
+    body_len, Escrow, GameAcc, History, HistoryEntry, Market, MarketEntry, Offer, Reputation,
+    ACCOUNT_HEADER_LEN, ACCOUNT_VERSION, CLOSED_ACC_TYPE, DISPUTE_FAULT_LENDER,
+    DISPUTE_FAULT_RENTER, DISPUTE_TIMEOUT, ESCROW_KIND_DUTCH_AUCTION, ESCROW_KIND_ENGLISH_AUCTION,
+    ESCROW_KIND_RENT_REQUEST, HISTORY_KIND_RENTAL, HISTORY_KIND_RETURN, HISTORY_KIND_SALE,
+    HISTORY_SEED, KEEPER_BOUNTY, LEGACY_HEADER_LEN, REPUTATION_SEED,
+};
+use crate::validation::{self, check_program_acc, load, load_uninitialized};
 use solana_program::clock::Clock;
-use std::convert::TryInto;
 
 pub struct Processor;
 
@@ -30,9 +47,9 @@ impl Processor {
                 Self::process_init_escrow(program_id, accounts, init_esc_data)
             }
 
-            InstructionType::TakeEscrow => {
+            InstructionType::TakeEscrow(take_data) => {
                 msg!(" Take Escrow instruction");
-                Self::process_take_escrow(program_id, accounts)
+                Self::process_take_escrow(program_id, accounts, take_data)
             }
 
             InstructionType::Revert => Self::revert(program_id, accounts),
@@ -40,6 +57,101 @@ impl Processor {
             InstructionType::InitGameAcc => Self::process_init_game_acc(program_id, accounts),
 
             InstructionType::AddAsset(asset_id) => Self::add_asset(program_id, accounts, asset_id),
+
+            InstructionType::CancelEscrow => {
+                msg!(" Cancel Escrow instruction");
+                Self::process_cancel_escrow(program_id, accounts)
+            }
+
+            InstructionType::InitAuction(init_auction_data) => {
+                msg!(" Init Auction instruction");
+                Self::process_init_auction(program_id, accounts, init_auction_data)
+            }
+
+            InstructionType::PlaceBid(bid) => {
+                msg!(" Place Bid instruction");
+                Self::process_place_bid(program_id, accounts, bid)
+            }
+
+            InstructionType::SettleAuction => {
+                msg!(" Settle Auction instruction");
+                Self::process_settle_auction(program_id, accounts)
+            }
+
+            InstructionType::InitDutchAuction(init_dutch_data) => {
+                msg!(" Init Dutch Auction instruction");
+                Self::process_init_dutch_auction(program_id, accounts, init_dutch_data)
+            }
+
+            InstructionType::MakeOffer(offer_data) => {
+                msg!(" Make Offer instruction");
+                Self::process_make_offer(program_id, accounts, offer_data)
+            }
+
+            InstructionType::AcceptOffer => {
+                msg!(" Accept Offer instruction");
+                Self::process_accept_offer(program_id, accounts)
+            }
+
+            InstructionType::InitMarket => {
+                msg!(" Init Market instruction");
+                Self::process_init_market(program_id, accounts)
+            }
+
+            InstructionType::AssertOwns(asset_id) => {
+                msg!(" Assert Owns instruction");
+                Self::process_assert_owns(program_id, accounts, asset_id)
+            }
+
+            InstructionType::LockForMatch => {
+                msg!(" Lock For Match instruction");
+                Self::process_lock_for_match(program_id, accounts)
+            }
+
+            InstructionType::UnlockAfterMatch => {
+                msg!(" Unlock After Match instruction");
+                Self::process_unlock_after_match(program_id, accounts)
+            }
+
+            InstructionType::MigrateAccount => {
+                msg!(" Migrate Account instruction");
+                Self::process_migrate_account(program_id, accounts)
+            }
+
+            InstructionType::InitHistory(asset_id) => {
+                msg!(" Init History instruction");
+                Self::process_init_history(program_id, accounts, asset_id)
+            }
+
+            InstructionType::InitReputation => {
+                msg!(" Init Reputation instruction");
+                Self::process_init_reputation(program_id, accounts)
+            }
+
+            InstructionType::RaiseDispute => {
+                msg!(" Raise Dispute instruction");
+                Self::process_raise_dispute(program_id, accounts)
+            }
+
+            InstructionType::ResolveDispute(resolve_data) => {
+                msg!(" Resolve Dispute instruction");
+                Self::process_resolve_dispute(program_id, accounts, resolve_data)
+            }
+
+            InstructionType::ConfirmDelivery => {
+                msg!(" Confirm Delivery instruction");
+                Self::process_confirm_delivery(program_id, accounts)
+            }
+
+            InstructionType::CloseGameAcc => {
+                msg!(" Close Game Acc instruction");
+                Self::process_close_game_acc(program_id, accounts)
+            }
+
+            InstructionType::CancelOffer => {
+                msg!(" Cancel Offer instruction");
+                Self::process_cancel_offer(program_id, accounts)
+            }
         }
     }
 
@@ -58,52 +170,92 @@ impl Processor {
 
         let initialiser_game_acc = next_account_info(acc_info_iter)?;
 
-        if initialiser_game_acc.owner != program_id || escrow_acc.owner != program_id {
-            return Err(ProgramError::IncorrectProgramId);
-        }
-
         let initialiser_main_acc = next_account_info(acc_info_iter)?;
 
+        let mut escrow_info = load_uninitialized::<Escrow>(program_id, escrow_acc)?;
+
+        check_program_acc::<GameAcc>(program_id, initialiser_game_acc)?;
+
         let rent = Rent::get()?;
 
-        if escrow_acc.lamports()
-            < (rent.minimum_balance(escrow_acc.data_len()) + init_esc_data.amount)
-        {
+        let required_lamports = rent
+            .minimum_balance(escrow_acc.data_len())
+            .checked_add(init_esc_data.amount)
+            .ok_or(EscrowError::AmountOverflow)?;
+
+        if escrow_acc.lamports() < required_lamports {
             return Err(ProgramError::InsufficientFunds);
         }
 
-        if escrow_acc.data.borrow()[0] == 2 {
-            // this implies it is game_acc not escrow
-            return Err(EscrowError::IncorrectAcc.into());
-        }
+        let clock = Clock::get()?;
 
-        if initialiser_game_acc.data.borrow()[0] != 2 {
-            // this implies it is  not game_acc
-            return Err(EscrowError::IncorrectAcc.into());
+        if init_esc_data.expires_at != 0 && init_esc_data.expires_at <= clock.unix_timestamp {
+            return Err(EscrowError::EscrowExpired.into());
         }
 
-        let mut escrow_info = Escrow::unpack_unchecked(&escrow_acc.data.borrow())?;
+        // 0 marks an empty slot, so it can't be asked for or start a collection range
 
-        if escrow_info.is_initialized() {
-            return Err(ProgramError::AccountAlreadyInitialized);
+        if init_esc_data.asset_id == 0
+            || (init_esc_data.asset_id_max != 0
+                && init_esc_data.asset_id_max < init_esc_data.asset_id)
+        {
+            return Err(EscrowError::InvalidInstruction.into());
         }
 
         // let taker_acc still be None it will be updated after take escrow
 
         escrow_info.acc_type = 1;
+        escrow_info.kind = ESCROW_KIND_RENT_REQUEST;
         escrow_info.is_taken = false;
         escrow_info.initialiser_main_acc = *initialiser_main_acc.key;
         escrow_info.initialiser_game_acc = *initialiser_game_acc.key;
         escrow_info.amount = init_esc_data.amount;
         escrow_info.time = init_esc_data.time;
         escrow_info.asset_id = init_esc_data.asset_id;
+        escrow_info.asset_id_max = init_esc_data.asset_id_max;
+        escrow_info.allowed_takers = init_esc_data.allowed_takers;
+        escrow_info.expires_at = init_esc_data.expires_at;
+        escrow_info.min_reputation = init_esc_data.min_reputation;
+
+        // list the escrow in the market if one is given
+
+        if let Ok(market_acc) = next_account_info(acc_info_iter) {
+            if !market_acc.is_writable {
+                return Err(EscrowError::MissingPermission.into());
+            }
+
+            let mut market_info = load::<Market>(program_id, market_acc)?;
+
+            escrow_info.arbiter = market_info.arbiter;
+            escrow_info.oracle = market_info.oracle;
+
+            let is_listed = market_info.insert(MarketEntry {
+                price: escrow_info.amount,
+                asset_id: escrow_info.asset_id,
+                escrow: *escrow_acc.key,
+            });
+
+            if is_listed {
+                escrow_info.market = *market_acc.key;
+
+                Market::pack(market_info, &mut market_acc.data.borrow_mut())?;
+            } else {
+                msg!("escrow is priced too low to be listed in the market");
+            }
+        }
+
+        Self::emit_escrow_created(escrow_acc.key, &escrow_info, clock.unix_timestamp);
 
         Escrow::pack(escrow_info, &mut escrow_acc.data.borrow_mut())?;
 
         Ok(())
     }
 
-    fn process_take_escrow(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    fn process_take_escrow(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        take_data: TakeEscData,
+    ) -> ProgramResult {
         let acc_info_iter = &mut accounts.iter();
 
         let taker_main_acc = next_account_info(acc_info_iter)?;
@@ -114,29 +266,54 @@ impl Processor {
 
         let escrow_acc = next_account_info(acc_info_iter)?;
 
-        if escrow_acc.owner != program_id {
-            return Err(ProgramError::IncorrectProgramId);
-        }
+        let mut escrow_info = load::<Escrow>(program_id, escrow_acc)?;
 
-        if escrow_acc.data.borrow()[0] != 1 {
-            // this implies it is not escrow
-            return Err(EscrowError::IncorrectAcc.into());
+        if escrow_info.kind == ESCROW_KIND_ENGLISH_AUCTION {
+            // english auctions are taken by bidding
+            return Err(EscrowError::WrongEscrowKind.into());
         }
 
-        let mut escrow_info = Escrow::unpack_unchecked(&escrow_acc.data.borrow())?;
-
         if escrow_info.is_taken {
             return Err(EscrowError::EscrowAlreadyTaken.into());
         }
 
-        let initialiser_game_acc = next_account_info(acc_info_iter)?;
+        if !escrow_info.is_allowed_taker(taker_main_acc.key) {
+            return Err(EscrowError::TakerNotAllowed.into());
+        }
 
-        let taker_game_acc = next_account_info(acc_info_iter)?;
+        let clock = Clock::get()?;
 
-        if taker_game_acc.owner != program_id {
-            return Err(ProgramError::IncorrectProgramId);
+        if escrow_info.is_expired(clock.unix_timestamp) {
+            return Err(EscrowError::EscrowExpired.into());
+        }
+
+        Self::check_reputation(program_id, accounts, taker_main_acc.key, &escrow_info)?;
+
+        // the arbiter and oracle come from a Market A/c anyone can create,
+        // so the taker must agree to them
+        if escrow_info.arbiter != take_data.arbiter {
+            return Err(EscrowError::ArbiterMismatch.into());
+        }
+
+        if escrow_info.oracle != take_data.oracle {
+            return Err(EscrowError::OracleMismatch.into());
         }
 
+        // for a collection bid the taker chooses which matching asset he gives,
+        // the escrow then holds that asset_id so it can be reverted
+
+        if take_data.asset_id != 0 {
+            if !escrow_info.accepts_asset(take_data.asset_id) {
+                return Err(EscrowError::AssetNotAccepted.into());
+            }
+
+            escrow_info.asset_id = take_data.asset_id;
+        }
+
+        let initialiser_game_acc = next_account_info(acc_info_iter)?;
+
+        let taker_game_acc = next_account_info(acc_info_iter)?;
+
         if !escrow_acc.is_writable
             || !initialiser_game_acc.is_writable
             || !taker_game_acc.is_writable
@@ -145,44 +322,170 @@ impl Processor {
         }
 
         if escrow_info.initialiser_game_acc != *initialiser_game_acc.key {
-            return Err(EscrowError::IncorrectAcc.into());
-        }
-
-        if taker_game_acc.data.borrow()[0] != 2 {
-            return Err(EscrowError::IncorrectAcc.into());
+            return Err(EscrowError::AccountMismatch.into());
         }
 
-        let mut taker_game_info = GameAcc::unpack_unchecked(&taker_game_acc.data.borrow())?;
-        let mut initialiser_game_info =
-            GameAcc::unpack_unchecked(&initialiser_game_acc.data.borrow())?;
+        let mut taker_game_info = load::<GameAcc>(program_id, taker_game_acc)?;
+        let mut initialiser_game_info = load::<GameAcc>(program_id, initialiser_game_acc)?;
 
         if taker_game_info.userspace_owner != *taker_main_acc.key {
-            return Err(EscrowError::IncorrectAcc.into());
+            return Err(EscrowError::OwnerMismatch.into());
         }
 
         // check if has required asset and if transfer is possible do it
 
-        GameAcc::transfer_asset(
-            &mut taker_game_info,
-            &mut initialiser_game_info,
-            escrow_info.asset_id,
-        )?;
+        if escrow_info.initialiser_is_renter() {
+            if escrow_info.initialiser_main_acc == *taker_main_acc.key {
+                return Err(EscrowError::OwnEscrow.into());
+            }
 
-        msg!("Escrow Taken");
+            GameAcc::transfer_asset(
+                &mut taker_game_info,
+                &mut initialiser_game_info,
+                escrow_info.asset_id,
+            )?;
 
-        escrow_info.is_taken = true;
-        escrow_info.taker_game_acc = *taker_game_acc.key;
+            msg!("Escrow Taken");
 
-        msg!("sending amount to taker_acc from escrow_acc");
+            if !escrow_info.holds_rent() {
+                msg!("sending amount to taker_acc from escrow_acc");
 
-        *(*taker_main_acc.lamports.borrow_mut()) = taker_main_acc
-            .lamports()
-            .checked_add(escrow_info.amount)
-            .ok_or(EscrowError::AmountOverflow)?;
+                Self::transfer_lamports(escrow_acc, taker_main_acc, escrow_info.amount)?;
+            }
 
-        *(*escrow_acc.lamports.borrow_mut()) = escrow_acc.lamports() - escrow_info.amount;
+            Self::delist_escrow(program_id, escrow_acc.key, &escrow_info, acc_info_iter)?;
 
-        let clock = Clock::get()?;
+            Event::EscrowTaken {
+                escrow: *escrow_acc.key,
+                taker_main_acc: *taker_main_acc.key,
+                taker_game_acc: *taker_game_acc.key,
+                asset_id: escrow_info.asset_id,
+                amount: escrow_info.amount,
+                timestamp: clock.unix_timestamp,
+            }
+            .emit();
+
+            Self::record_history(
+                program_id,
+                accounts,
+                escrow_info.asset_id,
+                HistoryEntry {
+                    kind: HISTORY_KIND_RENTAL,
+                    from_game_acc: *taker_game_acc.key,
+                    to_game_acc: *initialiser_game_acc.key,
+                    price: escrow_info.amount,
+                    start: clock.unix_timestamp,
+                    end: escrow_info.due_at(clock.unix_timestamp),
+                },
+            )?;
+        } else {
+            // dutch auction, the taker pays the current price to the initialiser
+
+            let initialiser_main_acc = next_account_info(acc_info_iter)?;
+
+            if !initialiser_main_acc.is_writable || !taker_main_acc.is_writable {
+                return Err(EscrowError::MissingPermission.into());
+            }
+
+            if escrow_info.initialiser_main_acc != *initialiser_main_acc.key {
+                return Err(EscrowError::AccountMismatch.into());
+            }
+
+            if escrow_info.initialiser_main_acc == *taker_main_acc.key {
+                return Err(EscrowError::OwnEscrow.into());
+            }
+
+            if escrow_info.time == 0 {
+                GameAcc::sell_held_asset(
+                    &mut initialiser_game_info,
+                    &mut taker_game_info,
+                    escrow_info.asset_id,
+                )?;
+            } else {
+                GameAcc::rent_held_asset(
+                    &mut initialiser_game_info,
+                    &mut taker_game_info,
+                    escrow_info.asset_id,
+                )?;
+            }
+
+            msg!("Escrow Taken");
+
+            let price = escrow_info.current_price(clock.unix_timestamp);
+
+            if price > take_data.max_price {
+                return Err(EscrowError::PriceTooHigh.into());
+            }
+
+            let system_program_acc = next_account_info(acc_info_iter)?;
+
+            msg!("sending price to initialiser_acc from taker_acc");
+
+            invoke(
+                &system_instruction::transfer(taker_main_acc.key, initialiser_main_acc.key, price),
+                &[
+                    taker_main_acc.clone(),
+                    initialiser_main_acc.clone(),
+                    system_program_acc.clone(),
+                ],
+            )?;
+
+            escrow_info.amount = price;
+
+            Event::EscrowTaken {
+                escrow: *escrow_acc.key,
+                taker_main_acc: *taker_main_acc.key,
+                taker_game_acc: *taker_game_acc.key,
+                asset_id: escrow_info.asset_id,
+                amount: price,
+                timestamp: clock.unix_timestamp,
+            }
+            .emit();
+
+            Self::record_history(
+                program_id,
+                accounts,
+                escrow_info.asset_id,
+                HistoryEntry {
+                    kind: if escrow_info.time == 0 {
+                        HISTORY_KIND_SALE
+                    } else {
+                        HISTORY_KIND_RENTAL
+                    },
+                    from_game_acc: *initialiser_game_acc.key,
+                    to_game_acc: *taker_game_acc.key,
+                    price,
+                    start: clock.unix_timestamp,
+                    end: escrow_info.due_at(clock.unix_timestamp),
+                },
+            )?;
+
+            if escrow_info.time == 0 {
+                // asset is sold so nothing is left to revert
+
+                Self::record_completed(
+                    program_id,
+                    accounts,
+                    &[*initialiser_main_acc.key, *taker_main_acc.key],
+                )?;
+
+                GameAcc::pack(
+                    initialiser_game_info,
+                    &mut initialiser_game_acc.data.borrow_mut(),
+                )?;
+                GameAcc::pack(taker_game_info, &mut taker_game_acc.data.borrow_mut())?;
+
+                msg!("sending rent-exempt amount to initialiser_acc from escrow_acc");
+
+                Self::close_acc(escrow_acc, initialiser_main_acc)?;
+
+                return Ok(());
+            }
+        }
+
+        escrow_info.is_taken = true;
+        escrow_info.taker_main_acc = *taker_main_acc.key;
+        escrow_info.taker_game_acc = *taker_game_acc.key;
         escrow_info.esc_taken_time = clock.unix_timestamp;
 
         Escrow::pack(escrow_info, &mut escrow_acc.data.borrow_mut())?;
@@ -206,15 +509,10 @@ impl Processor {
 
         let escrow_acc = next_account_info(acc_info_iter)?;
 
-        if escrow_acc.data.borrow()[0] != 1 {
-            // this implies it is not escrow
-            return Err(EscrowError::IncorrectAcc.into());
-        }
-
-        let escrow_info = Escrow::unpack_unchecked(&escrow_acc.data.borrow())?;
+        let escrow_info = load::<Escrow>(program_id, escrow_acc)?;
 
         if !escrow_info.is_taken {
-            return Err(EscrowError::InvalidInstruction.into());
+            return Err(EscrowError::EscrowNotTaken.into());
         }
 
         let initialiser_game_acc = next_account_info(acc_info_iter)?;
@@ -228,122 +526,1933 @@ impl Processor {
             return Err(EscrowError::MissingPermission.into());
         }
 
-        if escrow_acc.owner != program_id {
-            return Err(ProgramError::IncorrectProgramId);
-        }
-
         if escrow_info.initialiser_game_acc != *initialiser_game_acc.key
-            || escrow_info.taker_game_acc != *taker_game_acc.key 
+            || escrow_info.taker_game_acc != *taker_game_acc.key
             || escrow_info.initialiser_main_acc != *initialiser_main_acc.key
         {
-            return Err(EscrowError::IncorrectAcc.into());
+            return Err(EscrowError::AccountMismatch.into());
         }
 
         // check if given_time has passed
 
         let clock = Clock::get()?;
 
-        if (clock.unix_timestamp - escrow_info.esc_taken_time)
-            <= (escrow_info.time * 60).try_into().unwrap()
+        if escrow_info.is_disputed()
+            && clock.unix_timestamp < escrow_info.disputed_at.saturating_add(DISPUTE_TIMEOUT)
         {
-            return Err(EscrowError::InvalidInstruction.into());
+            // the arbiter still has time to resolve it
+            return Err(EscrowError::EscrowDisputed.into());
+        }
+
+        if clock.unix_timestamp <= escrow_info.due_at(escrow_info.esc_taken_time) {
+            return Err(EscrowError::RentalNotExpired.into());
         }
 
-        let mut taker_game_info = GameAcc::unpack_unchecked(&taker_game_acc.data.borrow())?;
-        let mut initialiser_game_info =
-            GameAcc::unpack_unchecked(&initialiser_game_acc.data.borrow())?;
+        let mut taker_game_info = load::<GameAcc>(program_id, taker_game_acc)?;
+        let mut initialiser_game_info = load::<GameAcc>(program_id, initialiser_game_acc)?;
 
         // revert back the asset
 
-        GameAcc::revert_asset(
-            &mut initialiser_game_info,
-            &mut taker_game_info,
-            escrow_info.asset_id,
+        if escrow_info.initialiser_is_renter() {
+            GameAcc::revert_asset(
+                &mut initialiser_game_info,
+                &mut taker_game_info,
+                escrow_info.asset_id,
+            )?;
+        } else {
+            GameAcc::revert_asset(
+                &mut taker_game_info,
+                &mut initialiser_game_info,
+                escrow_info.asset_id,
+            )?;
+        }
+
+        GameAcc::pack(
+            initialiser_game_info,
+            &mut initialiser_game_acc.data.borrow_mut(),
         )?;
+        GameAcc::pack(taker_game_info, &mut taker_game_acc.data.borrow_mut())?;
 
-        msg!("sending rent-exempt amount to initialiser_acc from escrow_acc");
+        let taker_main_acc = next_account_info(acc_info_iter)?;
 
-        *(*initialiser_main_acc.lamports.borrow_mut()) = initialiser_main_acc
-            .lamports()
-            .checked_add(escrow_acc.lamports())
-            .ok_or(EscrowError::AmountOverflow)?;
+        if !taker_main_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        // escrows taken before the taker's main A/c was recorded have it unset
+        if escrow_info.taker_main_acc != Pubkey::default()
+            && escrow_info.taker_main_acc != *taker_main_acc.key
+        {
+            return Err(EscrowError::AccountMismatch.into());
+        }
+
+        // the rental ended so the held rent goes to the lender,
+        // if delivery was never confirmed it goes back to the renter with the rest
+
+        if escrow_info.pays_rent_on_revert() {
+            msg!("sending amount to taker_acc from escrow_acc");
+
+            Self::transfer_lamports(escrow_acc, taker_main_acc, escrow_info.amount)?;
+        }
+
+        // pay the keeper who cranked the revert, if it wasn't the initialiser
+
+        let keeper_acc = next_account_info(acc_info_iter)?;
+
+        if !keeper_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if !keeper_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let mut keeper = None;
+
+        if keeper_acc.key != initialiser_main_acc.key {
+            let bounty = KEEPER_BOUNTY.min(escrow_acc.lamports());
+
+            msg!("sending keeper bounty to keeper_acc from escrow_acc");
+
+            Self::transfer_lamports(escrow_acc, keeper_acc, bounty)?;
+
+            keeper = Some(*keeper_acc.key);
+        }
+
+        let (renter_game_acc, lender_game_acc) = if escrow_info.initialiser_is_renter() {
+            (escrow_info.initialiser_game_acc, escrow_info.taker_game_acc)
+        } else {
+            (escrow_info.taker_game_acc, escrow_info.initialiser_game_acc)
+        };
+
+        Event::EscrowReverted {
+            escrow: *escrow_acc.key,
+            renter_game_acc,
+            lender_game_acc,
+            asset_id: escrow_info.asset_id,
+            keeper,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit();
+
+        Self::record_completed(
+            program_id,
+            accounts,
+            &[escrow_info.initialiser_main_acc, escrow_info.taker_main_acc],
+        )?;
+
+        Self::record_history(
+            program_id,
+            accounts,
+            escrow_info.asset_id,
+            HistoryEntry {
+                kind: HISTORY_KIND_RETURN,
+                from_game_acc: renter_game_acc,
+                to_game_acc: lender_game_acc,
+                price: 0,
+                start: escrow_info.esc_taken_time,
+                end: clock.unix_timestamp,
+            },
+        )?;
 
-        *(*escrow_acc.lamports.borrow_mut()) = 0;
+        msg!("sending rent-exempt amount to initialiser_acc from escrow_acc");
 
-        *escrow_acc.data.borrow_mut() = &mut [];
+        Self::close_acc(escrow_acc, initialiser_main_acc)?;
 
         Ok(())
     }
 
-    fn process_init_game_acc(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    fn process_cancel_escrow(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         let acc_info_iter = &mut accounts.iter();
 
-        let game_acc = next_account_info(acc_info_iter)?;
+        let escrow_acc = next_account_info(acc_info_iter)?;
 
-        if !game_acc.is_writable {
+        let escrow_info = load::<Escrow>(program_id, escrow_acc)?;
+
+        if escrow_info.is_taken || escrow_info.has_bids() {
+            // an auction with bids can only be settled
+            return Err(EscrowError::EscrowAlreadyTaken.into());
+        }
+
+        let initialiser_main_acc = next_account_info(acc_info_iter)?;
+
+        if !escrow_acc.is_writable || !initialiser_main_acc.is_writable {
             return Err(EscrowError::MissingPermission.into());
         }
 
-        if game_acc.owner != program_id {
-            return Err(ProgramError::IncorrectProgramId);
+        if escrow_info.initialiser_main_acc != *initialiser_main_acc.key {
+            return Err(EscrowError::AccountMismatch.into());
+        }
+
+        // before expiry only the initialiser can cancel, after it anyone can crank it
+
+        let clock = Clock::get()?;
+
+        if !escrow_info.is_expired(clock.unix_timestamp) && !initialiser_main_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
         }
 
-        if game_acc.data.borrow()[0] != 0 {
-            // this implies it is initialised
-            return Err(EscrowError::IncorrectAcc.into());
+        // a listed asset comes back out of custody
+
+        if !escrow_info.initialiser_is_renter() {
+            let seller_game_acc = next_account_info(acc_info_iter)?;
+
+            if !seller_game_acc.is_writable {
+                return Err(EscrowError::MissingPermission.into());
+            }
+
+            if escrow_info.initialiser_game_acc != *seller_game_acc.key {
+                return Err(EscrowError::AccountMismatch.into());
+            }
+
+            let mut seller_game_info = load::<GameAcc>(program_id, seller_game_acc)?;
+
+            seller_game_info.release_asset(escrow_info.asset_id)?;
+
+            GameAcc::pack(seller_game_info, &mut seller_game_acc.data.borrow_mut())?;
         }
 
-        let main_acc = next_account_info(acc_info_iter)?;
-        let rent = Rent::get()?;
+        Self::delist_escrow(program_id, escrow_acc.key, &escrow_info, acc_info_iter)?;
 
-        if game_acc.lamports() < rent.minimum_balance(game_acc.data_len()) {
-            return Err(ProgramError::InsufficientFunds);
+        if !escrow_info.is_expired(clock.unix_timestamp) {
+            Self::record_against(
+                program_id,
+                accounts,
+                &escrow_info.initialiser_main_acc,
+                |reputation| reputation.cancelled = reputation.cancelled.saturating_add(1),
+            )?;
         }
 
-        let mut game_acc_info = GameAcc::unpack_unchecked(&game_acc.data.borrow())?;
+        Event::EscrowCancelled {
+            escrow: *escrow_acc.key,
+            initialiser_main_acc: *initialiser_main_acc.key,
+            refund: escrow_acc.lamports(),
+            timestamp: clock.unix_timestamp,
+        }
+        .emit();
 
-        game_acc_info.userspace_owner = *main_acc.key;
-        game_acc_info.acc_type = 2;
+        msg!("sending all lamports to initialiser_acc from escrow_acc");
 
-        GameAcc::pack(game_acc_info, &mut game_acc.data.borrow_mut())?;
+        Self::close_acc(escrow_acc, initialiser_main_acc)?;
 
         Ok(())
     }
 
-    fn add_asset(program_id: &Pubkey, accounts: &[AccountInfo], asset_id: u64) -> ProgramResult {
+    fn process_init_auction(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        init_auction_data: InitAuctionData,
+    ) -> ProgramResult {
         let acc_info_iter = &mut accounts.iter();
 
-        let game_acc = next_account_info(acc_info_iter)?;
+        let escrow_acc = next_account_info(acc_info_iter)?;
+        let seller_game_acc = next_account_info(acc_info_iter)?;
+        let seller_main_acc = next_account_info(acc_info_iter)?;
+
+        let mut escrow_info = Self::unpack_new_listing(
+            program_id,
+            escrow_acc,
+            seller_game_acc,
+            seller_main_acc,
+            init_auction_data.asset_id,
+        )?;
 
-        if !game_acc.is_writable {
-            return Err(EscrowError::MissingPermission.into());
-        }
+        let clock = Clock::get()?;
 
-        if game_acc.owner != program_id {
-            return Err(ProgramError::IncorrectProgramId);
+        if init_auction_data.end_time <= clock.unix_timestamp {
+            return Err(EscrowError::AuctionEnded.into());
         }
 
-        if game_acc.data.borrow()[0] != 2 {
-            // this implies it is not game_acc
-            return Err(EscrowError::IncorrectAcc.into());
+        escrow_info.kind = ESCROW_KIND_ENGLISH_AUCTION;
+        escrow_info.amount = init_auction_data.reserve_price;
+        escrow_info.min_increment = init_auction_data.min_increment;
+        escrow_info.time = init_auction_data.time;
+        escrow_info.end_time = init_auction_data.end_time;
+        escrow_info.min_reputation = init_auction_data.min_reputation;
+
+        Self::emit_escrow_created(escrow_acc.key, &escrow_info, clock.unix_timestamp);
+
+        Escrow::pack(escrow_info, &mut escrow_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    fn process_init_dutch_auction(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        init_dutch_data: InitDutchAuctionData,
+    ) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let escrow_acc = next_account_info(acc_info_iter)?;
+        let seller_game_acc = next_account_info(acc_info_iter)?;
+        let seller_main_acc = next_account_info(acc_info_iter)?;
+
+        let mut escrow_info = Self::unpack_new_listing(
+            program_id,
+            escrow_acc,
+            seller_game_acc,
+            seller_main_acc,
+            init_dutch_data.asset_id,
+        )?;
+
+        if init_dutch_data.start_price < init_dutch_data.end_price
+            || init_dutch_data.end_time <= init_dutch_data.start_time
+        {
+            return Err(EscrowError::InvalidInstruction.into());
         }
 
-        let mut game_acc_info = GameAcc::unpack_unchecked(&game_acc.data.borrow())?;
+        escrow_info.kind = ESCROW_KIND_DUTCH_AUCTION;
+        escrow_info.start_price = init_dutch_data.start_price;
+        escrow_info.end_price = init_dutch_data.end_price;
+        escrow_info.time = init_dutch_data.time;
+        escrow_info.start_time = init_dutch_data.start_time;
+        escrow_info.end_time = init_dutch_data.end_time;
+        escrow_info.min_reputation = init_dutch_data.min_reputation;
 
-        let mut is_full = true;
+        Self::emit_escrow_created(escrow_acc.key, &escrow_info, Clock::get()?.unix_timestamp);
 
-        for i in 0..20 {
-            if game_acc_info.owned[i] == 0 {
-                game_acc_info.owned[i] = asset_id;
-                is_full = false;
-            }
+        Escrow::pack(escrow_info, &mut escrow_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    // checks common to escrows where the initialiser lists his own asset and takes it into custody,
+    // returns the escrow filled with the seller's details
+    fn unpack_new_listing(
+        program_id: &Pubkey,
+        escrow_acc: &AccountInfo,
+        seller_game_acc: &AccountInfo,
+        seller_main_acc: &AccountInfo,
+        asset_id: u64,
+    ) -> Result<Escrow, ProgramError> {
+        if asset_id == 0 {
+            return Err(EscrowError::InvalidInstruction.into());
         }
 
-        if is_full {
-            return Err(EscrowError::AssetSpaceFull.into());
+        if !escrow_acc.is_writable || !seller_game_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
         }
 
-        GameAcc::pack(game_acc_info, &mut game_acc.data.borrow_mut())?;
+        if !seller_main_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut escrow_info = load_uninitialized::<Escrow>(program_id, escrow_acc)?;
+
+        let mut seller_game_info = load::<GameAcc>(program_id, seller_game_acc)?;
+
+        if seller_game_info.userspace_owner != *seller_main_acc.key {
+            return Err(EscrowError::OwnerMismatch.into());
+        }
+
+        // the asset is held in the seller's lent till the listing is settled or cancelled
+
+        seller_game_info.hold_asset(asset_id)?;
+
+        GameAcc::pack(seller_game_info, &mut seller_game_acc.data.borrow_mut())?;
+
+        escrow_info.acc_type = 1;
+        escrow_info.is_taken = false;
+        escrow_info.initialiser_main_acc = *seller_main_acc.key;
+        escrow_info.initialiser_game_acc = *seller_game_acc.key;
+        escrow_info.asset_id = asset_id;
+
+        Ok(escrow_info)
+    }
+
+    fn process_place_bid(program_id: &Pubkey, accounts: &[AccountInfo], bid: u64) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let bidder_main_acc = next_account_info(acc_info_iter)?;
+
+        if !bidder_main_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let escrow_acc = next_account_info(acc_info_iter)?;
+
+        if !escrow_acc.is_writable || !bidder_main_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let mut escrow_info = load::<Escrow>(program_id, escrow_acc)?;
+
+        if escrow_info.kind != ESCROW_KIND_ENGLISH_AUCTION {
+            return Err(EscrowError::WrongEscrowKind.into());
+        }
+
+        let clock = Clock::get()?;
+
+        if escrow_info.is_taken || clock.unix_timestamp >= escrow_info.end_time {
+            return Err(EscrowError::AuctionEnded.into());
+        }
+
+        if escrow_info.initialiser_main_acc == *bidder_main_acc.key {
+            // seller can't bid on his own auction
+            return Err(EscrowError::OwnEscrow.into());
+        }
+
+        Self::check_reputation(program_id, accounts, bidder_main_acc.key, &escrow_info)?;
+
+        // a bid must beat the highest one, by at least 1 lamport if min_increment is 0
+
+        let min_bid = if escrow_info.has_bids() {
+            escrow_info
+                .amount
+                .checked_add(escrow_info.min_increment.max(1))
+                .ok_or(EscrowError::AmountOverflow)?
+        } else {
+            escrow_info.amount
+        };
+
+        if bid < min_bid {
+            return Err(EscrowError::BidTooLow.into());
+        }
+
+        let bidder_game_acc = next_account_info(acc_info_iter)?;
+
+        let bidder_game_info = load::<GameAcc>(program_id, bidder_game_acc)?;
+
+        if bidder_game_info.userspace_owner != *bidder_main_acc.key {
+            return Err(EscrowError::OwnerMismatch.into());
+        }
+
+        let system_program_acc = next_account_info(acc_info_iter)?;
+
+        msg!("sending bid to escrow_acc from bidder_acc");
+
+        invoke(
+            &system_instruction::transfer(bidder_main_acc.key, escrow_acc.key, bid),
+            &[
+                bidder_main_acc.clone(),
+                escrow_acc.clone(),
+                system_program_acc.clone(),
+            ],
+        )?;
+
+        if escrow_info.has_bids() {
+            let prev_bidder_main_acc = next_account_info(acc_info_iter)?;
+
+            if !prev_bidder_main_acc.is_writable {
+                return Err(EscrowError::MissingPermission.into());
+            }
+
+            if escrow_info.taker_main_acc != *prev_bidder_main_acc.key {
+                return Err(EscrowError::AccountMismatch.into());
+            }
+
+            msg!("refunding previous bid to prev_bidder_acc from escrow_acc");
+
+            Self::transfer_lamports(escrow_acc, prev_bidder_main_acc, escrow_info.amount)?;
+        }
+
+        escrow_info.amount = bid;
+        escrow_info.taker_main_acc = *bidder_main_acc.key;
+        escrow_info.taker_game_acc = *bidder_game_acc.key;
+
+        Event::BidPlaced {
+            escrow: *escrow_acc.key,
+            bidder_main_acc: *bidder_main_acc.key,
+            amount: bid,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit();
+
+        Escrow::pack(escrow_info, &mut escrow_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    fn process_settle_auction(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let escrow_acc = next_account_info(acc_info_iter)?;
+
+        let mut escrow_info = load::<Escrow>(program_id, escrow_acc)?;
+
+        if escrow_info.kind != ESCROW_KIND_ENGLISH_AUCTION {
+            return Err(EscrowError::WrongEscrowKind.into());
+        }
+
+        if escrow_info.is_taken {
+            return Err(EscrowError::EscrowAlreadyTaken.into());
+        }
+
+        let clock = Clock::get()?;
+
+        if clock.unix_timestamp < escrow_info.end_time {
+            return Err(EscrowError::AuctionNotEnded.into());
+        }
+
+        let seller_main_acc = next_account_info(acc_info_iter)?;
+        let seller_game_acc = next_account_info(acc_info_iter)?;
+
+        if !escrow_acc.is_writable || !seller_main_acc.is_writable || !seller_game_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        if escrow_info.initialiser_main_acc != *seller_main_acc.key
+            || escrow_info.initialiser_game_acc != *seller_game_acc.key
+        {
+            return Err(EscrowError::AccountMismatch.into());
+        }
+
+        let mut seller_game_info = load::<GameAcc>(program_id, seller_game_acc)?;
+
+        if escrow_info.has_bids() {
+            let winner_game_acc = next_account_info(acc_info_iter)?;
+            let winner_main_acc = next_account_info(acc_info_iter)?;
+
+            if !winner_game_acc.is_writable || !winner_main_acc.is_writable {
+                return Err(EscrowError::MissingPermission.into());
+            }
+
+            if escrow_info.taker_game_acc != *winner_game_acc.key
+                || escrow_info.taker_main_acc != *winner_main_acc.key
+            {
+                return Err(EscrowError::AccountMismatch.into());
+            }
+
+            let mut winner_game_info = load::<GameAcc>(program_id, winner_game_acc)?;
+
+            let transfer_result = if escrow_info.time == 0 {
+                GameAcc::sell_held_asset(
+                    &mut seller_game_info,
+                    &mut winner_game_info,
+                    escrow_info.asset_id,
+                )
+            } else {
+                GameAcc::rent_held_asset(
+                    &mut seller_game_info,
+                    &mut winner_game_info,
+                    escrow_info.asset_id,
+                )
+            };
+
+            // if the asset can't be transferred the winning bid goes back to the bidder
+            let payee_acc = if transfer_result.is_ok() {
+                msg!("Auction Settled");
+
+                Event::AuctionSettled {
+                    escrow: *escrow_acc.key,
+                    winner_main_acc: Some(*winner_main_acc.key),
+                    asset_id: escrow_info.asset_id,
+                    amount: escrow_info.amount,
+                    timestamp: clock.unix_timestamp,
+                }
+                .emit();
+
+                Self::record_history(
+                    program_id,
+                    accounts,
+                    escrow_info.asset_id,
+                    HistoryEntry {
+                        kind: if escrow_info.time == 0 {
+                            HISTORY_KIND_SALE
+                        } else {
+                            HISTORY_KIND_RENTAL
+                        },
+                        from_game_acc: *seller_game_acc.key,
+                        to_game_acc: *winner_game_acc.key,
+                        price: escrow_info.amount,
+                        start: clock.unix_timestamp,
+                        end: escrow_info.due_at(clock.unix_timestamp),
+                    },
+                )?;
+
+                GameAcc::pack(seller_game_info, &mut seller_game_acc.data.borrow_mut())?;
+                GameAcc::pack(winner_game_info, &mut winner_game_acc.data.borrow_mut())?;
+
+                seller_main_acc
+            } else {
+                msg!("asset can't be transferred, refunding the highest bid");
+
+                seller_game_info.release_asset(escrow_info.asset_id)?;
+
+                GameAcc::pack(seller_game_info, &mut seller_game_acc.data.borrow_mut())?;
+
+                winner_main_acc
+            };
+
+            msg!("sending winning bid from escrow_acc");
+
+            Self::transfer_lamports(escrow_acc, payee_acc, escrow_info.amount)?;
+
+            if transfer_result.is_ok() && escrow_info.time == 0 {
+                Self::record_completed(
+                    program_id,
+                    accounts,
+                    &[*seller_main_acc.key, *winner_main_acc.key],
+                )?;
+            }
+
+            if transfer_result.is_ok() && escrow_info.time != 0 {
+                // escrow stays open till the rented asset is reverted
+
+                escrow_info.is_taken = true;
+                escrow_info.esc_taken_time = clock.unix_timestamp;
+
+                Escrow::pack(escrow_info, &mut escrow_acc.data.borrow_mut())?;
+
+                return Ok(());
+            }
+        } else {
+            seller_game_info.release_asset(escrow_info.asset_id)?;
+
+            GameAcc::pack(seller_game_info, &mut seller_game_acc.data.borrow_mut())?;
+
+            Event::AuctionSettled {
+                escrow: *escrow_acc.key,
+                winner_main_acc: None,
+                asset_id: escrow_info.asset_id,
+                amount: 0,
+                timestamp: clock.unix_timestamp,
+            }
+            .emit();
+        }
+
+        msg!("sending rent-exempt amount to seller_acc from escrow_acc");
+
+        Self::close_acc(escrow_acc, seller_main_acc)?;
+
+        Ok(())
+    }
+
+    fn process_make_offer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        offer_data: OfferData,
+    ) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let offer_acc = next_account_info(acc_info_iter)?;
+
+        if !offer_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let escrow_acc = next_account_info(acc_info_iter)?;
+
+        let mut offer_info = load_uninitialized::<Offer>(program_id, offer_acc)?;
+
+        let escrow_info = load::<Escrow>(program_id, escrow_acc)?;
+
+        if escrow_info.kind != ESCROW_KIND_RENT_REQUEST {
+            return Err(EscrowError::WrongEscrowKind.into());
+        }
+
+        if escrow_info.is_taken {
+            return Err(EscrowError::EscrowAlreadyTaken.into());
+        }
+
+        let clock = Clock::get()?;
+
+        if escrow_info.is_expired(clock.unix_timestamp) {
+            return Err(EscrowError::EscrowExpired.into());
+        }
+
+        let offerer_main_acc = next_account_info(acc_info_iter)?;
+
+        if !offerer_main_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if !escrow_info.is_allowed_taker(offerer_main_acc.key) {
+            return Err(EscrowError::TakerNotAllowed.into());
+        }
+
+        Self::check_reputation(program_id, accounts, offerer_main_acc.key, &escrow_info)?;
+
+        // the offerer becomes the taker when the offer is accepted,
+        // so agrees to the arbiter and oracle now
+        if escrow_info.arbiter != offer_data.arbiter {
+            return Err(EscrowError::ArbiterMismatch.into());
+        }
+
+        if escrow_info.oracle != offer_data.oracle {
+            return Err(EscrowError::OracleMismatch.into());
+        }
+
+        let offerer_game_acc = next_account_info(acc_info_iter)?;
+
+        let offerer_game_info = load::<GameAcc>(program_id, offerer_game_acc)?;
+
+        let asset_id = if offer_data.asset_id == 0 {
+            escrow_info.asset_id
+        } else {
+            offer_data.asset_id
+        };
+
+        if offerer_game_info.userspace_owner != *offerer_main_acc.key {
+            return Err(EscrowError::OwnerMismatch.into());
+        }
+
+        if escrow_info.initialiser_main_acc == *offerer_main_acc.key {
+            return Err(EscrowError::OwnEscrow.into());
+        }
+
+        if !escrow_info.accepts_asset(asset_id) {
+            return Err(EscrowError::AssetNotAccepted.into());
+        }
+
+        // an escrow for asset 0 would match any empty slot
+
+        if asset_id == 0 || !offerer_game_info.owned.contains(&asset_id) {
+            return Err(EscrowError::AssetNotOwned.into());
+        }
+
+        offer_info.acc_type = 3;
+        offer_info.escrow = *escrow_acc.key;
+        offer_info.offerer_main_acc = *offerer_main_acc.key;
+        offer_info.offerer_game_acc = *offerer_game_acc.key;
+        offer_info.amount = offer_data.amount;
+        offer_info.time = offer_data.time;
+        offer_info.asset_id = asset_id;
+
+        Event::OfferMade {
+            offer: *offer_acc.key,
+            escrow: *escrow_acc.key,
+            offerer_main_acc: *offerer_main_acc.key,
+            asset_id,
+            amount: offer_data.amount,
+            time: offer_data.time,
+        }
+        .emit();
+
+        Offer::pack(offer_info, &mut offer_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    fn process_accept_offer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let initialiser_main_acc = next_account_info(acc_info_iter)?;
+
+        if !initialiser_main_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let escrow_acc = next_account_info(acc_info_iter)?;
+        let offer_acc = next_account_info(acc_info_iter)?;
+        let initialiser_game_acc = next_account_info(acc_info_iter)?;
+        let offerer_game_acc = next_account_info(acc_info_iter)?;
+        let offerer_main_acc = next_account_info(acc_info_iter)?;
+
+        if !initialiser_main_acc.is_writable
+            || !escrow_acc.is_writable
+            || !offer_acc.is_writable
+            || !initialiser_game_acc.is_writable
+            || !offerer_game_acc.is_writable
+            || !offerer_main_acc.is_writable
+        {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let mut escrow_info = load::<Escrow>(program_id, escrow_acc)?;
+        let offer_info = load::<Offer>(program_id, offer_acc)?;
+
+        if escrow_info.kind != ESCROW_KIND_RENT_REQUEST {
+            return Err(EscrowError::WrongEscrowKind.into());
+        }
+
+        if escrow_info.is_taken {
+            return Err(EscrowError::EscrowAlreadyTaken.into());
+        }
+
+        if escrow_info.initialiser_main_acc != *initialiser_main_acc.key
+            || escrow_info.initialiser_game_acc != *initialiser_game_acc.key
+            || offer_info.escrow != *escrow_acc.key
+            || offer_info.offerer_game_acc != *offerer_game_acc.key
+            || offer_info.offerer_main_acc != *offerer_main_acc.key
+        {
+            return Err(EscrowError::AccountMismatch.into());
+        }
+
+        let clock = Clock::get()?;
+
+        if escrow_info.is_expired(clock.unix_timestamp) {
+            return Err(EscrowError::EscrowExpired.into());
+        }
+
+        // amend the escrow to the offer's terms, refunding whatever the initialiser put extra
+
+        let rent = Rent::get()?;
+
+        let deposit = escrow_acc
+            .lamports()
+            .saturating_sub(rent.minimum_balance(escrow_acc.data_len()));
+
+        let excess = deposit
+            .checked_sub(offer_info.amount)
+            .ok_or(ProgramError::InsufficientFunds)?;
+
+        msg!("refunding excess to initialiser_acc from escrow_acc");
+
+        Self::transfer_lamports(escrow_acc, initialiser_main_acc, excess)?;
+
+        escrow_info.amount = offer_info.amount;
+        escrow_info.time = offer_info.time;
+        escrow_info.asset_id = offer_info.asset_id;
+
+        let mut offerer_game_info = load::<GameAcc>(program_id, offerer_game_acc)?;
+        let mut initialiser_game_info = load::<GameAcc>(program_id, initialiser_game_acc)?;
+
+        GameAcc::transfer_asset(
+            &mut offerer_game_info,
+            &mut initialiser_game_info,
+            escrow_info.asset_id,
+        )?;
+
+        msg!("Offer Accepted");
+
+        Event::OfferAccepted {
+            offer: *offer_acc.key,
+            escrow: *escrow_acc.key,
+            offerer_main_acc: *offerer_main_acc.key,
+            asset_id: escrow_info.asset_id,
+            amount: escrow_info.amount,
+            time: escrow_info.time,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit();
+
+        Self::record_history(
+            program_id,
+            accounts,
+            escrow_info.asset_id,
+            HistoryEntry {
+                kind: HISTORY_KIND_RENTAL,
+                from_game_acc: *offerer_game_acc.key,
+                to_game_acc: *initialiser_game_acc.key,
+                price: escrow_info.amount,
+                start: clock.unix_timestamp,
+                end: escrow_info.due_at(clock.unix_timestamp),
+            },
+        )?;
+
+        if !escrow_info.holds_rent() {
+            msg!("sending amount to offerer_acc from escrow_acc");
+
+            Self::transfer_lamports(escrow_acc, offerer_main_acc, escrow_info.amount)?;
+        }
+
+        Self::delist_escrow(program_id, escrow_acc.key, &escrow_info, acc_info_iter)?;
+
+        escrow_info.is_taken = true;
+        escrow_info.taker_main_acc = *offerer_main_acc.key;
+        escrow_info.taker_game_acc = *offerer_game_acc.key;
+        escrow_info.esc_taken_time = clock.unix_timestamp;
+
+        Escrow::pack(escrow_info, &mut escrow_acc.data.borrow_mut())?;
+        GameAcc::pack(
+            initialiser_game_info,
+            &mut initialiser_game_acc.data.borrow_mut(),
+        )?;
+        GameAcc::pack(offerer_game_info, &mut offerer_game_acc.data.borrow_mut())?;
+
+        msg!("sending rent-exempt amount to offerer_acc from offer_acc");
+
+        Self::close_acc(offer_acc, offerer_main_acc)?;
+
+        Ok(())
+    }
+
+    fn process_cancel_offer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let offer_acc = next_account_info(acc_info_iter)?;
+        let offerer_main_acc = next_account_info(acc_info_iter)?;
+        let escrow_acc = next_account_info(acc_info_iter)?;
+
+        if !offer_acc.is_writable || !offerer_main_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let offer_info = load::<Offer>(program_id, offer_acc)?;
+
+        if offer_info.offerer_main_acc != *offerer_main_acc.key
+            || offer_info.escrow != *escrow_acc.key
+        {
+            return Err(EscrowError::AccountMismatch.into());
+        }
+
+        // while the escrow is open only the offerer can withdraw, once it is closed,
+        // taken or expired the offer can't be accepted so anyone can crank it
+
+        let clock = Clock::get()?;
+
+        let escrow_is_open = if escrow_acc.owner == program_id
+            && !escrow_acc.data_is_empty()
+            && validation::acc_type(escrow_acc).is_ok()
+        {
+            let escrow_info = load::<Escrow>(program_id, escrow_acc)?;
+
+            !escrow_info.is_taken && !escrow_info.is_expired(clock.unix_timestamp)
+        } else {
+            false
+        };
+
+        if escrow_is_open && !offerer_main_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Event::OfferCancelled {
+            offer: *offer_acc.key,
+            escrow: *escrow_acc.key,
+            offerer_main_acc: *offerer_main_acc.key,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit();
+
+        msg!("sending all lamports to offerer_acc from offer_acc");
+
+        Self::close_acc(offer_acc, offerer_main_acc)?;
+
+        Ok(())
+    }
+
+    fn process_init_market(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let market_acc = next_account_info(acc_info_iter)?;
+
+        if !market_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let authority_acc = next_account_info(acc_info_iter)?;
+
+        if !authority_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut market_info = load_uninitialized::<Market>(program_id, market_acc)?;
+
+        market_info.acc_type = 4;
+        market_info.authority = *authority_acc.key;
+
+        if let Ok(arbiter_acc) = next_account_info(acc_info_iter) {
+            market_info.arbiter = *arbiter_acc.key;
+        }
+
+        if let Ok(oracle_acc) = next_account_info(acc_info_iter) {
+            market_info.oracle = *oracle_acc.key;
+        }
+
+        Event::MarketCreated {
+            market: *market_acc.key,
+            authority: *authority_acc.key,
+        }
+        .emit();
+
+        Market::pack(market_info, &mut market_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    // moves lamports out of an account owned by the program, failing rather than overdrawing it
+    fn transfer_lamports(
+        from_acc: &AccountInfo,
+        to_acc: &AccountInfo,
+        amount: u64,
+    ) -> ProgramResult {
+        let from_lamports = from_acc
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(ProgramError::InsufficientFunds)?;
+
+        **from_acc.try_borrow_mut_lamports()? = from_lamports;
+
+        let to_lamports = to_acc
+            .lamports()
+            .checked_add(amount)
+            .ok_or(EscrowError::AmountOverflow)?;
+
+        **to_acc.try_borrow_mut_lamports()? = to_lamports;
+
+        Ok(())
+    }
+
+    // closes an account of the program, its data is zeroed but for the closed acc_type
+    // and all of its lamports go to the recipient
+    fn close_acc(acc: &AccountInfo, recipient_acc: &AccountInfo) -> ProgramResult {
+        {
+            let mut data = acc.try_borrow_mut_data()?;
+            data.fill(0);
+
+            if let Some(acc_type) = data.first_mut() {
+                *acc_type = CLOSED_ACC_TYPE;
+            }
+        }
+
+        Self::transfer_lamports(acc, recipient_acc, acc.lamports())
+    }
+
+    fn emit_escrow_created(escrow_key: &Pubkey, escrow_info: &Escrow, unix_timestamp: i64) {
+        let amount = match escrow_info.kind {
+            ESCROW_KIND_DUTCH_AUCTION => escrow_info.start_price,
+            _ => escrow_info.amount,
+        };
+
+        Event::EscrowCreated {
+            escrow: *escrow_key,
+            kind: escrow_info.kind,
+            initialiser_main_acc: escrow_info.initialiser_main_acc,
+            initialiser_game_acc: escrow_info.initialiser_game_acc,
+            asset_id: escrow_info.asset_id,
+            asset_id_max: escrow_info.asset_id_max,
+            amount,
+            time: escrow_info.time,
+            expires_at: escrow_info.expires_at,
+            timestamp: unix_timestamp,
+        }
+        .emit();
+    }
+
+    // takes an escrow off the index of the market it is listed in,
+    // the Market A/c is the next account only if the escrow is listed
+    fn delist_escrow<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+        program_id: &Pubkey,
+        escrow_key: &Pubkey,
+        escrow_info: &Escrow,
+        acc_info_iter: &mut I,
+    ) -> ProgramResult {
+        if escrow_info.market == Pubkey::default() {
+            return Ok(());
+        }
+
+        let market_acc = next_account_info(acc_info_iter)?;
+
+        if !market_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        if escrow_info.market != *market_acc.key {
+            return Err(EscrowError::AccountMismatch.into());
+        }
+
+        let mut market_info = load::<Market>(program_id, market_acc)?;
+
+        market_info.remove(escrow_key);
+
+        Market::pack(market_info, &mut market_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    fn process_assert_owns(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        asset_id: u64,
+    ) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let game_acc = next_account_info(acc_info_iter)?;
+
+        let player_main_acc = next_account_info(acc_info_iter)?;
+
+        let game_acc_info = load::<GameAcc>(program_id, game_acc)?;
+
+        if game_acc_info.userspace_owner != *player_main_acc.key {
+            return Err(EscrowError::OwnerMismatch.into());
+        }
+
+        if asset_id != 0 && game_acc_info.lent.contains(&asset_id) {
+            return Err(EscrowError::AssetLentOut.into());
+        }
+
+        if !game_acc_info.holds_asset(asset_id) {
+            return Err(EscrowError::AssetNotOwned.into());
+        }
+
+        Ok(())
+    }
+
+    fn process_lock_for_match(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let game_acc = next_account_info(acc_info_iter)?;
+
+        if !game_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let player_main_acc = next_account_info(acc_info_iter)?;
+        let authority_acc = next_account_info(acc_info_iter)?;
+
+        if !player_main_acc.is_signer || !authority_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut game_acc_info = load::<GameAcc>(program_id, game_acc)?;
+
+        if game_acc_info.userspace_owner != *player_main_acc.key {
+            return Err(EscrowError::OwnerMismatch.into());
+        }
+
+        if game_acc_info.is_locked() {
+            return Err(EscrowError::AccountLocked.into());
+        }
+
+        game_acc_info.locked_by = *authority_acc.key;
+
+        Event::GameAccLocked {
+            game_acc: *game_acc.key,
+            authority: *authority_acc.key,
+        }
+        .emit();
+
+        GameAcc::pack(game_acc_info, &mut game_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    fn process_unlock_after_match(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let game_acc = next_account_info(acc_info_iter)?;
+
+        if !game_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let authority_acc = next_account_info(acc_info_iter)?;
+
+        if !authority_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut game_acc_info = load::<GameAcc>(program_id, game_acc)?;
+
+        if !game_acc_info.is_locked() || game_acc_info.locked_by != *authority_acc.key {
+            return Err(EscrowError::NotLockAuthority.into());
+        }
+
+        game_acc_info.locked_by = Pubkey::default();
+
+        Event::GameAccUnlocked {
+            game_acc: *game_acc.key,
+            authority: *authority_acc.key,
+        }
+        .emit();
+
+        GameAcc::pack(game_acc_info, &mut game_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    fn process_migrate_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let acc = next_account_info(acc_info_iter)?;
+
+        if !acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        if acc.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let payer_acc = next_account_info(acc_info_iter)?;
+
+        if !payer_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let system_program_acc = next_account_info(acc_info_iter)?;
+
+        let acc_type = validation::acc_type(acc)?;
+
+        let body_len_now = body_len(acc_type, ACCOUNT_VERSION).ok_or(EscrowError::IncorrectAcc)?;
+        let new_len = ACCOUNT_HEADER_LEN + body_len_now;
+        let data_len = acc.data_len();
+
+        // a version 1 account has fixed size fields behind a shorter header with no version byte,
+        // it is decoded field by field and packed in the current layout once resized.
+        // later versions say their version in the header and are a prefix of the current body
+
+        let (v1_packed, old_body_len) = match body_len(acc_type, 1) {
+            Some(len) if LEGACY_HEADER_LEN + len == data_len => {
+                let data = acc.try_borrow_data()?;
+                let mut packed = vec![0; new_len];
+
+                match acc_type {
+                    1 => Escrow::pack(Escrow::unpack_v1(&data)?, &mut packed)?,
+                    _ => GameAcc::pack(GameAcc::unpack_v1(&data)?, &mut packed)?,
+                }
+
+                (Some(packed), len)
+            }
+            _ => {
+                let version = acc.try_borrow_data()?.get(1).copied().unwrap_or(0);
+
+                if version == ACCOUNT_VERSION && data_len == new_len {
+                    // already current
+                    return Ok(());
+                }
+
+                match body_len(acc_type, version) {
+                    Some(len)
+                        if version > 1
+                            && version < ACCOUNT_VERSION
+                            && ACCOUNT_HEADER_LEN + len == data_len =>
+                    {
+                        (None, len)
+                    }
+                    _ => return Err(ProgramError::InvalidAccountData),
+                }
+            }
+        };
+
+        // the payer covers the rent of the added bytes so lamports an Escrow A/c holds
+        // above its rent-exempt minimum, like the rent of a rent request, are left as they are
+        let rent = Rent::get()?;
+        let rent_now = rent.minimum_balance(data_len).min(acc.lamports());
+        let top_up = rent.minimum_balance(new_len).saturating_sub(rent_now);
+
+        if top_up > 0 {
+            invoke(
+                &system_instruction::transfer(payer_acc.key, acc.key, top_up),
+                &[payer_acc.clone(), acc.clone(), system_program_acc.clone()],
+            )?;
+        }
+
+        acc.realloc(new_len, true)?;
+
+        {
+            let mut data = acc.data.borrow_mut();
+
+            if let Some(packed) = v1_packed {
+                data.copy_from_slice(&packed);
+            } else {
+                data[1] = ACCOUNT_VERSION;
+
+                // fields appended since the old version start out zeroed
+                data[(ACCOUNT_HEADER_LEN + old_body_len)..].fill(0);
+            }
+        }
+
+        // make sure the upgraded account reads back as a valid account of its type
+        {
+            let data = acc.data.borrow();
+            match acc_type {
+                1 => Escrow::unpack(&data).map(|_| ()),
+                2 => GameAcc::unpack(&data).map(|_| ()),
+                3 => Offer::unpack(&data).map(|_| ()),
+                4 => Market::unpack(&data).map(|_| ()),
+                5 => History::unpack(&data).map(|_| ()),
+                _ => Reputation::unpack(&data).map(|_| ()),
+            }?;
+        }
+
+        Event::AccountMigrated {
+            account: *acc.key,
+            acc_type,
+            version: ACCOUNT_VERSION,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    fn process_init_history(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        asset_id: u64,
+    ) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let history_acc = next_account_info(acc_info_iter)?;
+        let payer_acc = next_account_info(acc_info_iter)?;
+        let system_program_acc = next_account_info(acc_info_iter)?;
+
+        if !history_acc.is_writable || !payer_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        if !payer_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if asset_id == 0 {
+            return Err(EscrowError::InvalidInstruction.into());
+        }
+
+        let (history_key, bump) = History::find_address(program_id, asset_id);
+
+        if history_key != *history_acc.key {
+            return Err(EscrowError::IncorrectPda.into());
+        }
+
+        if history_acc.owner == program_id {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let rent = Rent::get()?;
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_acc.key,
+                history_acc.key,
+                rent.minimum_balance(History::LEN),
+                History::LEN as u64,
+                program_id,
+            ),
+            &[
+                payer_acc.clone(),
+                history_acc.clone(),
+                system_program_acc.clone(),
+            ],
+            &[&[HISTORY_SEED, &asset_id.to_le_bytes(), &[bump]]],
+        )?;
+
+        let mut history_info = History::unpack_unchecked(&history_acc.data.borrow())?;
+
+        history_info.acc_type = 5;
+        history_info.asset_id = asset_id;
+        history_info.bump = bump;
+
+        History::pack(history_info, &mut history_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    // appends an entry to the asset's History A/c, found among the accounts by its PDA,
+    // every transfer of an asset is recorded so it fails if the History A/c wasn't passed
+    fn record_history(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        asset_id: u64,
+        entry: HistoryEntry,
+    ) -> ProgramResult {
+        let (history_key, _) = History::find_address(program_id, asset_id);
+
+        let history_acc = accounts
+            .iter()
+            .find(|acc| *acc.key == history_key)
+            .ok_or(EscrowError::MissingHistory)?;
+
+        if !history_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let mut history_info = load::<History>(program_id, history_acc)?;
+
+        history_info.push(entry);
+
+        History::pack(history_info, &mut history_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    fn process_init_reputation(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let reputation_acc = next_account_info(acc_info_iter)?;
+        let wallet_acc = next_account_info(acc_info_iter)?;
+        let payer_acc = next_account_info(acc_info_iter)?;
+        let system_program_acc = next_account_info(acc_info_iter)?;
+
+        if !reputation_acc.is_writable || !payer_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        if !payer_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let (reputation_key, bump) = Reputation::find_address(program_id, wallet_acc.key);
+
+        if reputation_key != *reputation_acc.key {
+            return Err(EscrowError::IncorrectPda.into());
+        }
+
+        if reputation_acc.owner == program_id {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let rent = Rent::get()?;
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_acc.key,
+                reputation_acc.key,
+                rent.minimum_balance(Reputation::LEN),
+                Reputation::LEN as u64,
+                program_id,
+            ),
+            &[
+                payer_acc.clone(),
+                reputation_acc.clone(),
+                system_program_acc.clone(),
+            ],
+            &[&[REPUTATION_SEED, wallet_acc.key.as_ref(), &[bump]]],
+        )?;
+
+        let mut reputation_info = Reputation::unpack_unchecked(&reputation_acc.data.borrow())?;
+
+        reputation_info.acc_type = 6;
+        reputation_info.wallet = *wallet_acc.key;
+        reputation_info.bump = bump;
+
+        Reputation::pack(reputation_info, &mut reputation_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    // finds a wallet's Reputation A/c among the accounts by its PDA
+    fn find_reputation<'a, 'b>(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+        wallet: &Pubkey,
+    ) -> Option<&'a AccountInfo<'b>> {
+        let (reputation_key, _) = Reputation::find_address(program_id, wallet);
+
+        accounts.iter().find(|acc| *acc.key == reputation_key)
+    }
+
+    // a taker without a Reputation A/c has a score of 0
+    fn check_reputation(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        taker_main_acc: &Pubkey,
+        escrow_info: &Escrow,
+    ) -> ProgramResult {
+        if escrow_info.min_reputation == 0 {
+            return Ok(());
+        }
+
+        let score = match Self::find_reputation(program_id, accounts, taker_main_acc) {
+            Some(reputation_acc) => load::<Reputation>(program_id, reputation_acc)?.score(),
+            None => 0,
+        };
+
+        if score < escrow_info.min_reputation {
+            return Err(EscrowError::ReputationTooLow.into());
+        }
+
+        Ok(())
+    }
+
+    // updates a wallet's Reputation A/c if it was passed, nothing happens if it was not
+    fn record_reputation(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        wallet: &Pubkey,
+        update: fn(&mut Reputation),
+    ) -> ProgramResult {
+        match Self::find_reputation(program_id, accounts, wallet) {
+            Some(reputation_acc) => Self::update_reputation(program_id, reputation_acc, update),
+            None => Ok(()),
+        }
+    }
+
+    // a cancel or a lost dispute counts against the wallet,
+    // so it fails if the wallet's Reputation A/c wasn't passed
+    fn record_against(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        wallet: &Pubkey,
+        update: fn(&mut Reputation),
+    ) -> ProgramResult {
+        let reputation_acc = Self::find_reputation(program_id, accounts, wallet)
+            .ok_or(EscrowError::MissingReputation)?;
+
+        Self::update_reputation(program_id, reputation_acc, update)
+    }
+
+    fn update_reputation(
+        program_id: &Pubkey,
+        reputation_acc: &AccountInfo,
+        update: fn(&mut Reputation),
+    ) -> ProgramResult {
+        if !reputation_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let mut reputation_info = load::<Reputation>(program_id, reputation_acc)?;
+
+        update(&mut reputation_info);
+
+        Reputation::pack(reputation_info, &mut reputation_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    fn record_completed(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        wallets: &[Pubkey],
+    ) -> ProgramResult {
+        // a wallet on both sides of an escrow only completed it once
+
+        for (i, wallet) in wallets.iter().enumerate() {
+            if wallets[..i].contains(wallet) {
+                continue;
+            }
+
+            Self::record_reputation(program_id, accounts, wallet, |reputation| {
+                reputation.completed = reputation.completed.saturating_add(1)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn process_raise_dispute(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let escrow_acc = next_account_info(acc_info_iter)?;
+
+        if !escrow_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let raiser_main_acc = next_account_info(acc_info_iter)?;
+
+        if !raiser_main_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut escrow_info = load::<Escrow>(program_id, escrow_acc)?;
+
+        if !escrow_info.can_be_disputed() {
+            // only a running rental with an arbiter and rent still held can be disputed
+            return Err(EscrowError::EscrowNotDisputable.into());
+        }
+
+        if escrow_info.is_disputed() {
+            return Err(EscrowError::EscrowDisputed.into());
+        }
+
+        if escrow_info.initialiser_main_acc != *raiser_main_acc.key
+            && escrow_info.taker_main_acc != *raiser_main_acc.key
+        {
+            return Err(EscrowError::NotEscrowParty.into());
+        }
+
+        let clock = Clock::get()?;
+
+        escrow_info.disputed_at = clock.unix_timestamp;
+
+        Event::DisputeRaised {
+            escrow: *escrow_acc.key,
+            raiser_main_acc: *raiser_main_acc.key,
+            arbiter: escrow_info.arbiter,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit();
+
+        Escrow::pack(escrow_info, &mut escrow_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    fn process_resolve_dispute(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        resolve_data: ResolveDisputeData,
+    ) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let arbiter_acc = next_account_info(acc_info_iter)?;
+
+        if !arbiter_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let escrow_acc = next_account_info(acc_info_iter)?;
+        let initialiser_main_acc = next_account_info(acc_info_iter)?;
+        let taker_main_acc = next_account_info(acc_info_iter)?;
+        let initialiser_game_acc = next_account_info(acc_info_iter)?;
+        let taker_game_acc = next_account_info(acc_info_iter)?;
+
+        if !escrow_acc.is_writable
+            || !initialiser_main_acc.is_writable
+            || !taker_main_acc.is_writable
+            || !initialiser_game_acc.is_writable
+            || !taker_game_acc.is_writable
+        {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let escrow_info = load::<Escrow>(program_id, escrow_acc)?;
+
+        if !escrow_info.is_disputed() {
+            return Err(EscrowError::EscrowNotDisputed.into());
+        }
+
+        if escrow_info.arbiter != *arbiter_acc.key {
+            return Err(EscrowError::NotArbiter.into());
+        }
+
+        if escrow_info.initialiser_main_acc != *initialiser_main_acc.key
+            || escrow_info.taker_main_acc != *taker_main_acc.key
+            || escrow_info.initialiser_game_acc != *initialiser_game_acc.key
+            || escrow_info.taker_game_acc != *taker_game_acc.key
+        {
+            return Err(EscrowError::AccountMismatch.into());
+        }
+
+        // disputes are only raised on rent requests, where the initialiser is the renter
+
+        let lender_share = escrow_info
+            .amount
+            .checked_sub(resolve_data.renter_refund)
+            .ok_or(EscrowError::AmountOverflow)?;
+
+        let mut renter_game_info = load::<GameAcc>(program_id, initialiser_game_acc)?;
+        let mut lender_game_info = load::<GameAcc>(program_id, taker_game_acc)?;
+
+        if resolve_data.renter_keeps_asset {
+            GameAcc::keep_asset(
+                &mut renter_game_info,
+                &mut lender_game_info,
+                escrow_info.asset_id,
+            )?;
+        } else {
+            GameAcc::revert_asset(
+                &mut renter_game_info,
+                &mut lender_game_info,
+                escrow_info.asset_id,
+            )?;
+        }
+
+        GameAcc::pack(
+            renter_game_info,
+            &mut initialiser_game_acc.data.borrow_mut(),
+        )?;
+        GameAcc::pack(lender_game_info, &mut taker_game_acc.data.borrow_mut())?;
+
+        msg!("sending lender's share to taker_acc from escrow_acc");
+
+        Self::transfer_lamports(escrow_acc, taker_main_acc, lender_share)?;
+
+        let clock = Clock::get()?;
+
+        match resolve_data.at_fault {
+            DISPUTE_FAULT_RENTER => Self::record_against(
+                program_id,
+                accounts,
+                &escrow_info.initialiser_main_acc,
+                |reputation| reputation.disputed = reputation.disputed.saturating_add(1),
+            )?,
+            DISPUTE_FAULT_LENDER => Self::record_against(
+                program_id,
+                accounts,
+                &escrow_info.taker_main_acc,
+                |reputation| reputation.disputed = reputation.disputed.saturating_add(1),
+            )?,
+            _ => {}
+        }
+
+        Self::record_history(
+            program_id,
+            accounts,
+            escrow_info.asset_id,
+            HistoryEntry {
+                kind: if resolve_data.renter_keeps_asset {
+                    HISTORY_KIND_SALE
+                } else {
+                    HISTORY_KIND_RETURN
+                },
+                from_game_acc: escrow_info.taker_game_acc,
+                to_game_acc: escrow_info.initialiser_game_acc,
+                price: lender_share,
+                start: escrow_info.esc_taken_time,
+                end: clock.unix_timestamp,
+            },
+        )?;
+
+        Event::DisputeResolved {
+            escrow: *escrow_acc.key,
+            arbiter: *arbiter_acc.key,
+            renter_refund: resolve_data.renter_refund,
+            lender_share,
+            renter_keeps_asset: resolve_data.renter_keeps_asset,
+            at_fault: resolve_data.at_fault,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit();
+
+        // the renter's refund goes back with the rest of the escrow's lamports
+
+        msg!("sending remaining lamports to initialiser_acc from escrow_acc");
+
+        Self::close_acc(escrow_acc, initialiser_main_acc)?;
+
+        Ok(())
+    }
+
+    fn process_confirm_delivery(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let escrow_acc = next_account_info(acc_info_iter)?;
+        let taker_main_acc = next_account_info(acc_info_iter)?;
+
+        if !escrow_acc.is_writable || !taker_main_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let mut escrow_info = load::<Escrow>(program_id, escrow_acc)?;
+
+        if !escrow_info.is_taken
+            || escrow_info.oracle == Pubkey::default()
+            || escrow_info.delivered_at != 0
+        {
+            return Err(EscrowError::DeliveryNotPending.into());
+        }
+
+        if escrow_info.is_disputed() {
+            // the arbiter decides where the held rent goes
+            return Err(EscrowError::EscrowDisputed.into());
+        }
+
+        if escrow_info.taker_main_acc != *taker_main_acc.key {
+            return Err(EscrowError::AccountMismatch.into());
+        }
+
+        // the oracle either signs this transaction or its Ed25519 signature
+        // over the delivery attestation is verified by the instruction before this one
+
+        let attestation_acc = next_account_info(acc_info_iter)?;
+
+        if *attestation_acc.key == escrow_info.oracle {
+            if !attestation_acc.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+        } else {
+            let message = escrow_info.delivery_attestation(escrow_acc.key);
+
+            Self::verify_ed25519_attestation(attestation_acc, &escrow_info.oracle, &message)?;
+        }
+
+        let clock = Clock::get()?;
+
+        escrow_info.delivered_at = clock.unix_timestamp;
+
+        msg!("sending amount to taker_acc from escrow_acc");
+
+        Self::transfer_lamports(escrow_acc, taker_main_acc, escrow_info.amount)?;
+
+        Event::DeliveryConfirmed {
+            escrow: *escrow_acc.key,
+            oracle: escrow_info.oracle,
+            amount: escrow_info.amount,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit();
+
+        Escrow::pack(escrow_info, &mut escrow_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    // checks that the instruction before the current one is an Ed25519 precompile instruction
+    // verifying a single signature by the oracle over the message
+    fn verify_ed25519_attestation(
+        instructions_acc: &AccountInfo,
+        oracle: &Pubkey,
+        message: &[u8],
+    ) -> ProgramResult {
+        let current_index = load_current_index_checked(instructions_acc)?;
+
+        let prev_index = current_index
+            .checked_sub(1)
+            .ok_or(EscrowError::InvalidAttestation)?;
+
+        let ed25519_ix = load_instruction_at_checked(prev_index as usize, instructions_acc)?;
+
+        if ed25519_ix.program_id != ed25519_program::id() {
+            return Err(EscrowError::InvalidAttestation.into());
+        }
+
+        let data = &ed25519_ix.data;
+
+        // [num_signatures, padding] then the offsets of the signature, key and message
+        if data.first() != Some(&1) {
+            return Err(EscrowError::InvalidAttestation.into());
+        }
+
+        let read_u16 = |at: usize| -> Result<usize, ProgramError> {
+            data.get(at..(at + 2))
+                .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+                .ok_or_else(|| EscrowError::InvalidAttestation.into())
+        };
+
+        let signature_ix_index = read_u16(4)?;
+        let public_key_offset = read_u16(6)?;
+        let public_key_ix_index = read_u16(8)?;
+        let message_offset = read_u16(10)?;
+        let message_size = read_u16(12)?;
+        let message_ix_index = read_u16(14)?;
+
+        // everything must be in the Ed25519 instruction's own data
+        let this_ix = u16::MAX as usize;
+
+        if signature_ix_index != this_ix
+            || public_key_ix_index != this_ix
+            || message_ix_index != this_ix
+        {
+            return Err(EscrowError::InvalidAttestation.into());
+        }
+
+        let signer = data.get(public_key_offset..(public_key_offset + 32));
+        let signed_message = data.get(message_offset..(message_offset + message_size));
+
+        if signer != Some(oracle.as_ref()) || signed_message != Some(message) {
+            return Err(EscrowError::InvalidAttestation.into());
+        }
+
+        Ok(())
+    }
+
+    fn process_init_game_acc(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let game_acc = next_account_info(acc_info_iter)?;
+
+        if !game_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let main_acc = next_account_info(acc_info_iter)?;
+
+        let mut game_acc_info = load_uninitialized::<GameAcc>(program_id, game_acc)?;
+
+        game_acc_info.userspace_owner = *main_acc.key;
+        game_acc_info.acc_type = 2;
+
+        Event::GameAccCreated {
+            game_acc: *game_acc.key,
+            userspace_owner: *main_acc.key,
+        }
+        .emit();
+
+        GameAcc::pack(game_acc_info, &mut game_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    fn add_asset(program_id: &Pubkey, accounts: &[AccountInfo], asset_id: u64) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let game_acc = next_account_info(acc_info_iter)?;
+
+        if !game_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        let mut game_acc_info = load::<GameAcc>(program_id, game_acc)?;
+
+        let mut is_full = true;
+
+        for i in 0..20 {
+            if game_acc_info.owned[i] == 0 {
+                game_acc_info.owned[i] = asset_id;
+                is_full = false;
+                break;
+            }
+        }
+
+        if is_full {
+            return Err(EscrowError::AssetSpaceFull.into());
+        }
+
+        Event::AssetAdded {
+            game_acc: *game_acc.key,
+            asset_id,
+        }
+        .emit();
+
+        GameAcc::pack(game_acc_info, &mut game_acc.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    fn process_close_game_acc(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let acc_info_iter = &mut accounts.iter();
+
+        let game_acc = next_account_info(acc_info_iter)?;
+        let owner_main_acc = next_account_info(acc_info_iter)?;
+
+        if !game_acc.is_writable || !owner_main_acc.is_writable {
+            return Err(EscrowError::MissingPermission.into());
+        }
+
+        if !owner_main_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let game_acc_info = load::<GameAcc>(program_id, game_acc)?;
+
+        if game_acc_info.userspace_owner != *owner_main_acc.key {
+            return Err(EscrowError::OwnerMismatch.into());
+        }
+
+        if game_acc_info.is_locked() {
+            return Err(EscrowError::AccountLocked.into());
+        }
+
+        // rented assets have to go back to their lender and lent ones have to come back first
+
+        if game_acc_info.rented.iter().any(|&asset_id| asset_id != 0) {
+            return Err(EscrowError::AssetRented.into());
+        }
+
+        if game_acc_info.lent.iter().any(|&asset_id| asset_id != 0) {
+            return Err(EscrowError::AssetLentOut.into());
+        }
+
+        let mut assets_moved_to = None;
+
+        if let Some(assets_to_game_acc) = acc_info_iter.next() {
+            if !assets_to_game_acc.is_writable {
+                return Err(EscrowError::MissingPermission.into());
+            }
+
+            if assets_to_game_acc.key == game_acc.key {
+                return Err(EscrowError::IncorrectAcc.into());
+            }
+
+            let mut assets_to_info = load::<GameAcc>(program_id, assets_to_game_acc)?;
+
+            if assets_to_info.userspace_owner != *owner_main_acc.key {
+                return Err(EscrowError::OwnerMismatch.into());
+            }
+
+            for &asset_id in game_acc_info
+                .owned
+                .iter()
+                .filter(|&&asset_id| asset_id != 0)
+            {
+                let slot = assets_to_info
+                    .owned
+                    .iter_mut()
+                    .find(|slot| **slot == 0)
+                    .ok_or(EscrowError::AssetSpaceFull)?;
+
+                *slot = asset_id;
+            }
+
+            GameAcc::pack(assets_to_info, &mut assets_to_game_acc.data.borrow_mut())?;
+
+            assets_moved_to = Some(*assets_to_game_acc.key);
+        }
+
+        Event::GameAccClosed {
+            game_acc: *game_acc.key,
+            userspace_owner: *owner_main_acc.key,
+            assets_moved_to,
+        }
+        .emit();
+
+        msg!("sending rent-exempt amount to owner_acc from game_acc");
+
+        Self::close_acc(game_acc, owner_main_acc)?;
 
         Ok(())
     }
@@ -5,10 +5,153 @@ use solana_program::{
 };
 
 use crate::error::EscrowError;
-use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::convert::TryFrom;
+
+// every account starts with a header of its acc_type and layout version,
+// the rest is the account Borsh serialized so fields can be added in a new version
+pub const ACCOUNT_HEADER_LEN: usize = 2;
+pub const ACCOUNT_VERSION: u8 = 5;
+
+// version 1 accounts had only the acc_type byte before fixed size fields,
+// MigrateAccount decodes them field by field and upgrades them to the current version in place
+pub const LEGACY_HEADER_LEN: usize = 1;
+
+// acc_type left in a closed account's zeroed data, so it can't be used again
+// even if it is refunded within the same transaction
+pub const CLOSED_ACC_TYPE: u8 = 255;
+
+// serialized body length of an account type at a layout version, None if the type didn't exist.
+// from version 2 fields are only ever appended, so an older body is a prefix of the current one
+// and the appended fields of a migrated account read as zero
+pub fn body_len(acc_type: u8, version: u8) -> Option<usize> {
+    match (acc_type, version) {
+        (1, 1) => Some(129),
+        (1, 2) => Some(346),
+        (1, 3) => Some(354), // min_reputation added
+        (1, 4) => Some(394), // arbiter and disputed_at added
+        (1, 5) => Some(434), // oracle and delivered_at added
+        (2, 1) => Some(352),
+        (2, 2..=5) => Some(544),
+        (3, 2..=5) => Some(120),
+        (4, 2..=3) => Some(801),
+        (4, 4) => Some(833), // arbiter added
+        (4, 5) => Some(865), // oracle added
+        (5, 2..=5) => Some(19 + 89 * HISTORY_CAPACITY),
+        (6, 3..=5) => Some(57),
+        _ => None,
+    }
+}
+
+fn unpack_versioned<T: BorshDeserialize>(
+    src: &[u8],
+    acc_type: u8,
+) -> Result<(u8, T), ProgramError> {
+    if src.len() < ACCOUNT_HEADER_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (header, body) = src.split_at(ACCOUNT_HEADER_LEN);
+
+    match header {
+        [0, _] => {} // uninitialised
+        [t, ACCOUNT_VERSION] if *t == acc_type => {}
+        [t, _] if *t == acc_type => return Err(EscrowError::AccountOutdated.into()),
+        _ => return Err(ProgramError::InvalidAccountData),
+    }
+
+    let account = T::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)?;
+
+    Ok((header[0], account))
+}
+
+// reads the fixed size little endian fields of a version 1 account in order
+struct V1Fields<'a>(&'a [u8]);
+
+impl<'a> V1Fields<'a> {
+    fn new(src: &'a [u8], acc_type: u8) -> Result<Self, ProgramError> {
+        match body_len(acc_type, 1) {
+            Some(len) if src.len() == LEGACY_HEADER_LEN + len && src[0] == acc_type => {
+                Ok(V1Fields(&src[LEGACY_HEADER_LEN..]))
+            }
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    fn next<const N: usize>(&mut self) -> [u8; N] {
+        let (field, rest) = self.0.split_at(N);
+        self.0 = rest;
+
+        <[u8; N]>::try_from(field).expect("field read past a checked length")
+    }
+
+    fn bool(&mut self) -> Result<bool, ProgramError> {
+        match self.next() {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    fn pubkey(&mut self) -> Pubkey {
+        Pubkey::new_from_array(self.next())
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.next())
+    }
+
+    fn i64(&mut self) -> i64 {
+        i64::from_le_bytes(self.next())
+    }
+
+    fn u64_array(&mut self) -> [u64; 20] {
+        let mut array = [0; 20];
+        for value in array.iter_mut() {
+            *value = self.u64();
+        }
+
+        array
+    }
+}
+
+fn pack_versioned<T: BorshSerialize>(account: &T, acc_type: u8, dst: &mut [u8]) {
+    dst[0] = acc_type;
+    dst[1] = ACCOUNT_VERSION;
 
+    // the body always fits as Pack::LEN is the header plus the serialized size
+    account
+        .serialize(&mut &mut dst[ACCOUNT_HEADER_LEN..])
+        .expect("account body larger than Pack::LEN");
+}
+
+// max number of keys that can be allowed to take a private escrow
+pub const ALLOWED_TAKERS_LEN: usize = 3;
+
+// seconds the arbiter has to resolve a dispute, after which Revert applies the default outcome
+pub const DISPUTE_TIMEOUT: i64 = 7 * 24 * 60 * 60;
+
+// who a resolved dispute found at fault, counted in their Reputation A/c
+pub const DISPUTE_FAULT_NONE: u8 = 0;
+pub const DISPUTE_FAULT_RENTER: u8 = 1;
+pub const DISPUTE_FAULT_LENDER: u8 = 2;
+
+// start of the message an oracle signs with Ed25519 to confirm delivery
+pub const DELIVERY_ATTESTATION_PREFIX: &[u8] = b"gamenest:delivered:";
+
+// paid from the escrow's rent-exempt lamports to whoever reverts an expired rental for the initialiser
+pub const KEEPER_BOUNTY: u64 = 10_000; // in lamports
+
+// kinds of escrow, for a rent request the initialiser pays and the taker gives the asset,
+// for auctions the initialiser gives the asset and the taker (highest bidder) pays
+pub const ESCROW_KIND_RENT_REQUEST: u8 = 0;
+pub const ESCROW_KIND_ENGLISH_AUCTION: u8 = 1;
+pub const ESCROW_KIND_DUTCH_AUCTION: u8 = 2;
+
+#[derive(Clone, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize)]
 pub struct Escrow {
-    pub acc_type: u8, // 0 for unitialsied 1 for Escrow type and 2 for Game type
+    #[borsh(skip)]
+    pub acc_type: u8, // 0 for unitialsied 1 for Escrow type, 2 for Game type, 3 for Offer type, 4 for Market type, 5 for History type and 6 for Reputation type, kept in the account header
     pub is_taken: bool,
     pub initialiser_main_acc: Pubkey,
     pub initialiser_game_acc: Pubkey,
@@ -17,6 +160,22 @@ pub struct Escrow {
     pub amount: u64,         // in lamports
     pub time: u64,           //  in minutes
     pub asset_id: u64,
+    pub allowed_takers: [Pubkey; ALLOWED_TAKERS_LEN], // Pubkey::default() is an empty slot, all empty means anyone can take
+    pub expires_at: i64, // unix timestamp after which escrow can't be taken, 0 for no expiry
+    pub kind: u8,
+    pub taker_main_acc: Pubkey, // for auctions this is the current highest bidder
+    pub min_increment: u64,     // in lamports, only used by auctions
+    pub end_time: i64,          // unix timestamp at which auction ends
+    pub start_price: u64,       // in lamports, dutch auction price at start_time
+    pub end_price: u64,         // in lamports, dutch auction price from end_time onwards
+    pub start_time: i64,        // unix timestamp at which dutch auction price starts to decline
+    pub asset_id_max: u64, // non 0 for a collection bid, asset_id is set to the given asset once taken
+    pub market: Pubkey,    // Market A/c the escrow is listed in, Pubkey::default() if not listed
+    pub min_reputation: u64, // Reputation score a taker needs, 0 if anyone can take
+    pub arbiter: Pubkey,   // resolves disputes, Pubkey::default() if the escrow can't be disputed
+    pub disputed_at: i64,  // unix timestamp a dispute was raised, 0 if not disputed
+    pub oracle: Pubkey,    // game server key confirming delivery, Pubkey::default() if not needed
+    pub delivered_at: i64, // unix timestamp the oracle confirmed delivery, 0 if not confirmed
 }
 
 impl Sealed for Escrow {}
@@ -27,92 +186,148 @@ impl IsInitialized for Escrow {
     }
 }
 
+impl Escrow {
+    // a version 1 Escrow A/c was always a rent request, the fields added since read as zero
+    pub fn unpack_v1(src: &[u8]) -> Result<Escrow, ProgramError> {
+        let mut fields = V1Fields::new(src, 1)?;
+
+        Ok(Escrow {
+            acc_type: 1,
+            is_taken: fields.bool()?,
+            initialiser_main_acc: fields.pubkey(),
+            initialiser_game_acc: fields.pubkey(),
+            taker_game_acc: fields.pubkey(),
+            esc_taken_time: fields.i64(),
+            amount: fields.u64(),
+            time: fields.u64(),
+            asset_id: fields.u64(),
+            kind: ESCROW_KIND_RENT_REQUEST,
+            ..Escrow::default()
+        })
+    }
+
+    // checks if the taker's main acc is allowed to take this escrow
+    pub fn is_allowed_taker(&self, taker_main_acc: &Pubkey) -> bool {
+        let is_public = self
+            .allowed_takers
+            .iter()
+            .all(|key| *key == Pubkey::default());
+
+        is_public || self.allowed_takers.contains(taker_main_acc)
+    }
+
+    // an untaken escrow can't be taken once it has expired
+    pub fn is_expired(&self, unix_timestamp: i64) -> bool {
+        self.expires_at != 0 && unix_timestamp >= self.expires_at
+    }
+
+    // a collection bid accepts any asset from asset_id to asset_id_max
+    pub fn accepts_asset(&self, asset_id: u64) -> bool {
+        if self.asset_id_max == 0 {
+            asset_id == self.asset_id
+        } else {
+            asset_id >= self.asset_id && asset_id <= self.asset_id_max
+        }
+    }
+
+    // for a rent request the initialiser rents the asset, for auctions he lends it
+    pub fn initialiser_is_renter(&self) -> bool {
+        self.kind == ESCROW_KIND_RENT_REQUEST
+    }
+
+    // for auctions amount is the reserve price till the first bid and the highest bid after it
+    // a rent request with an arbiter or oracle keeps the rent in the escrow, with an arbiter
+    // so it can be split if the rental is disputed and with an oracle till delivery is confirmed
+    pub fn holds_rent(&self) -> bool {
+        self.kind == ESCROW_KIND_RENT_REQUEST
+            && (self.arbiter != Pubkey::default() || self.oracle != Pubkey::default())
+            && self.delivered_at == 0
+    }
+
+    // held rent goes to the lender when the rental ends unless delivery was never confirmed
+    pub fn pays_rent_on_revert(&self) -> bool {
+        self.holds_rent() && self.oracle == Pubkey::default()
+    }
+
+    pub fn can_be_disputed(&self) -> bool {
+        self.is_taken && self.holds_rent() && self.arbiter != Pubkey::default()
+    }
+
+    // message the oracle signs to attest the rented asset was delivered to the renter in-game
+    pub fn delivery_attestation(&self, escrow_key: &Pubkey) -> Vec<u8> {
+        let mut message = DELIVERY_ATTESTATION_PREFIX.to_vec();
+        message.extend_from_slice(escrow_key.as_ref());
+        message.extend_from_slice(self.initialiser_game_acc.as_ref());
+        message.extend_from_slice(&self.asset_id.to_le_bytes());
+
+        message
+    }
+
+    pub fn is_disputed(&self) -> bool {
+        self.disputed_at != 0
+    }
+
+    pub fn has_bids(&self) -> bool {
+        self.taker_main_acc != Pubkey::default()
+    }
+
+    // unix timestamp a rental taken at taken_time is due back, same as taken_time for a sale
+    pub fn due_at(&self, taken_time: i64) -> i64 {
+        let secs = i64::try_from(self.time)
+            .unwrap_or(i64::MAX)
+            .saturating_mul(60);
+
+        taken_time.saturating_add(secs)
+    }
+
+    // dutch auction price declines linearly from start_price to end_price between start_time and end_time
+    pub fn current_price(&self, unix_timestamp: i64) -> u64 {
+        if unix_timestamp <= self.start_time {
+            return self.start_price;
+        }
+
+        if unix_timestamp >= self.end_time {
+            return self.end_price;
+        }
+
+        let elapsed = (unix_timestamp as i128 - self.start_time as i128) as u128;
+        let duration = (self.end_time as i128 - self.start_time as i128) as u128;
+        let price_drop =
+            self.start_price.saturating_sub(self.end_price) as u128 * elapsed / duration;
+
+        self.start_price.saturating_sub(price_drop as u64)
+    }
+}
+
 impl Pack for Escrow {
-    const LEN: usize = 130;
+    const LEN: usize = ACCOUNT_HEADER_LEN + 434;
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, Escrow::LEN];
-        let (
-            acc_type,
-            is_taken,
-            initialiser_main_acc,
-            initialiser_game_acc,
-            taker_game_acc,
-            esc_taken_time,
-            amount,
-            time,
-            asset_id,
-        ) = array_refs![src, 1, 1, 32, 32, 32, 8, 8, 8, 8];
-
-        let acc_type: u8 = match acc_type {
-            [0] => 0,
-            [1] => 1,
-            _ => return Err(ProgramError::InvalidAccountData),
-        };
+        let (acc_type, mut escrow) = unpack_versioned::<Escrow>(src, 1)?;
+        escrow.acc_type = acc_type;
 
-        let is_taken = match is_taken {
-            [0] => false,
-            [1] => true,
+        match escrow.kind {
+            ESCROW_KIND_RENT_REQUEST | ESCROW_KIND_ENGLISH_AUCTION | ESCROW_KIND_DUTCH_AUCTION => {}
             _ => return Err(ProgramError::InvalidAccountData),
-        };
+        }
 
-        Ok(Escrow {
-            acc_type,
-            is_taken,
-            initialiser_main_acc: Pubkey::new_from_array(*initialiser_main_acc),
-            initialiser_game_acc: Pubkey::new_from_array(*initialiser_game_acc),
-            taker_game_acc: Pubkey::new_from_array(*taker_game_acc),
-            esc_taken_time: i64::from_le_bytes(*esc_taken_time),
-            amount: u64::from_le_bytes(*amount),
-            time: u64::from_le_bytes(*time),
-            asset_id: u64::from_le_bytes(*asset_id),
-        })
+        Ok(escrow)
     }
 
-    fn pack_into_slice(&self, ad: &mut [u8]) {
-        // ad - actual data location
-        let ad = array_mut_ref![ad, 0, Escrow::LEN];
-        let (
-            acc_type_ad,
-            is_taken_ad,
-            initialiser_main_acc_ad,
-            initialiser_game_acc_ad,
-            taker_game_acc_ad,
-            esc_taken_time_ad,
-            amount_ad,
-            time_ad,
-            asset_id_ad,
-        ) = mut_array_refs![ad, 1, 1, 32, 32, 32, 8, 8, 8, 8];
-
-        let Escrow {
-            acc_type,
-            is_taken,
-            initialiser_main_acc,
-            initialiser_game_acc,
-            taker_game_acc,
-            esc_taken_time,
-            amount,
-            time,
-            asset_id,
-        } = self;
-
-        acc_type_ad[0] = *acc_type;
-        is_taken_ad[0] = *is_taken as u8;
-        initialiser_main_acc_ad.copy_from_slice(initialiser_main_acc.as_ref());
-        initialiser_game_acc_ad.copy_from_slice(initialiser_game_acc.as_ref());
-        taker_game_acc_ad.copy_from_slice(taker_game_acc.as_ref());
-        *esc_taken_time_ad = esc_taken_time.to_le_bytes();
-        *amount_ad = amount.to_le_bytes();
-        *time_ad = time.to_le_bytes();
-        *asset_id_ad = asset_id.to_le_bytes();
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_versioned(self, self.acc_type, dst)
     }
 }
 
+#[derive(Clone, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize)]
 pub struct GameAcc {
-    pub acc_type: u8,
+    #[borsh(skip)]
+    pub acc_type: u8, // 2 for Game type, kept in the account header
     pub userspace_owner: Pubkey,
     pub owned: [u64; 20],  //owned by player and using
     pub rented: [u64; 20], // using but taken from others on rent
+    pub lent: [u64; 20],   // owned by player but given to others on rent
+    pub locked_by: Pubkey, // game program authority that locked it for a match, Pubkey::default() if unlocked
 }
 
 impl Sealed for GameAcc {}
@@ -123,7 +338,34 @@ impl IsInitialized for GameAcc {
     }
 }
 
+// 0 marks an empty slot, so no one holds it and taking it would pay for nothing,
+// moves that give an asset back don't check it so escrows made before this can still end
+fn check_asset_id(asset_id: u64) -> Result<(), ProgramError> {
+    if asset_id == 0 {
+        return Err(EscrowError::AssetNotOwned.into());
+    }
+
+    Ok(())
+}
+
 impl GameAcc {
+    // a version 1 GameAcc had nothing lent and couldn't be locked
+    pub fn unpack_v1(src: &[u8]) -> Result<GameAcc, ProgramError> {
+        let mut fields = V1Fields::new(src, 2)?;
+
+        Ok(GameAcc {
+            acc_type: 2,
+            userspace_owner: fields.pubkey(),
+            owned: fields.u64_array(),
+            rented: fields.u64_array(),
+            ..GameAcc::default()
+        })
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked_by != Pubkey::default()
+    }
+
     // this will transfer asset from sender's owned to reciever's rented
     //you can't give the rented asset for rent
     pub fn transfer_asset(
@@ -131,6 +373,12 @@ impl GameAcc {
         reciever_acc_info: &mut GameAcc,
         asset_id: u64,
     ) -> Result<(), ProgramError> {
+        check_asset_id(asset_id)?;
+
+        if sender_acc_info.is_locked() {
+            return Err(EscrowError::AccountLocked.into());
+        }
+
         let mut is_absent = true;
         let mut is_full = true;
         let mut a: usize = 0; // 0 so that compiler doesn't show us of uninitialsied variable
@@ -144,7 +392,7 @@ impl GameAcc {
         }
 
         if is_absent {
-            return Err(EscrowError::IncorrectAcc.into()); // incorrect account as it is not eligible to sender
+            return Err(EscrowError::AssetNotOwned.into());
         }
 
         for j in 0..20 {
@@ -158,7 +406,44 @@ impl GameAcc {
             return Err(EscrowError::AssetSpaceFull.into());
         }
 
+        let b = sender_acc_info
+            .lent
+            .iter()
+            .position(|id| *id == 0)
+            .ok_or(EscrowError::AssetSpaceFull)?;
+
         sender_acc_info.owned[a] = 0;
+        sender_acc_info.lent[b] = asset_id;
+
+        Ok(())
+    }
+
+    // this will transfer asset from sender's owned to reciever's owned, used when asset is sold
+    pub fn sell_asset(
+        sender_acc_info: &mut GameAcc,
+        reciever_acc_info: &mut GameAcc,
+        asset_id: u64,
+    ) -> Result<(), ProgramError> {
+        check_asset_id(asset_id)?;
+
+        if sender_acc_info.is_locked() {
+            return Err(EscrowError::AccountLocked.into());
+        }
+
+        let a = sender_acc_info
+            .owned
+            .iter()
+            .position(|id| *id == asset_id)
+            .ok_or(EscrowError::AssetNotOwned)?;
+
+        let b = reciever_acc_info
+            .owned
+            .iter()
+            .position(|id| *id == 0)
+            .ok_or(EscrowError::AssetSpaceFull)?;
+
+        sender_acc_info.owned[a] = 0;
+        reciever_acc_info.owned[b] = asset_id;
 
         Ok(())
     }
@@ -184,60 +469,406 @@ impl GameAcc {
             }
         }
 
+        for i in 0..20 {
+            if reciever_acc_info.lent[i] == asset_id {
+                reciever_acc_info.lent[i] = 0;
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    // the renter keeps the rented asset for good, used when a dispute is resolved that way
+    pub fn keep_asset(
+        renter_acc_info: &mut GameAcc,
+        lender_acc_info: &mut GameAcc,
+        asset_id: u64,
+    ) -> Result<(), ProgramError> {
+        let a = renter_acc_info
+            .rented
+            .iter()
+            .position(|id| *id == asset_id)
+            .ok_or(EscrowError::AssetNotOwned)?;
+
+        let b = renter_acc_info
+            .owned
+            .iter()
+            .position(|id| *id == 0)
+            .ok_or(EscrowError::AssetSpaceFull)?;
+
+        renter_acc_info.rented[a] = 0;
+        renter_acc_info.owned[b] = asset_id;
+
+        if let Some(c) = lender_acc_info.lent.iter().position(|id| *id == asset_id) {
+            lender_acc_info.lent[c] = 0;
+        }
+
         Ok(())
     }
+
+    // this will move a listed asset from owned to lent so the seller can't use it
+    // or list it again till the listing ends
+    pub fn hold_asset(&mut self, asset_id: u64) -> Result<(), ProgramError> {
+        check_asset_id(asset_id)?;
+
+        if self.is_locked() {
+            return Err(EscrowError::AccountLocked.into());
+        }
+
+        let a = self
+            .owned
+            .iter()
+            .position(|id| *id == asset_id)
+            .ok_or(EscrowError::AssetNotOwned)?;
+
+        let b = self
+            .lent
+            .iter()
+            .position(|id| *id == 0)
+            .ok_or(EscrowError::AssetSpaceFull)?;
+
+        self.owned[a] = 0;
+        self.lent[b] = asset_id;
+
+        Ok(())
+    }
+
+    // this will give a held asset back to owned, used when a listing ends without a taker
+    pub fn release_asset(&mut self, asset_id: u64) -> Result<(), ProgramError> {
+        let a = self
+            .lent
+            .iter()
+            .position(|id| *id == asset_id)
+            .ok_or(EscrowError::AssetNotOwned)?;
+
+        let b = self
+            .owned
+            .iter()
+            .position(|id| *id == 0)
+            .ok_or(EscrowError::AssetSpaceFull)?;
+
+        self.lent[a] = 0;
+        self.owned[b] = asset_id;
+
+        Ok(())
+    }
+
+    // this will transfer a held asset from sender's lent to reciever's owned, used when it is sold
+    pub fn sell_held_asset(
+        sender_acc_info: &mut GameAcc,
+        reciever_acc_info: &mut GameAcc,
+        asset_id: u64,
+    ) -> Result<(), ProgramError> {
+        check_asset_id(asset_id)?;
+
+        let a = sender_acc_info
+            .lent
+            .iter()
+            .position(|id| *id == asset_id)
+            .ok_or(EscrowError::AssetNotOwned)?;
+
+        let b = reciever_acc_info
+            .owned
+            .iter()
+            .position(|id| *id == 0)
+            .ok_or(EscrowError::AssetSpaceFull)?;
+
+        sender_acc_info.lent[a] = 0;
+        reciever_acc_info.owned[b] = asset_id;
+
+        Ok(())
+    }
+
+    // this will add a held asset to reciever's rented, it stays in sender's lent till it is reverted
+    pub fn rent_held_asset(
+        sender_acc_info: &mut GameAcc,
+        reciever_acc_info: &mut GameAcc,
+        asset_id: u64,
+    ) -> Result<(), ProgramError> {
+        check_asset_id(asset_id)?;
+
+        if !sender_acc_info.lent.contains(&asset_id) {
+            return Err(EscrowError::AssetNotOwned.into());
+        }
+
+        let b = reciever_acc_info
+            .rented
+            .iter()
+            .position(|id| *id == 0)
+            .ok_or(EscrowError::AssetSpaceFull)?;
+
+        reciever_acc_info.rented[b] = asset_id;
+
+        Ok(())
+    }
+
+    // an asset is usable by the player if it is owned or rented by him
+    pub fn holds_asset(&self, asset_id: u64) -> bool {
+        asset_id != 0 && (self.owned.contains(&asset_id) || self.rented.contains(&asset_id))
+    }
 }
 
 impl Pack for GameAcc {
-    const LEN: usize = 353;
+    const LEN: usize = ACCOUNT_HEADER_LEN + 544;
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let mut owned: [u64; 20] = [0; 20];
-        let mut rented: [u64; 20] = [0; 20];
-        let src = array_ref![src, 0, GameAcc::LEN];
-        let (acc_type, userspace_owner, owned_ref, rented_ref) = array_refs![src, 1, 32, 160, 160];
-
-        let acc_type: u8 = match acc_type {
-            [0] => 0,
-            [2] => 2,
-            _ => return Err(ProgramError::InvalidAccountData),
-        };
+        let (acc_type, mut game_acc) = unpack_versioned::<GameAcc>(src, 2)?;
+        game_acc.acc_type = acc_type;
+
+        Ok(game_acc)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_versioned(self, self.acc_type, dst)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Offer {
+    #[borsh(skip)]
+    pub acc_type: u8, // 3 for Offer type, kept in the account header
+    pub escrow: Pubkey,
+    pub offerer_main_acc: Pubkey,
+    pub offerer_game_acc: Pubkey,
+    pub amount: u64, // in lamports
+    pub time: u64,   // in minutes
+    pub asset_id: u64,
+}
+
+impl Sealed for Offer {}
+
+impl IsInitialized for Offer {
+    fn is_initialized(&self) -> bool {
+        self.acc_type == 3
+    }
+}
+
+impl Pack for Offer {
+    const LEN: usize = ACCOUNT_HEADER_LEN + 120;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let (acc_type, mut offer) = unpack_versioned::<Offer>(src, 3)?;
+        offer.acc_type = acc_type;
+
+        Ok(offer)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_versioned(self, self.acc_type, dst)
+    }
+}
+
+// max number of open offers a market keeps, the lowest priced offer is dropped when it is full
+pub const MARKET_CAPACITY: usize = 16;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct MarketEntry {
+    pub price: u64, // in lamports
+    pub asset_id: u64,
+    pub escrow: Pubkey,
+}
+
+// index of open rent request escrows sorted by price, best paying first. it isn't tied to a game,
+// anyone can create one so clients only read the Market A/c's whose authority they trust
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Market {
+    #[borsh(skip)]
+    pub acc_type: u8, // 4 for Market type, kept in the account header
+    pub authority: Pubkey,
+    pub len: u8,
+    pub entries: [MarketEntry; MARKET_CAPACITY],
+    pub arbiter: Pubkey, // resolves disputes of the game's escrows, Pubkey::default() if none
+    pub oracle: Pubkey,  // game server key confirming delivery, Pubkey::default() if none
+}
+
+impl Sealed for Market {}
+
+impl IsInitialized for Market {
+    fn is_initialized(&self) -> bool {
+        self.acc_type == 4
+    }
+}
+
+impl Market {
+    // inserts the entry at its sorted place, returns false if it is not good enough to be kept
+    pub fn insert(&mut self, entry: MarketEntry) -> bool {
+        let len = self.len as usize;
 
-        for i in 1..=20 {
-            owned[i - 1] =
-                u64::from_le_bytes(*array_ref![&(owned_ref[((i - 1) * 8)..(i * 8)]), 0, 8]);
-            rented[i - 1] =
-                u64::from_le_bytes(*array_ref![&(rented_ref[((i - 1) * 8)..(i * 8)]), 0, 8]);
+        let pos = self.entries[..len]
+            .iter()
+            .position(|e| e.price < entry.price)
+            .unwrap_or(len);
+
+        if pos == MARKET_CAPACITY {
+            return false;
         }
 
-        Ok(GameAcc {
-            acc_type: acc_type,
-            userspace_owner: Pubkey::new_from_array(*userspace_owner),
-            owned: owned,
-            rented: rented,
-        })
+        let last = len.min(MARKET_CAPACITY - 1);
+        self.entries.copy_within(pos..last, pos + 1);
+        self.entries[pos] = entry;
+        self.len = (last + 1) as u8;
+
+        true
     }
 
-    fn pack_into_slice(&self, ad: &mut [u8]) {
-        let ad = array_mut_ref![ad, 0, GameAcc::LEN];
-        let (acc_type_ad, userspace_owner_ad, owned_ad, rented_ad) =
-            mut_array_refs![ad, 1, 32, 160, 160];
+    // removing an escrow that is not listed (i.e. it was dropped) does nothing
+    pub fn remove(&mut self, escrow: &Pubkey) {
+        let len = self.len as usize;
 
-        let GameAcc {
-            acc_type,
-            userspace_owner,
-            owned,
-            rented,
-        } = self;
+        if let Some(pos) = self.entries[..len].iter().position(|e| e.escrow == *escrow) {
+            self.entries.copy_within((pos + 1)..len, pos);
+            self.entries[len - 1] = MarketEntry::default();
+            self.len -= 1;
+        }
+    }
+}
 
-        for i in 0..20 {
-            for j in 0..8 {
-                owned_ad[i * 8 + j] = owned[i].to_le_bytes()[j];
-                rented_ad[i * 8 + j] = rented[i].to_le_bytes()[j];
-            }
+impl Pack for Market {
+    const LEN: usize = ACCOUNT_HEADER_LEN + 865;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let (acc_type, mut market) = unpack_versioned::<Market>(src, 4)?;
+        market.acc_type = acc_type;
+
+        if market.len as usize > MARKET_CAPACITY {
+            return Err(ProgramError::InvalidAccountData);
         }
 
-        acc_type_ad[0] = *acc_type;
-        userspace_owner_ad.copy_from_slice(userspace_owner.as_ref());
+        Ok(market)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_versioned(self, self.acc_type, dst)
+    }
+}
+
+// max number of entries a history keeps, the oldest entry is overwritten when it is full
+pub const HISTORY_CAPACITY: usize = 16;
+
+// seed of the History A/c PDA, followed by the asset_id as le bytes
+pub const HISTORY_SEED: &[u8] = b"history";
+
+// kinds of history entry
+pub const HISTORY_KIND_RENTAL: u8 = 0;
+pub const HISTORY_KIND_SALE: u8 = 1;
+pub const HISTORY_KIND_RETURN: u8 = 2;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct HistoryEntry {
+    pub kind: u8,
+    pub from_game_acc: Pubkey, // Game Program A/c the asset left
+    pub to_game_acc: Pubkey,   // Game Program A/c the asset went to
+    pub price: u64,            // in lamports, 0 for a return
+    pub start: i64,            // unix timestamp
+    pub end: i64, // unix timestamp the rental is due back or was returned, same as start for a sale
+}
+
+// append-only log of an asset's rentals, sales and returns kept at a PDA of the asset_id
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct History {
+    #[borsh(skip)]
+    pub acc_type: u8, // 5 for History type, kept in the account header
+    pub asset_id: u64,
+    pub bump: u8,
+    pub head: u8,   // index the next entry is written at
+    pub len: u8,    // number of entries kept, at most HISTORY_CAPACITY
+    pub total: u64, // number of entries ever recorded
+    pub entries: [HistoryEntry; HISTORY_CAPACITY],
+}
+
+impl Sealed for History {}
+
+impl IsInitialized for History {
+    fn is_initialized(&self) -> bool {
+        self.acc_type == 5
+    }
+}
+
+impl History {
+    pub fn find_address(program_id: &Pubkey, asset_id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[HISTORY_SEED, &asset_id.to_le_bytes()], program_id)
+    }
+
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.entries[self.head as usize] = entry;
+        self.head = ((self.head as usize + 1) % HISTORY_CAPACITY) as u8;
+        self.len = (self.len as usize + 1).min(HISTORY_CAPACITY) as u8;
+        self.total = self.total.saturating_add(1);
+    }
+
+    // entries kept, oldest first
+    pub fn entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        let start = (self.head as usize + HISTORY_CAPACITY - self.len as usize) % HISTORY_CAPACITY;
+
+        (0..self.len as usize).map(move |i| &self.entries[(start + i) % HISTORY_CAPACITY])
+    }
+}
+
+impl Pack for History {
+    const LEN: usize = ACCOUNT_HEADER_LEN + 19 + 89 * HISTORY_CAPACITY;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let (acc_type, mut history) = unpack_versioned::<History>(src, 5)?;
+        history.acc_type = acc_type;
+
+        if history.head as usize >= HISTORY_CAPACITY || history.len as usize > HISTORY_CAPACITY {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(history)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_versioned(self, self.acc_type, dst)
+    }
+}
+
+// seed of the Reputation A/c PDA, followed by the wallet's key
+pub const REPUTATION_SEED: &[u8] = b"reputation";
+
+// deal counts of a wallet kept at a PDA of its key, updated when deals settle
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Reputation {
+    #[borsh(skip)]
+    pub acc_type: u8, // 6 for Reputation type, kept in the account header
+    pub wallet: Pubkey,
+    pub bump: u8,
+    pub completed: u64, // rentals returned and sales made, as either party
+    pub cancelled: u64, // escrows the wallet cancelled before they expired
+    pub disputed: u64,  // deals the wallet was found at fault in a dispute
+}
+
+impl Sealed for Reputation {}
+
+impl IsInitialized for Reputation {
+    fn is_initialized(&self) -> bool {
+        self.acc_type == 6
+    }
+}
+
+impl Reputation {
+    pub fn find_address(program_id: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[REPUTATION_SEED, wallet.as_ref()], program_id)
+    }
+
+    // every dispute lost takes away a completed deal
+    pub fn score(&self) -> u64 {
+        self.completed.saturating_sub(self.disputed)
+    }
+}
+
+impl Pack for Reputation {
+    const LEN: usize = ACCOUNT_HEADER_LEN + 57;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let (acc_type, mut reputation) = unpack_versioned::<Reputation>(src, 6)?;
+        reputation.acc_type = acc_type;
+
+        Ok(reputation)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        pack_versioned(self, self.acc_type, dst)
     }
 }
@@ -0,0 +1,138 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    sysvar::{rent::Rent, Sysvar},
+};
+
+use crate::error::EscrowError;
+use crate::state::{
+    body_len, Escrow, GameAcc, History, Market, Offer, Reputation, ACCOUNT_HEADER_LEN,
+    ACCOUNT_VERSION, CLOSED_ACC_TYPE, LEGACY_HEADER_LEN,
+};
+
+// checks every account goes through before its data is used, so an account that is foreign,
+// of another type, undersized or not rent-exempt fails cleanly instead of aborting the program
+
+pub trait ProgramAccount: Pack + IsInitialized {
+    const ACC_TYPE: u8;
+
+    // error for a program account of another type passed where a T is expected
+    const WRONG_TYPE: EscrowError = EscrowError::IncorrectAcc;
+}
+
+impl ProgramAccount for Escrow {
+    const ACC_TYPE: u8 = 1;
+    const WRONG_TYPE: EscrowError = EscrowError::NotEscrowAccount;
+}
+
+impl ProgramAccount for GameAcc {
+    const ACC_TYPE: u8 = 2;
+    const WRONG_TYPE: EscrowError = EscrowError::NotGameAccount;
+}
+
+impl ProgramAccount for Offer {
+    const ACC_TYPE: u8 = 3;
+}
+
+impl ProgramAccount for Market {
+    const ACC_TYPE: u8 = 4;
+}
+
+impl ProgramAccount for History {
+    const ACC_TYPE: u8 = 5;
+}
+
+impl ProgramAccount for Reputation {
+    const ACC_TYPE: u8 = 6;
+}
+
+// first byte of the account's data, an account without data reads as uninitialised
+// and a closed account is rejected whatever it is being used as
+pub fn acc_type(acc: &AccountInfo) -> Result<u8, ProgramError> {
+    match acc.try_borrow_data()?.first().copied().unwrap_or(0) {
+        CLOSED_ACC_TYPE => Err(EscrowError::AccountClosed.into()),
+        acc_type => Ok(acc_type),
+    }
+}
+
+pub fn check_rent_exempt(acc: &AccountInfo) -> ProgramResult {
+    if !Rent::get()?.is_exempt(acc.lamports(), acc.data_len()) {
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    Ok(())
+}
+
+// an account sized for an older layout of T has to be migrated before it can be used
+fn check_len<T: ProgramAccount>(acc: &AccountInfo) -> ProgramResult {
+    if acc.data_len() == T::LEN {
+        return Ok(());
+    }
+
+    let is_outdated = (1..ACCOUNT_VERSION).any(|version| {
+        let header_len = if version == 1 {
+            LEGACY_HEADER_LEN
+        } else {
+            ACCOUNT_HEADER_LEN
+        };
+
+        body_len(T::ACC_TYPE, version).is_some_and(|len| header_len + len == acc.data_len())
+    });
+
+    if is_outdated {
+        return Err(EscrowError::AccountOutdated.into());
+    }
+
+    Err(ProgramError::InvalidAccountData)
+}
+
+// checks the account is owned by the program, holds a T and is rent-exempt
+pub fn check_program_acc<T: ProgramAccount>(
+    program_id: &Pubkey,
+    acc: &AccountInfo,
+) -> ProgramResult {
+    if acc.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if acc_type(acc)? != T::ACC_TYPE {
+        return Err(T::WRONG_TYPE.into());
+    }
+
+    check_len::<T>(acc)?;
+
+    check_rent_exempt(acc)
+}
+
+// validates an initialised T and unpacks it
+pub fn load<T: ProgramAccount>(program_id: &Pubkey, acc: &AccountInfo) -> Result<T, ProgramError> {
+    check_program_acc::<T>(program_id, acc)?;
+
+    T::unpack(&acc.try_borrow_data()?)
+}
+
+// validates an account the instruction is about to initialise as a T,
+// it must be owned by the program, sized for a T, rent-exempt and not hold an account yet
+pub fn load_uninitialized<T: ProgramAccount>(
+    program_id: &Pubkey,
+    acc: &AccountInfo,
+) -> Result<T, ProgramError> {
+    if acc.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    match acc_type(acc)? {
+        0 => {}
+        t if t == T::ACC_TYPE => return Err(ProgramError::AccountAlreadyInitialized),
+        _ => return Err(T::WRONG_TYPE.into()),
+    }
+
+    check_len::<T>(acc)?;
+
+    check_rent_exempt(acc)?;
+
+    T::unpack_unchecked(&acc.try_borrow_data()?)
+}
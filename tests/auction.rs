@@ -0,0 +1,450 @@
+mod common;
+
+use common::*;
+use game_nest::{
+    error::EscrowError,
+    instruction::{self, InitAuctionData},
+    state::Escrow,
+};
+use solana_program::{clock::Clock, program_pack::Pack, pubkey::Pubkey};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::{Keypair, Signer};
+
+const ASSET_ID: u64 = 11;
+const RESERVE_PRICE: u64 = 1_000_000;
+const MIN_INCREMENT: u64 = 100_000;
+const AUCTION_MINUTES: i64 = 30;
+
+struct Auction {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    seller: Keypair,
+    bidder: Keypair,
+    other_bidder: Keypair,
+    seller_game_acc: Keypair,
+    bidder_game_acc: Keypair,
+    other_bidder_game_acc: Keypair,
+    escrow_acc: Keypair,
+}
+
+async fn auction_data(context: &mut ProgramTestContext, time: u64) -> InitAuctionData {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+
+    InitAuctionData {
+        reserve_price: RESERVE_PRICE,
+        min_increment: MIN_INCREMENT,
+        time,
+        asset_id: ASSET_ID,
+        end_time: clock.unix_timestamp + AUCTION_MINUTES * 60,
+        min_reputation: 0,
+    }
+}
+
+// a seller holding ASSET_ID and two bidders, the Escrow A/c is created but not initialised
+async fn setup() -> Auction {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+
+    let seller = funded_wallet(&mut program_test);
+    let bidder = funded_wallet(&mut program_test);
+    let other_bidder = funded_wallet(&mut program_test);
+
+    let mut context = program_test.start_with_context().await;
+
+    let seller_game_acc = create_game_acc(&mut context, &program_id, &seller).await;
+    let bidder_game_acc = create_game_acc(&mut context, &program_id, &bidder).await;
+    let other_bidder_game_acc = create_game_acc(&mut context, &program_id, &other_bidder).await;
+
+    add_asset(
+        &mut context,
+        &program_id,
+        &seller_game_acc.pubkey(),
+        ASSET_ID,
+    )
+    .await;
+    create_history(&mut context, &program_id, ASSET_ID).await;
+
+    let escrow_acc = create_program_acc(&mut context, &program_id, Escrow::LEN, 0).await;
+
+    Auction {
+        context,
+        program_id,
+        seller,
+        bidder,
+        other_bidder,
+        seller_game_acc,
+        bidder_game_acc,
+        other_bidder_game_acc,
+        escrow_acc,
+    }
+}
+
+// time is 0 for a sale or the rental time in minutes
+async fn setup_auction(time: u64) -> Auction {
+    let mut auction = setup().await;
+    let seller = auction.seller.insecure_clone();
+
+    let data = auction_data(&mut auction.context, time).await;
+    let init = instruction::init_auction(
+        &auction.program_id,
+        &auction.escrow_acc.pubkey(),
+        &auction.seller_game_acc.pubkey(),
+        &seller.pubkey(),
+        data,
+    );
+    send(&mut auction.context, &[init], &[&seller])
+        .await
+        .unwrap();
+
+    auction
+}
+
+async fn place_bid(
+    auction: &mut Auction,
+    bidder: &Keypair,
+    bidder_game_acc: &Pubkey,
+    prev_bidder: Option<&Pubkey>,
+    bid: u64,
+) -> Result<(), solana_sdk::transaction::TransactionError> {
+    let place = instruction::place_bid(
+        &auction.program_id,
+        &bidder.pubkey(),
+        &auction.escrow_acc.pubkey(),
+        bidder_game_acc,
+        prev_bidder,
+        bid,
+    );
+    send(&mut auction.context, &[place], &[bidder]).await
+}
+
+fn settle_ix(auction: &Auction, with_winner: bool) -> solana_program::instruction::Instruction {
+    let winner_game_acc = auction.bidder_game_acc.pubkey();
+    let winner_main_acc = auction.bidder.pubkey();
+
+    let settle = instruction::settle_auction(
+        &auction.program_id,
+        &auction.escrow_acc.pubkey(),
+        &auction.seller.pubkey(),
+        &auction.seller_game_acc.pubkey(),
+        if with_winner {
+            Some((&winner_game_acc, &winner_main_acc))
+        } else {
+            None
+        },
+    );
+
+    instruction::with_history(settle, ASSET_ID)
+}
+
+#[tokio::test]
+async fn init_auction_holds_the_asset() {
+    let mut auction = setup_auction(0).await;
+
+    let seller_info = game_acc_info(&mut auction.context, &auction.seller_game_acc.pubkey()).await;
+    assert!(!seller_info.owned.contains(&ASSET_ID));
+    assert!(seller_info.lent.contains(&ASSET_ID));
+
+    let escrow = escrow_info(&mut auction.context, &auction.escrow_acc.pubkey()).await;
+    assert_eq!(escrow.amount, RESERVE_PRICE);
+    assert_eq!(escrow.asset_id, ASSET_ID);
+}
+
+#[tokio::test]
+async fn init_auction_without_the_asset_fails() {
+    let mut auction = setup().await;
+    let bidder = auction.bidder.insecure_clone();
+
+    // the bidder's game account has nothing to sell
+    let data = auction_data(&mut auction.context, 0).await;
+    let init = instruction::init_auction(
+        &auction.program_id,
+        &auction.escrow_acc.pubkey(),
+        &auction.bidder_game_acc.pubkey(),
+        &bidder.pubkey(),
+        data,
+    );
+    let err = send(&mut auction.context, &[init], &[&bidder])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AssetNotOwned as u32));
+}
+
+#[tokio::test]
+async fn listed_asset_cannot_be_listed_twice() {
+    let mut auction = setup_auction(0).await;
+    let seller = auction.seller.insecure_clone();
+    let program_id = auction.program_id;
+
+    let escrow_acc = create_program_acc(&mut auction.context, &program_id, Escrow::LEN, 0).await;
+
+    let data = auction_data(&mut auction.context, 0).await;
+    let init = instruction::init_auction(
+        &program_id,
+        &escrow_acc.pubkey(),
+        &auction.seller_game_acc.pubkey(),
+        &seller.pubkey(),
+        data,
+    );
+    let err = send(&mut auction.context, &[init], &[&seller])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AssetNotOwned as u32));
+}
+
+#[tokio::test]
+async fn bids_below_the_reserve_or_increment_fail() {
+    let mut auction = setup_auction(0).await;
+    let bidder = auction.bidder.insecure_clone();
+    let other_bidder = auction.other_bidder.insecure_clone();
+    let bidder_game_acc = auction.bidder_game_acc.pubkey();
+    let other_bidder_game_acc = auction.other_bidder_game_acc.pubkey();
+
+    let err = place_bid(
+        &mut auction,
+        &bidder,
+        &bidder_game_acc,
+        None,
+        RESERVE_PRICE - 1,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(custom_error(err), Some(EscrowError::BidTooLow as u32));
+
+    place_bid(&mut auction, &bidder, &bidder_game_acc, None, RESERVE_PRICE)
+        .await
+        .unwrap();
+
+    let err = place_bid(
+        &mut auction,
+        &other_bidder,
+        &other_bidder_game_acc,
+        Some(&bidder.pubkey()),
+        RESERVE_PRICE + MIN_INCREMENT - 1,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(custom_error(err), Some(EscrowError::BidTooLow as u32));
+}
+
+// with no min_increment a bid equal to the highest would still replace its bidder
+#[tokio::test]
+async fn bid_equal_to_the_highest_fails() {
+    let mut auction = setup().await;
+    let seller = auction.seller.insecure_clone();
+    let bidder = auction.bidder.insecure_clone();
+    let other_bidder = auction.other_bidder.insecure_clone();
+    let bidder_game_acc = auction.bidder_game_acc.pubkey();
+    let other_bidder_game_acc = auction.other_bidder_game_acc.pubkey();
+
+    let data = InitAuctionData {
+        min_increment: 0,
+        ..auction_data(&mut auction.context, 0).await
+    };
+    let init = instruction::init_auction(
+        &auction.program_id,
+        &auction.escrow_acc.pubkey(),
+        &auction.seller_game_acc.pubkey(),
+        &seller.pubkey(),
+        data,
+    );
+    send(&mut auction.context, &[init], &[&seller])
+        .await
+        .unwrap();
+
+    place_bid(&mut auction, &bidder, &bidder_game_acc, None, RESERVE_PRICE)
+        .await
+        .unwrap();
+
+    let err = place_bid(
+        &mut auction,
+        &other_bidder,
+        &other_bidder_game_acc,
+        Some(&bidder.pubkey()),
+        RESERVE_PRICE,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(custom_error(err), Some(EscrowError::BidTooLow as u32));
+
+    place_bid(
+        &mut auction,
+        &other_bidder,
+        &other_bidder_game_acc,
+        Some(&bidder.pubkey()),
+        RESERVE_PRICE + 1,
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn seller_cannot_bid_on_own_auction() {
+    let mut auction = setup_auction(0).await;
+    let seller = auction.seller.insecure_clone();
+    let seller_game_acc = auction.seller_game_acc.pubkey();
+
+    let err = place_bid(&mut auction, &seller, &seller_game_acc, None, RESERVE_PRICE)
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::OwnEscrow as u32));
+}
+
+#[tokio::test]
+async fn outbid_bidder_is_refunded() {
+    let mut auction = setup_auction(0).await;
+    let bidder = auction.bidder.insecure_clone();
+    let other_bidder = auction.other_bidder.insecure_clone();
+    let bidder_game_acc = auction.bidder_game_acc.pubkey();
+    let other_bidder_game_acc = auction.other_bidder_game_acc.pubkey();
+
+    let bidder_before = balance(&mut auction.context, &bidder.pubkey()).await;
+
+    place_bid(&mut auction, &bidder, &bidder_game_acc, None, RESERVE_PRICE)
+        .await
+        .unwrap();
+    place_bid(
+        &mut auction,
+        &other_bidder,
+        &other_bidder_game_acc,
+        Some(&bidder.pubkey()),
+        RESERVE_PRICE + MIN_INCREMENT,
+    )
+    .await
+    .unwrap();
+
+    let bidder_after = balance(&mut auction.context, &bidder.pubkey()).await;
+    assert_eq!(bidder_after, bidder_before);
+
+    let escrow = escrow_info(&mut auction.context, &auction.escrow_acc.pubkey()).await;
+    assert_eq!(escrow.taker_main_acc, other_bidder.pubkey());
+    assert_eq!(escrow.amount, RESERVE_PRICE + MIN_INCREMENT);
+}
+
+#[tokio::test]
+async fn settle_sells_the_held_asset_to_the_winner() {
+    let mut auction = setup_auction(0).await;
+    let bidder = auction.bidder.insecure_clone();
+    let bidder_game_acc = auction.bidder_game_acc.pubkey();
+
+    place_bid(&mut auction, &bidder, &bidder_game_acc, None, RESERVE_PRICE)
+        .await
+        .unwrap();
+
+    let settle = settle_ix(&auction, true);
+    let err = send(&mut auction.context, &[settle], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(custom_error(err), Some(EscrowError::AuctionNotEnded as u32));
+
+    warp_minutes(&mut auction.context, AUCTION_MINUTES + 1).await;
+
+    let seller_before = balance(&mut auction.context, &auction.seller.pubkey()).await;
+
+    let settle = settle_ix(&auction, true);
+    send(&mut auction.context, &[settle], &[]).await.unwrap();
+
+    let seller_info = game_acc_info(&mut auction.context, &auction.seller_game_acc.pubkey()).await;
+    let winner_info = game_acc_info(&mut auction.context, &bidder_game_acc).await;
+    assert!(!seller_info.lent.contains(&ASSET_ID));
+    assert!(!seller_info.owned.contains(&ASSET_ID));
+    assert!(winner_info.owned.contains(&ASSET_ID));
+
+    let seller_after = balance(&mut auction.context, &auction.seller.pubkey()).await;
+    assert!(seller_after > seller_before + RESERVE_PRICE);
+    assert!(is_closed(&mut auction.context, &auction.escrow_acc.pubkey()).await);
+}
+
+#[tokio::test]
+async fn settle_rents_the_held_asset_to_the_winner() {
+    let mut auction = setup_auction(60).await;
+    let bidder = auction.bidder.insecure_clone();
+    let bidder_game_acc = auction.bidder_game_acc.pubkey();
+
+    place_bid(&mut auction, &bidder, &bidder_game_acc, None, RESERVE_PRICE)
+        .await
+        .unwrap();
+
+    warp_minutes(&mut auction.context, AUCTION_MINUTES + 1).await;
+
+    let settle = settle_ix(&auction, true);
+    send(&mut auction.context, &[settle], &[]).await.unwrap();
+
+    let seller_info = game_acc_info(&mut auction.context, &auction.seller_game_acc.pubkey()).await;
+    let winner_info = game_acc_info(&mut auction.context, &bidder_game_acc).await;
+    assert!(seller_info.lent.contains(&ASSET_ID));
+    assert!(winner_info.rented.contains(&ASSET_ID));
+
+    let escrow = escrow_info(&mut auction.context, &auction.escrow_acc.pubkey()).await;
+    assert!(escrow.is_taken);
+}
+
+#[tokio::test]
+async fn settle_without_bids_gives_the_asset_back() {
+    let mut auction = setup_auction(0).await;
+
+    warp_minutes(&mut auction.context, AUCTION_MINUTES + 1).await;
+
+    let settle = settle_ix(&auction, false);
+    send(&mut auction.context, &[settle], &[]).await.unwrap();
+
+    let seller_info = game_acc_info(&mut auction.context, &auction.seller_game_acc.pubkey()).await;
+    assert!(seller_info.owned.contains(&ASSET_ID));
+    assert!(!seller_info.lent.contains(&ASSET_ID));
+    assert!(is_closed(&mut auction.context, &auction.escrow_acc.pubkey()).await);
+}
+
+#[tokio::test]
+async fn cancel_auction_gives_the_asset_back() {
+    let mut auction = setup_auction(0).await;
+    let seller = auction.seller.insecure_clone();
+    let program_id = auction.program_id;
+
+    create_reputation(&mut auction.context, &program_id, &seller.pubkey()).await;
+
+    let cancel = instruction::cancel_escrow(
+        &auction.program_id,
+        &auction.escrow_acc.pubkey(),
+        &seller.pubkey(),
+        true,
+        Some(&auction.seller_game_acc.pubkey()),
+    );
+    send(&mut auction.context, &[cancel], &[&seller])
+        .await
+        .unwrap();
+
+    let seller_info = game_acc_info(&mut auction.context, &auction.seller_game_acc.pubkey()).await;
+    assert!(seller_info.owned.contains(&ASSET_ID));
+    assert!(!seller_info.lent.contains(&ASSET_ID));
+    assert!(is_closed(&mut auction.context, &auction.escrow_acc.pubkey()).await);
+}
+
+#[tokio::test]
+async fn auction_with_bids_cannot_be_cancelled() {
+    let mut auction = setup_auction(0).await;
+    let seller = auction.seller.insecure_clone();
+    let bidder = auction.bidder.insecure_clone();
+    let bidder_game_acc = auction.bidder_game_acc.pubkey();
+
+    place_bid(&mut auction, &bidder, &bidder_game_acc, None, RESERVE_PRICE)
+        .await
+        .unwrap();
+
+    let cancel = instruction::cancel_escrow(
+        &auction.program_id,
+        &auction.escrow_acc.pubkey(),
+        &seller.pubkey(),
+        true,
+        Some(&auction.seller_game_acc.pubkey()),
+    );
+    let err = send(&mut auction.context, &[cancel], &[&seller])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        custom_error(err),
+        Some(EscrowError::EscrowAlreadyTaken as u32)
+    );
+}
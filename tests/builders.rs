@@ -0,0 +1,103 @@
+mod common;
+
+use common::*;
+use game_nest::{
+    instruction::{self, TakeEscData},
+    state::{Escrow, GameAcc},
+};
+use solana_program::{instruction::AccountMeta, program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::signature::Signer;
+
+const ASSET_ID: u64 = 29;
+
+// every builder's instruction is sent as built, so a wrong signer or writable flag fails here
+#[tokio::test]
+async fn builders_run_through_the_processor_as_built() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+
+    let renter = funded_wallet(&mut program_test);
+    let lender = funded_wallet(&mut program_test);
+
+    let mut context = program_test.start_with_context().await;
+
+    let renter_game_acc = create_program_acc(&mut context, &program_id, GameAcc::LEN, 0).await;
+    let lender_game_acc = create_program_acc(&mut context, &program_id, GameAcc::LEN, 0).await;
+
+    let init_game_accs = [
+        instruction::init_game_acc(&program_id, &renter_game_acc.pubkey(), &renter.pubkey()),
+        instruction::init_game_acc(&program_id, &lender_game_acc.pubkey(), &lender.pubkey()),
+    ];
+    send(&mut context, &init_game_accs, &[]).await.unwrap();
+
+    let add = instruction::add_asset(&program_id, &lender_game_acc.pubkey(), ASSET_ID);
+    let init_history = instruction::init_history(&program_id, &context.payer.pubkey(), ASSET_ID);
+    send(&mut context, &[add, init_history], &[]).await.unwrap();
+
+    let lender_info = game_acc_info(&mut context, &lender_game_acc.pubkey()).await;
+    assert_eq!(lender_info.userspace_owner, lender.pubkey());
+    assert!(lender_info.owned.contains(&ASSET_ID));
+
+    let escrow_acc = create_program_acc(&mut context, &program_id, Escrow::LEN, RENT_AMOUNT).await;
+
+    let init = instruction::init_escrow(
+        &program_id,
+        &escrow_acc.pubkey(),
+        &renter_game_acc.pubkey(),
+        &renter.pubkey(),
+        None,
+        rent_request(ASSET_ID),
+    );
+    send(&mut context, &[init], &[]).await.unwrap();
+
+    let take = instruction::take_escrow(
+        &program_id,
+        &lender.pubkey(),
+        &escrow_acc.pubkey(),
+        &renter_game_acc.pubkey(),
+        &lender_game_acc.pubkey(),
+        None,
+        TakeEscData::default(),
+    );
+    let take = instruction::with_history(take, ASSET_ID);
+    send(&mut context, &[take], &[&lender]).await.unwrap();
+
+    let escrow = escrow_info(&mut context, &escrow_acc.pubkey()).await;
+    assert!(escrow.is_taken);
+
+    warp_minutes(&mut context, RENT_TIME as i64 + 1).await;
+
+    let revert = instruction::revert(
+        &program_id,
+        &renter.pubkey(),
+        &escrow_acc.pubkey(),
+        &renter_game_acc.pubkey(),
+        &lender_game_acc.pubkey(),
+        &lender.pubkey(),
+        &context.payer.pubkey(),
+    );
+    let revert = instruction::with_history(revert, ASSET_ID);
+    send(&mut context, &[revert], &[]).await.unwrap();
+
+    let lender_info = game_acc_info(&mut context, &lender_game_acc.pubkey()).await;
+    assert!(lender_info.owned.contains(&ASSET_ID));
+    assert!(is_closed(&mut context, &escrow_acc.pubkey()).await);
+}
+
+// the taker is paid the rent out of the Escrow A/c, a readonly taker can't be credited
+#[test]
+fn take_escrow_marks_the_taker_a_writable_signer() {
+    let taker_main_acc = Pubkey::new_unique();
+
+    let take = instruction::take_escrow(
+        &Pubkey::new_unique(),
+        &taker_main_acc,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        None,
+        TakeEscData::default(),
+    );
+
+    assert_eq!(take.accounts[0], AccountMeta::new(taker_main_acc, true));
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use common::*;
+use game_nest::{
+    error::EscrowError,
+    instruction::{self, InitEscData, OfferData, TakeEscData},
+    state::{Escrow, Offer},
+};
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::signature::Signer;
+
+const ASSET_ID_MIN: u64 = 100;
+const ASSET_ID_MAX: u64 = 199;
+
+// a renter bidding on any asset from ASSET_ID_MIN to ASSET_ID_MAX,
+// the lender holds one asset in the range and one above it
+async fn setup() -> Rental {
+    Rental::setup(
+        &[ASSET_ID_MIN + 5, ASSET_ID_MAX + 1],
+        InitEscData {
+            asset_id_max: ASSET_ID_MAX,
+            ..rent_request(ASSET_ID_MIN)
+        },
+        None,
+    )
+    .await
+}
+
+fn take_ix(bid: &Rental, asset_id: u64) -> solana_program::instruction::Instruction {
+    bid.take_ix(TakeEscData {
+        asset_id,
+        ..TakeEscData::default()
+    })
+}
+
+#[tokio::test]
+async fn any_asset_in_the_range_takes_the_bid() {
+    let mut bid = setup().await;
+    let lender = bid.lender.insecure_clone();
+    let asset_id = ASSET_ID_MIN + 5;
+
+    let take = take_ix(&bid, asset_id);
+    send(&mut bid.context, &[take], &[&lender]).await.unwrap();
+
+    let escrow = escrow_info(&mut bid.context, &bid.escrow_acc.pubkey()).await;
+    assert_eq!(escrow.asset_id, asset_id);
+
+    let renter_info = game_acc_info(&mut bid.context, &bid.renter_game_acc.pubkey()).await;
+    assert!(renter_info.rented.contains(&asset_id));
+
+    // the chosen asset is the one that comes back
+    warp_minutes(&mut bid.context, RENT_TIME as i64 + 1).await;
+
+    bid.asset_id = asset_id;
+    let revert = bid.revert_ix();
+    send(&mut bid.context, &[revert], &[]).await.unwrap();
+
+    let lender_info = game_acc_info(&mut bid.context, &bid.lender_game_acc.pubkey()).await;
+    assert!(lender_info.owned.contains(&asset_id));
+}
+
+#[tokio::test]
+async fn asset_outside_the_range_fails() {
+    let mut bid = setup().await;
+    let lender = bid.lender.insecure_clone();
+
+    let take = take_ix(&bid, ASSET_ID_MAX + 1);
+    let err = send(&mut bid.context, &[take], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        custom_error(err),
+        Some(EscrowError::AssetNotAccepted as u32)
+    );
+}
+
+#[tokio::test]
+async fn asset_in_the_range_the_taker_doesnt_hold_fails() {
+    let mut bid = setup().await;
+    let lender = bid.lender.insecure_clone();
+
+    let take = take_ix(&bid, ASSET_ID_MIN + 6);
+    let err = send(&mut bid.context, &[take], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AssetNotOwned as u32));
+}
+
+#[tokio::test]
+async fn bid_starting_at_asset_0_or_with_the_range_reversed_fails() {
+    let mut bid = Rental::start(&[ASSET_ID_MIN + 5], None).await;
+    let program_id = bid.program_id;
+
+    for (asset_id, asset_id_max) in [(0, ASSET_ID_MAX), (ASSET_ID_MAX, ASSET_ID_MIN)] {
+        let escrow_acc =
+            create_program_acc(&mut bid.context, &program_id, Escrow::LEN, RENT_AMOUNT).await;
+
+        let init = instruction::init_escrow(
+            &program_id,
+            &escrow_acc.pubkey(),
+            &bid.renter_game_acc.pubkey(),
+            &bid.renter.pubkey(),
+            None,
+            InitEscData {
+                asset_id_max,
+                ..rent_request(asset_id)
+            },
+        );
+        let err = send(&mut bid.context, &[init], &[]).await.unwrap_err();
+
+        assert_eq!(
+            custom_error(err),
+            Some(EscrowError::InvalidInstruction as u32)
+        );
+    }
+}
+
+// a bid on 0 to ASSET_ID_MAX made before asset_id 0 was rejected,
+// taking it with asset_id 0 would match an empty slot of the taker's
+#[tokio::test]
+async fn take_of_a_bid_on_asset_0_fails() {
+    let mut bid = setup().await;
+    let program_id = bid.program_id;
+    let lender = bid.lender.insecure_clone();
+    let escrow_key = bid.escrow_acc.pubkey();
+
+    edit_escrow(&mut bid.context, &escrow_key, |escrow| escrow.asset_id = 0).await;
+
+    let take = take_ix(&bid, 0);
+    let err = send(&mut bid.context, &[take], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AssetNotOwned as u32));
+
+    let offer_acc = create_program_acc(&mut bid.context, &program_id, Offer::LEN, 0).await;
+    let offer = instruction::make_offer(
+        &program_id,
+        &offer_acc.pubkey(),
+        &escrow_key,
+        &lender.pubkey(),
+        &bid.lender_game_acc.pubkey(),
+        OfferData {
+            amount: RENT_AMOUNT,
+            time: RENT_TIME,
+            asset_id: 0,
+            arbiter: Pubkey::default(),
+            oracle: Pubkey::default(),
+        },
+    );
+    let err = send(&mut bid.context, &[offer], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AssetNotOwned as u32));
+}
@@ -0,0 +1,410 @@
+// helpers shared by the program-test suites, each suite uses only some of them
+#![allow(dead_code)]
+
+use game_nest::{
+    instruction::{self, InitEscData, TakeEscData},
+    processor::Processor,
+    state::{Escrow, GameAcc, Market, ALLOWED_TAKERS_LEN},
+};
+use solana_program::{
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction,
+};
+use solana_program_test::{processor, ProgramTest, ProgramTestBanksClientExt, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+pub const RENT_AMOUNT: u64 = 1_000_000;
+pub const RENT_TIME: u64 = 60; // in minutes
+
+pub fn program_test(program_id: Pubkey) -> ProgramTest {
+    ProgramTest::new(
+        "game_nest",
+        program_id,
+        processor!(Processor::process_instruction),
+    )
+}
+
+pub fn funded_wallet(program_test: &mut ProgramTest) -> Keypair {
+    let wallet = Keypair::new();
+
+    program_test.add_account(
+        wallet.pubkey(),
+        Account::new(10_000_000_000, 0, &solana_program::system_program::id()),
+    );
+
+    wallet
+}
+
+pub async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let blockhash = context
+        .banks_client
+        .get_new_latest_blockhash(&context.last_blockhash)
+        .await
+        .unwrap();
+    context.last_blockhash = blockhash;
+
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+pub fn custom_error(err: TransactionError) -> Option<u32> {
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(code),
+        _ => None,
+    }
+}
+
+// creates a program owned account of the given size holding rent-exempt lamports plus extra
+pub async fn create_program_acc(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    space: usize,
+    extra_lamports: u64,
+) -> Keypair {
+    let acc = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+
+    let payer = context.payer.insecure_clone();
+
+    send(
+        context,
+        &[system_instruction::create_account(
+            &payer.pubkey(),
+            &acc.pubkey(),
+            rent.minimum_balance(space) + extra_lamports,
+            space as u64,
+            program_id,
+        )],
+        &[&acc],
+    )
+    .await
+    .unwrap();
+
+    acc
+}
+
+pub async fn create_game_acc(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    owner: &Keypair,
+) -> Keypair {
+    let game_acc = create_program_acc(context, program_id, GameAcc::LEN, 0).await;
+
+    send(
+        context,
+        &[instruction::init_game_acc(
+            program_id,
+            &game_acc.pubkey(),
+            &owner.pubkey(),
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    game_acc
+}
+
+pub async fn game_acc_info(context: &mut ProgramTestContext, game_acc: &Pubkey) -> GameAcc {
+    let account = context
+        .banks_client
+        .get_account(*game_acc)
+        .await
+        .unwrap()
+        .unwrap();
+
+    GameAcc::unpack(&account.data).unwrap()
+}
+
+pub async fn escrow_info(context: &mut ProgramTestContext, escrow_acc: &Pubkey) -> Escrow {
+    let account = context
+        .banks_client
+        .get_account(*escrow_acc)
+        .await
+        .unwrap()
+        .unwrap();
+
+    Escrow::unpack(&account.data).unwrap()
+}
+
+// rewrites an Escrow A/c in place, to stand in for escrows made before a check was added
+pub async fn edit_escrow(
+    context: &mut ProgramTestContext,
+    escrow_acc: &Pubkey,
+    edit: impl FnOnce(&mut Escrow),
+) {
+    let mut account = context
+        .banks_client
+        .get_account(*escrow_acc)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let mut escrow = Escrow::unpack(&account.data).unwrap();
+    edit(&mut escrow);
+    escrow.pack_into_slice(&mut account.data);
+
+    context.set_account(escrow_acc, &account.into());
+}
+
+pub async fn balance(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    context.banks_client.get_balance(*address).await.unwrap()
+}
+
+pub async fn is_closed(context: &mut ProgramTestContext, address: &Pubkey) -> bool {
+    context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .is_none()
+}
+
+pub async fn add_asset(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    game_acc: &Pubkey,
+    asset_id: u64,
+) {
+    send(
+        context,
+        &[instruction::add_asset(program_id, game_acc, asset_id)],
+        &[],
+    )
+    .await
+    .unwrap();
+}
+
+// the asset's History A/c, every take, settle or revert of the asset records an entry in it
+pub async fn create_history(context: &mut ProgramTestContext, program_id: &Pubkey, asset_id: u64) {
+    let payer = context.payer.pubkey();
+
+    send(
+        context,
+        &[instruction::init_history(program_id, &payer, asset_id)],
+        &[],
+    )
+    .await
+    .unwrap();
+}
+
+pub async fn create_reputation(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+) {
+    let payer = context.payer.pubkey();
+
+    send(
+        context,
+        &[instruction::init_reputation(program_id, wallet, &payer)],
+        &[],
+    )
+    .await
+    .unwrap();
+}
+
+pub async fn unix_timestamp(context: &mut ProgramTestContext) -> i64 {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp
+}
+
+pub async fn warp_minutes(context: &mut ProgramTestContext, minutes: i64) {
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += minutes * 60;
+    context.set_sysvar(&clock);
+}
+
+// a public rent request for asset_id paying RENT_AMOUNT for RENT_TIME that never expires,
+// tests override only the fields they need
+pub fn rent_request(asset_id: u64) -> InitEscData {
+    InitEscData {
+        amount: RENT_AMOUNT,
+        time: RENT_TIME,
+        asset_id,
+        allowed_takers: [Pubkey::default(); ALLOWED_TAKERS_LEN],
+        expires_at: 0,
+        asset_id_max: 0,
+        min_reputation: 0,
+    }
+}
+
+// the arbiter and oracle of the Market A/c a rent request is listed in
+#[derive(Default)]
+pub struct MarketRoles {
+    pub arbiter: Option<Pubkey>,
+    pub oracle: Option<Pubkey>,
+}
+
+pub struct Rental {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+    pub renter: Keypair,
+    pub lender: Keypair,
+    pub renter_game_acc: Keypair,
+    pub lender_game_acc: Keypair,
+    pub escrow_acc: Keypair,
+    pub market_acc: Option<Pubkey>,
+    pub asset_id: u64,
+}
+
+impl Rental {
+    // a renter's open rent request and a lender holding each of held, every held asset has a
+    // History A/c, the request is listed in a new Market A/c when market is given
+    pub async fn setup(held: &[u64], request: InitEscData, market: Option<MarketRoles>) -> Rental {
+        let mut rental = Rental::start(held, market).await;
+        rental.init_escrow(request).await;
+
+        rental
+    }
+
+    // the same as setup without the rent request, for tests that need the clock to write it
+    pub async fn start(held: &[u64], market: Option<MarketRoles>) -> Rental {
+        let program_id = Pubkey::new_unique();
+        let mut program_test = program_test(program_id);
+
+        let renter = funded_wallet(&mut program_test);
+        let lender = funded_wallet(&mut program_test);
+
+        let mut context = program_test.start_with_context().await;
+
+        let renter_game_acc = create_game_acc(&mut context, &program_id, &renter).await;
+        let lender_game_acc = create_game_acc(&mut context, &program_id, &lender).await;
+
+        for &asset_id in held {
+            add_asset(
+                &mut context,
+                &program_id,
+                &lender_game_acc.pubkey(),
+                asset_id,
+            )
+            .await;
+            create_history(&mut context, &program_id, asset_id).await;
+        }
+
+        let market_acc = match market {
+            Some(roles) => {
+                let market_acc =
+                    create_program_acc(&mut context, &program_id, Market::LEN, 0).await;
+                let authority = Keypair::new();
+
+                send(
+                    &mut context,
+                    &[instruction::init_market(
+                        &program_id,
+                        &market_acc.pubkey(),
+                        &authority.pubkey(),
+                        roles.arbiter.as_ref(),
+                        roles.oracle.as_ref(),
+                    )],
+                    &[&authority],
+                )
+                .await
+                .unwrap();
+
+                Some(market_acc.pubkey())
+            }
+            None => None,
+        };
+
+        Rental {
+            context,
+            program_id,
+            renter,
+            lender,
+            renter_game_acc,
+            lender_game_acc,
+            escrow_acc: Keypair::new(),
+            market_acc,
+            asset_id: 0,
+        }
+    }
+
+    // the renter's rent request, funded with its amount
+    pub async fn init_escrow(&mut self, request: InitEscData) {
+        let program_id = self.program_id;
+        self.escrow_acc =
+            create_program_acc(&mut self.context, &program_id, Escrow::LEN, request.amount).await;
+        self.asset_id = request.asset_id;
+
+        let init = instruction::init_escrow(
+            &program_id,
+            &self.escrow_acc.pubkey(),
+            &self.renter_game_acc.pubkey(),
+            &self.renter.pubkey(),
+            self.market_acc.as_ref(),
+            request,
+        );
+        send(&mut self.context, &[init], &[]).await.unwrap();
+    }
+
+    // the lender taking the rent request, with the History A/c of the asset it lends
+    pub fn take_ix(&self, data: TakeEscData) -> Instruction {
+        let asset_id = if data.asset_id != 0 {
+            data.asset_id
+        } else {
+            self.asset_id
+        };
+
+        let take = instruction::take_escrow(
+            &self.program_id,
+            &self.lender.pubkey(),
+            &self.escrow_acc.pubkey(),
+            &self.renter_game_acc.pubkey(),
+            &self.lender_game_acc.pubkey(),
+            self.market_acc.as_ref(),
+            data,
+        );
+
+        instruction::with_history(take, asset_id)
+    }
+
+    // the rental returned by anyone once it's due, with the payer as the keeper
+    pub fn revert_ix(&self) -> Instruction {
+        let revert = instruction::revert(
+            &self.program_id,
+            &self.renter.pubkey(),
+            &self.escrow_acc.pubkey(),
+            &self.renter_game_acc.pubkey(),
+            &self.lender_game_acc.pubkey(),
+            &self.lender.pubkey(),
+            &self.context.payer.pubkey(),
+        );
+
+        instruction::with_history(revert, self.asset_id)
+    }
+
+    pub fn cancel_ix(&self, initialiser_signs: bool) -> Instruction {
+        instruction::cancel_escrow(
+            &self.program_id,
+            &self.escrow_acc.pubkey(),
+            &self.renter.pubkey(),
+            initialiser_signs,
+            self.market_acc.as_ref(),
+        )
+    }
+}
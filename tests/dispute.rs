@@ -0,0 +1,236 @@
+mod common;
+
+use common::*;
+use game_nest::{
+    error::EscrowError,
+    instruction::{self, OfferData, ResolveDisputeData, TakeEscData},
+    state::{Offer, Reputation, DISPUTE_FAULT_LENDER, DISPUTE_FAULT_NONE, DISPUTE_TIMEOUT},
+};
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::signature::{Keypair, Signer};
+
+const ASSET_ID: u64 = 41;
+
+// a renter's rent request for ASSET_ID, listed in a market with the returned arbiter,
+// and a lender holding the asset
+async fn setup() -> (Rental, Keypair) {
+    let arbiter = Keypair::new();
+    let market = MarketRoles {
+        arbiter: Some(arbiter.pubkey()),
+        ..MarketRoles::default()
+    };
+
+    let rental = Rental::setup(&[ASSET_ID], rent_request(ASSET_ID), Some(market)).await;
+
+    (rental, arbiter)
+}
+
+fn take_ix(rental: &Rental, arbiter: Pubkey) -> solana_program::instruction::Instruction {
+    rental.take_ix(TakeEscData {
+        arbiter,
+        ..TakeEscData::default()
+    })
+}
+
+fn resolve_ix(
+    rental: &Rental,
+    arbiter: &Pubkey,
+    data: ResolveDisputeData,
+) -> solana_program::instruction::Instruction {
+    let resolve = instruction::resolve_dispute(
+        &rental.program_id,
+        arbiter,
+        &rental.escrow_acc.pubkey(),
+        &rental.renter.pubkey(),
+        &rental.lender.pubkey(),
+        &rental.renter_game_acc.pubkey(),
+        &rental.lender_game_acc.pubkey(),
+        data,
+    );
+
+    instruction::with_history(resolve, ASSET_ID)
+}
+
+// the lender has taken the rent request and the renter has disputed it
+async fn setup_disputed() -> (Rental, Keypair) {
+    let (mut rental, arbiter) = setup().await;
+    let lender = rental.lender.insecure_clone();
+    let renter = rental.renter.insecure_clone();
+
+    let take = take_ix(&rental, arbiter.pubkey());
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    let raise = instruction::raise_dispute(
+        &rental.program_id,
+        &rental.escrow_acc.pubkey(),
+        &renter.pubkey(),
+    );
+    send(&mut rental.context, &[raise], &[&renter])
+        .await
+        .unwrap();
+
+    (rental, arbiter)
+}
+
+#[tokio::test]
+async fn take_without_agreeing_to_the_arbiter_fails() {
+    let (mut rental, _) = setup().await;
+    let lender = rental.lender.insecure_clone();
+
+    for arbiter in [Pubkey::default(), Pubkey::new_unique()] {
+        let take = take_ix(&rental, arbiter);
+        let err = send(&mut rental.context, &[take], &[&lender])
+            .await
+            .unwrap_err();
+
+        assert_eq!(custom_error(err), Some(EscrowError::ArbiterMismatch as u32));
+    }
+}
+
+#[tokio::test]
+async fn offer_without_agreeing_to_the_arbiter_fails() {
+    let (mut rental, _) = setup().await;
+    let program_id = rental.program_id;
+    let lender = rental.lender.insecure_clone();
+
+    let offer_acc = create_program_acc(&mut rental.context, &program_id, Offer::LEN, 0).await;
+
+    let offer = instruction::make_offer(
+        &program_id,
+        &offer_acc.pubkey(),
+        &rental.escrow_acc.pubkey(),
+        &lender.pubkey(),
+        &rental.lender_game_acc.pubkey(),
+        OfferData {
+            amount: RENT_AMOUNT,
+            time: RENT_TIME,
+            asset_id: 0,
+            arbiter: Pubkey::default(),
+            oracle: Pubkey::default(),
+        },
+    );
+    let err = send(&mut rental.context, &[offer], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::ArbiterMismatch as u32));
+}
+
+#[tokio::test]
+async fn arbiter_resolves_the_dispute() {
+    let (mut rental, arbiter) = setup_disputed().await;
+    let program_id = rental.program_id;
+    let lender = rental.lender.pubkey();
+    let renter = rental.renter.pubkey();
+
+    create_reputation(&mut rental.context, &program_id, &lender).await;
+
+    let lender_before = balance(&mut rental.context, &lender).await;
+    let renter_before = balance(&mut rental.context, &renter).await;
+    let escrow_before = balance(&mut rental.context, &rental.escrow_acc.pubkey()).await;
+
+    let renter_refund = RENT_AMOUNT / 4;
+    let resolve = resolve_ix(
+        &rental,
+        &arbiter.pubkey(),
+        ResolveDisputeData {
+            renter_refund,
+            renter_keeps_asset: false,
+            at_fault: DISPUTE_FAULT_LENDER,
+        },
+    );
+    send(&mut rental.context, &[resolve], &[&arbiter])
+        .await
+        .unwrap();
+
+    let lender_share = RENT_AMOUNT - renter_refund;
+    assert_eq!(
+        balance(&mut rental.context, &lender).await,
+        lender_before + lender_share
+    );
+    assert_eq!(
+        balance(&mut rental.context, &renter).await,
+        renter_before + escrow_before - lender_share
+    );
+    assert!(is_closed(&mut rental.context, &rental.escrow_acc.pubkey()).await);
+
+    let lender_info = game_acc_info(&mut rental.context, &rental.lender_game_acc.pubkey()).await;
+    assert!(lender_info.owned.contains(&ASSET_ID));
+
+    let (reputation_acc, _) = Reputation::find_address(&program_id, &lender);
+    let account = rental
+        .context
+        .banks_client
+        .get_account(reputation_acc)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(Reputation::unpack(&account.data).unwrap().disputed, 1);
+}
+
+#[tokio::test]
+async fn dispute_resolved_by_another_signer_fails() {
+    let (mut rental, _) = setup_disputed().await;
+    let other = Keypair::new();
+
+    let resolve = resolve_ix(
+        &rental,
+        &other.pubkey(),
+        ResolveDisputeData {
+            renter_refund: 0,
+            renter_keeps_asset: false,
+            at_fault: DISPUTE_FAULT_NONE,
+        },
+    );
+    let err = send(&mut rental.context, &[resolve], &[&other])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::NotArbiter as u32));
+}
+
+#[tokio::test]
+async fn dispute_at_fault_without_the_reputation_fails() {
+    let (mut rental, arbiter) = setup_disputed().await;
+
+    let mut resolve = resolve_ix(
+        &rental,
+        &arbiter.pubkey(),
+        ResolveDisputeData {
+            renter_refund: 0,
+            renter_keeps_asset: false,
+            at_fault: DISPUTE_FAULT_LENDER,
+        },
+    );
+    // the lender's Reputation A/c before the History A/c
+    resolve.accounts.remove(6);
+    let err = send(&mut rental.context, &[resolve], &[&arbiter])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        custom_error(err),
+        Some(EscrowError::MissingReputation as u32)
+    );
+}
+
+#[tokio::test]
+async fn disputed_rental_is_reverted_only_after_the_timeout() {
+    let (mut rental, _) = setup_disputed().await;
+
+    let revert = rental.revert_ix();
+
+    warp_minutes(&mut rental.context, RENT_TIME as i64 + 1).await;
+
+    let err = send(&mut rental.context, std::slice::from_ref(&revert), &[])
+        .await
+        .unwrap_err();
+    assert_eq!(custom_error(err), Some(EscrowError::EscrowDisputed as u32));
+
+    warp_minutes(&mut rental.context, DISPUTE_TIMEOUT / 60).await;
+
+    send(&mut rental.context, &[revert], &[]).await.unwrap();
+    assert!(is_closed(&mut rental.context, &rental.escrow_acc.pubkey()).await);
+}
@@ -0,0 +1,306 @@
+mod common;
+
+use common::*;
+use game_nest::{
+    error::EscrowError,
+    instruction::{self, InitDutchAuctionData, TakeEscData},
+    state::Escrow,
+};
+use solana_program::{clock::Clock, program_pack::Pack, pubkey::Pubkey};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::{Keypair, Signer};
+
+const ASSET_ID: u64 = 13;
+const START_PRICE: u64 = 3_000_000;
+const END_PRICE: u64 = 1_000_000;
+
+struct DutchAuction {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    seller: Keypair,
+    taker: Keypair,
+    seller_game_acc: Keypair,
+    taker_game_acc: Keypair,
+    escrow_acc: Keypair,
+}
+
+// the price starts declining a day from now and reaches END_PRICE a day after that
+async fn dutch_data(context: &mut ProgramTestContext, time: u64) -> InitDutchAuctionData {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+
+    InitDutchAuctionData {
+        start_price: START_PRICE,
+        end_price: END_PRICE,
+        time,
+        asset_id: ASSET_ID,
+        start_time: clock.unix_timestamp + 24 * 60 * 60,
+        end_time: clock.unix_timestamp + 2 * 24 * 60 * 60,
+        min_reputation: 0,
+    }
+}
+
+// a seller holding ASSET_ID and a taker, the Escrow A/c is created but not initialised
+async fn setup() -> DutchAuction {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+
+    let seller = funded_wallet(&mut program_test);
+    let taker = funded_wallet(&mut program_test);
+
+    let mut context = program_test.start_with_context().await;
+
+    let seller_game_acc = create_game_acc(&mut context, &program_id, &seller).await;
+    let taker_game_acc = create_game_acc(&mut context, &program_id, &taker).await;
+
+    add_asset(
+        &mut context,
+        &program_id,
+        &seller_game_acc.pubkey(),
+        ASSET_ID,
+    )
+    .await;
+    create_history(&mut context, &program_id, ASSET_ID).await;
+
+    let escrow_acc = create_program_acc(&mut context, &program_id, Escrow::LEN, 0).await;
+
+    DutchAuction {
+        context,
+        program_id,
+        seller,
+        taker,
+        seller_game_acc,
+        taker_game_acc,
+        escrow_acc,
+    }
+}
+
+// time is 0 for a sale or the rental time in minutes
+async fn setup_dutch_auction(time: u64) -> DutchAuction {
+    let mut auction = setup().await;
+    let seller = auction.seller.insecure_clone();
+
+    let data = dutch_data(&mut auction.context, time).await;
+    let init = instruction::init_dutch_auction(
+        &auction.program_id,
+        &auction.escrow_acc.pubkey(),
+        &auction.seller_game_acc.pubkey(),
+        &seller.pubkey(),
+        data,
+    );
+    send(&mut auction.context, &[init], &[&seller])
+        .await
+        .unwrap();
+
+    auction
+}
+
+fn take_ix(
+    auction: &DutchAuction,
+    taker: &Keypair,
+    taker_game_acc: &Pubkey,
+    max_price: u64,
+) -> solana_program::instruction::Instruction {
+    let take = instruction::take_escrow(
+        &auction.program_id,
+        &taker.pubkey(),
+        &auction.escrow_acc.pubkey(),
+        &auction.seller_game_acc.pubkey(),
+        taker_game_acc,
+        Some(&auction.seller.pubkey()),
+        TakeEscData {
+            max_price,
+            ..TakeEscData::default()
+        },
+    );
+
+    instruction::with_history(take, ASSET_ID)
+}
+
+#[tokio::test]
+async fn init_dutch_auction_holds_the_asset() {
+    let mut auction = setup_dutch_auction(0).await;
+
+    let seller_info = game_acc_info(&mut auction.context, &auction.seller_game_acc.pubkey()).await;
+    assert!(!seller_info.owned.contains(&ASSET_ID));
+    assert!(seller_info.lent.contains(&ASSET_ID));
+}
+
+#[tokio::test]
+async fn init_dutch_auction_with_a_rising_price_fails() {
+    let mut auction = setup().await;
+    let seller = auction.seller.insecure_clone();
+
+    let data = InitDutchAuctionData {
+        start_price: END_PRICE,
+        end_price: START_PRICE,
+        ..dutch_data(&mut auction.context, 0).await
+    };
+    let init = instruction::init_dutch_auction(
+        &auction.program_id,
+        &auction.escrow_acc.pubkey(),
+        &auction.seller_game_acc.pubkey(),
+        &seller.pubkey(),
+        data,
+    );
+    let err = send(&mut auction.context, &[init], &[&seller])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        custom_error(err),
+        Some(EscrowError::InvalidInstruction as u32)
+    );
+}
+
+#[tokio::test]
+async fn take_pays_the_price_from_the_taker() {
+    let mut auction = setup_dutch_auction(0).await;
+    let taker = auction.taker.insecure_clone();
+    let taker_game_acc = auction.taker_game_acc.pubkey();
+
+    let taker_before = balance(&mut auction.context, &taker.pubkey()).await;
+    let seller_before = balance(&mut auction.context, &auction.seller.pubkey()).await;
+    let escrow_lamports = balance(&mut auction.context, &auction.escrow_acc.pubkey()).await;
+
+    // the most the taker would pay is above the price, only the price is taken
+    let take = take_ix(&auction, &taker, &taker_game_acc, START_PRICE * 2);
+    send(&mut auction.context, &[take], &[&taker])
+        .await
+        .unwrap();
+
+    let taker_after = balance(&mut auction.context, &taker.pubkey()).await;
+    let seller_after = balance(&mut auction.context, &auction.seller.pubkey()).await;
+    assert_eq!(taker_after, taker_before - START_PRICE);
+    assert_eq!(seller_after, seller_before + START_PRICE + escrow_lamports);
+
+    let seller_info = game_acc_info(&mut auction.context, &auction.seller_game_acc.pubkey()).await;
+    let taker_info = game_acc_info(&mut auction.context, &taker_game_acc).await;
+    assert!(!seller_info.lent.contains(&ASSET_ID));
+    assert!(taker_info.owned.contains(&ASSET_ID));
+    assert!(is_closed(&mut auction.context, &auction.escrow_acc.pubkey()).await);
+}
+
+#[tokio::test]
+async fn take_above_max_price_fails() {
+    let mut auction = setup_dutch_auction(0).await;
+    let taker = auction.taker.insecure_clone();
+    let taker_game_acc = auction.taker_game_acc.pubkey();
+
+    let take = take_ix(&auction, &taker, &taker_game_acc, START_PRICE - 1);
+    let err = send(&mut auction.context, &[take], &[&taker])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::PriceTooHigh as u32));
+}
+
+#[tokio::test]
+async fn take_after_the_decline_pays_the_end_price() {
+    let mut auction = setup_dutch_auction(0).await;
+    let taker = auction.taker.insecure_clone();
+    let taker_game_acc = auction.taker_game_acc.pubkey();
+
+    warp_minutes(&mut auction.context, 3 * 24 * 60).await;
+
+    let taker_before = balance(&mut auction.context, &taker.pubkey()).await;
+
+    let take = take_ix(&auction, &taker, &taker_game_acc, END_PRICE);
+    send(&mut auction.context, &[take], &[&taker])
+        .await
+        .unwrap();
+
+    let taker_after = balance(&mut auction.context, &taker.pubkey()).await;
+    assert_eq!(taker_after, taker_before - END_PRICE);
+}
+
+#[tokio::test]
+async fn take_rents_the_held_asset() {
+    let mut auction = setup_dutch_auction(60).await;
+    let taker = auction.taker.insecure_clone();
+    let taker_game_acc = auction.taker_game_acc.pubkey();
+
+    let take = take_ix(&auction, &taker, &taker_game_acc, START_PRICE);
+    send(&mut auction.context, &[take], &[&taker])
+        .await
+        .unwrap();
+
+    let seller_info = game_acc_info(&mut auction.context, &auction.seller_game_acc.pubkey()).await;
+    let taker_info = game_acc_info(&mut auction.context, &taker_game_acc).await;
+    assert!(seller_info.lent.contains(&ASSET_ID));
+    assert!(taker_info.rented.contains(&ASSET_ID));
+
+    let escrow = escrow_info(&mut auction.context, &auction.escrow_acc.pubkey()).await;
+    assert!(escrow.is_taken);
+    assert_eq!(escrow.amount, START_PRICE);
+}
+
+#[tokio::test]
+async fn seller_cannot_take_own_dutch_auction() {
+    let mut auction = setup_dutch_auction(0).await;
+    let seller = auction.seller.insecure_clone();
+    let seller_game_acc = auction.seller_game_acc.pubkey();
+
+    let take = take_ix(&auction, &seller, &seller_game_acc, START_PRICE);
+    let err = send(&mut auction.context, &[take], &[&seller])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::OwnEscrow as u32));
+}
+
+#[tokio::test]
+async fn cancel_dutch_auction_gives_the_asset_back() {
+    let mut auction = setup_dutch_auction(0).await;
+    let seller = auction.seller.insecure_clone();
+    let program_id = auction.program_id;
+
+    create_reputation(&mut auction.context, &program_id, &seller.pubkey()).await;
+
+    let cancel = instruction::cancel_escrow(
+        &auction.program_id,
+        &auction.escrow_acc.pubkey(),
+        &seller.pubkey(),
+        true,
+        Some(&auction.seller_game_acc.pubkey()),
+    );
+    send(&mut auction.context, &[cancel], &[&seller])
+        .await
+        .unwrap();
+
+    let seller_info = game_acc_info(&mut auction.context, &auction.seller_game_acc.pubkey()).await;
+    assert!(seller_info.owned.contains(&ASSET_ID));
+    assert!(is_closed(&mut auction.context, &auction.escrow_acc.pubkey()).await);
+}
+
+// the builder used to leave out the System Program when max_price was 0
+#[tokio::test]
+async fn auction_ending_at_price_0_is_taken_with_max_price_0() {
+    let mut auction = setup().await;
+    let seller = auction.seller.insecure_clone();
+    let taker = auction.taker.insecure_clone();
+
+    let data = InitDutchAuctionData {
+        end_price: 0,
+        ..dutch_data(&mut auction.context, 0).await
+    };
+    let init = instruction::init_dutch_auction(
+        &auction.program_id,
+        &auction.escrow_acc.pubkey(),
+        &auction.seller_game_acc.pubkey(),
+        &seller.pubkey(),
+        data,
+    );
+    send(&mut auction.context, &[init], &[&seller])
+        .await
+        .unwrap();
+
+    warp_minutes(&mut auction.context, 2 * 24 * 60 + 1).await;
+
+    let take = take_ix(&auction, &taker, &auction.taker_game_acc.pubkey(), 0);
+    send(&mut auction.context, &[take], &[&taker])
+        .await
+        .unwrap();
+
+    let taker_info = game_acc_info(&mut auction.context, &auction.taker_game_acc.pubkey()).await;
+    assert!(taker_info.owned.contains(&ASSET_ID));
+}
@@ -0,0 +1,69 @@
+use game_nest::error::EscrowError;
+use solana_program::{decode_error::DecodeError, program_error::ProgramError};
+
+// clients match on these codes, they must not change between versions
+const CODES: [(EscrowError, u32); 41] = [
+    (EscrowError::InvalidInstruction, 0),
+    (EscrowError::MissingPermission, 1),
+    (EscrowError::IncorrectAcc, 2),
+    (EscrowError::AmountOverflow, 3),
+    (EscrowError::EscrowAlreadyTaken, 4),
+    (EscrowError::AssetSpaceFull, 5),
+    (EscrowError::TakerNotAllowed, 6),
+    (EscrowError::EscrowExpired, 7),
+    (EscrowError::BidTooLow, 8),
+    (EscrowError::AuctionEnded, 9),
+    (EscrowError::AuctionNotEnded, 10),
+    (EscrowError::AccountLocked, 11),
+    (EscrowError::AssetLentOut, 12),
+    (EscrowError::AccountOutdated, 13),
+    (EscrowError::ReputationTooLow, 14),
+    (EscrowError::EscrowDisputed, 15),
+    (EscrowError::AccountClosed, 16),
+    (EscrowError::AssetRented, 17),
+    (EscrowError::AssetNotOwned, 18),
+    (EscrowError::RentalNotExpired, 19),
+    (EscrowError::NotEscrowAccount, 20),
+    (EscrowError::NotGameAccount, 21),
+    (EscrowError::OwnerMismatch, 22),
+    (EscrowError::AccountMismatch, 23),
+    (EscrowError::AssetNotAccepted, 24),
+    (EscrowError::OwnEscrow, 25),
+    (EscrowError::EscrowNotTaken, 26),
+    (EscrowError::WrongEscrowKind, 27),
+    (EscrowError::EscrowNotDisputable, 28),
+    (EscrowError::EscrowNotDisputed, 29),
+    (EscrowError::DeliveryNotPending, 30),
+    (EscrowError::NotEscrowParty, 31),
+    (EscrowError::IncorrectPda, 32),
+    (EscrowError::PriceTooHigh, 33),
+    (EscrowError::MissingHistory, 34),
+    (EscrowError::MissingReputation, 35),
+    (EscrowError::ArbiterMismatch, 36),
+    (EscrowError::OracleMismatch, 37),
+    (EscrowError::InvalidAttestation, 38),
+    (EscrowError::NotArbiter, 39),
+    (EscrowError::NotLockAuthority, 40),
+];
+
+#[test]
+fn error_codes_are_stable() {
+    for (error, code) in CODES {
+        assert_eq!(ProgramError::from(error), ProgramError::Custom(code));
+        assert_eq!(EscrowError::from_code(code), Some(error));
+    }
+
+    assert_eq!(EscrowError::from_code(CODES.len() as u32), None);
+}
+
+#[test]
+fn decodes_as_escrow_error() {
+    assert_eq!(
+        <EscrowError as DecodeError<EscrowError>>::type_of(),
+        "EscrowError"
+    );
+    assert_eq!(
+        EscrowError::RentalNotExpired.to_string(),
+        "Rental Not Expired"
+    );
+}
@@ -0,0 +1,572 @@
+mod common;
+
+use common::*;
+use game_nest::{
+    error::EscrowError,
+    instruction::{self, InitEscData, TakeEscData},
+    state::{Escrow, ALLOWED_TAKERS_LEN, KEEPER_BOUNTY},
+};
+use solana_program::{
+    instruction::{AccountMeta, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction,
+};
+use solana_sdk::{signature::Signer, transaction::TransactionError};
+
+const ASSET_ID: u64 = 7;
+
+// a lender holding ASSET_ID and a renter with an open rent request for it
+async fn setup_rental() -> Rental {
+    Rental::setup(&[ASSET_ID], rent_request(ASSET_ID), None).await
+}
+
+// the same rent request restricted to another wallet, and to the lender too if allow_lender
+async fn setup_allowed_takers(allow_lender: bool) -> Rental {
+    let mut rental = Rental::start(&[ASSET_ID], None).await;
+
+    let mut allowed_takers = [Pubkey::default(); ALLOWED_TAKERS_LEN];
+    allowed_takers[0] = Pubkey::new_unique();
+    if allow_lender {
+        allowed_takers[1] = rental.lender.pubkey();
+    }
+
+    rental
+        .init_escrow(InitEscData {
+            allowed_takers,
+            ..rent_request(ASSET_ID)
+        })
+        .await;
+
+    rental
+}
+
+#[tokio::test]
+async fn rent_take_and_revert() {
+    let mut rental = setup_rental().await;
+    let lender = rental.lender.insecure_clone();
+
+    let lender_before = rental
+        .context
+        .banks_client
+        .get_balance(lender.pubkey())
+        .await
+        .unwrap();
+
+    let take = rental.take_ix(TakeEscData::default());
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    let renter_info = game_acc_info(&mut rental.context, &rental.renter_game_acc.pubkey()).await;
+    let lender_info = game_acc_info(&mut rental.context, &rental.lender_game_acc.pubkey()).await;
+
+    assert!(renter_info.rented.contains(&ASSET_ID));
+    assert!(!lender_info.owned.contains(&ASSET_ID));
+    assert!(lender_info.lent.contains(&ASSET_ID));
+
+    let lender_after = rental
+        .context
+        .banks_client
+        .get_balance(lender.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(lender_after, lender_before + RENT_AMOUNT);
+
+    // the rental can't come back before its time is up
+    let revert = rental.revert_ix();
+    let err = send(&mut rental.context, &[revert], &[]).await.unwrap_err();
+    assert_eq!(
+        custom_error(err),
+        Some(EscrowError::RentalNotExpired as u32)
+    );
+
+    warp_minutes(&mut rental.context, RENT_TIME as i64 + 1).await;
+
+    let revert = rental.revert_ix();
+    send(&mut rental.context, &[revert], &[]).await.unwrap();
+
+    let renter_info = game_acc_info(&mut rental.context, &rental.renter_game_acc.pubkey()).await;
+    let lender_info = game_acc_info(&mut rental.context, &rental.lender_game_acc.pubkey()).await;
+
+    assert!(!renter_info.rented.contains(&ASSET_ID));
+    assert!(lender_info.owned.contains(&ASSET_ID));
+    assert!(!lender_info.lent.contains(&ASSET_ID));
+
+    let escrow = rental
+        .context
+        .banks_client
+        .get_account(rental.escrow_acc.pubkey())
+        .await
+        .unwrap();
+    assert!(escrow.is_none());
+}
+
+#[tokio::test]
+async fn refunded_escrow_cannot_be_reverted_again() {
+    let mut rental = setup_rental().await;
+    let lender = rental.lender.insecure_clone();
+    let payer = rental.context.payer.insecure_clone();
+
+    let take = rental.take_ix(TakeEscData::default());
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    warp_minutes(&mut rental.context, RENT_TIME as i64 + 1).await;
+
+    // refunding the closed escrow within the same transaction must not revive it
+    let rent = rental.context.banks_client.get_rent().await.unwrap();
+    let refund = system_instruction::transfer(
+        &payer.pubkey(),
+        &rental.escrow_acc.pubkey(),
+        rent.minimum_balance(Escrow::LEN),
+    );
+
+    let revert = rental.revert_ix();
+    let err = send(&mut rental.context, &[revert.clone(), refund, revert], &[])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AccountClosed as u32));
+}
+
+// the baseline revert moved the asset back without saving either game account
+#[tokio::test]
+async fn revert_writes_back_both_game_accs() {
+    let mut rental = setup_rental().await;
+    let lender = rental.lender.insecure_clone();
+
+    let take = rental.take_ix(TakeEscData::default());
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    warp_minutes(&mut rental.context, RENT_TIME as i64 + 1).await;
+
+    let revert = rental.revert_ix();
+    send(&mut rental.context, &[revert], &[]).await.unwrap();
+
+    let renter_info = game_acc_info(&mut rental.context, &rental.renter_game_acc.pubkey()).await;
+    let lender_info = game_acc_info(&mut rental.context, &rental.lender_game_acc.pubkey()).await;
+
+    assert_eq!(renter_info.rented, [0; 20]);
+    assert_eq!(lender_info.lent, [0; 20]);
+    assert_eq!(lender_info.owned[0], ASSET_ID);
+}
+
+#[tokio::test]
+async fn revert_pays_the_keeper_bounty() {
+    let mut rental = setup_rental().await;
+    let lender = rental.lender.insecure_clone();
+
+    let take = rental.take_ix(TakeEscData::default());
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    warp_minutes(&mut rental.context, RENT_TIME as i64 + 1).await;
+
+    let lender_before = rental
+        .context
+        .banks_client
+        .get_balance(lender.pubkey())
+        .await
+        .unwrap();
+
+    // the lender cranks it this time
+    let mut revert = rental.revert_ix();
+    revert.accounts[5] = AccountMeta::new(lender.pubkey(), true);
+    send(&mut rental.context, &[revert], &[&lender])
+        .await
+        .unwrap();
+
+    let lender_after = rental
+        .context
+        .banks_client
+        .get_balance(lender.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(lender_after, lender_before + KEEPER_BOUNTY);
+}
+
+// the History A/c is optional after the keeper, so a missing keeper can't be mistaken for it
+#[tokio::test]
+async fn revert_without_a_keeper_fails() {
+    let mut rental = setup_rental().await;
+    let lender = rental.lender.insecure_clone();
+
+    let take = rental.take_ix(TakeEscData::default());
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    warp_minutes(&mut rental.context, RENT_TIME as i64 + 1).await;
+
+    let mut revert = rental.revert_ix();
+    revert.accounts.truncate(5);
+    let err = send(&mut rental.context, &[revert], &[]).await.unwrap_err();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+}
+
+#[tokio::test]
+async fn take_twice_fails_with_escrow_already_taken() {
+    let mut rental = setup_rental().await;
+    let lender = rental.lender.insecure_clone();
+
+    let take = rental.take_ix(TakeEscData::default());
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    let take = rental.take_ix(TakeEscData::default());
+    let err = send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        custom_error(err),
+        Some(EscrowError::EscrowAlreadyTaken as u32)
+    );
+}
+
+#[tokio::test]
+async fn take_by_a_taker_not_allowed_fails() {
+    let mut rental = setup_allowed_takers(false).await;
+    let lender = rental.lender.insecure_clone();
+
+    let take = rental.take_ix(TakeEscData::default());
+    let err = send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::TakerNotAllowed as u32));
+}
+
+#[tokio::test]
+async fn take_by_an_allowed_taker_succeeds() {
+    let mut rental = setup_allowed_takers(true).await;
+    let lender = rental.lender.insecure_clone();
+
+    let take = rental.take_ix(TakeEscData::default());
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    let escrow = escrow_info(&mut rental.context, &rental.escrow_acc.pubkey()).await;
+    assert_eq!(escrow.taker_main_acc, lender.pubkey());
+}
+
+// no allowed takers set means anyone may take
+#[tokio::test]
+async fn take_of_a_public_escrow_succeeds() {
+    let mut rental = setup_rental().await;
+    let lender = rental.lender.insecure_clone();
+
+    let escrow = escrow_info(&mut rental.context, &rental.escrow_acc.pubkey()).await;
+    assert_eq!(
+        escrow.allowed_takers,
+        [Pubkey::default(); ALLOWED_TAKERS_LEN]
+    );
+
+    let take = rental.take_ix(TakeEscData::default());
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    let escrow = escrow_info(&mut rental.context, &rental.escrow_acc.pubkey()).await;
+    assert!(escrow.is_taken);
+}
+
+// renting to oneself would count a completed rental for nothing
+#[tokio::test]
+async fn take_of_own_rent_request_fails() {
+    let mut rental = setup_rental().await;
+    let program_id = rental.program_id;
+    let renter = rental.renter.insecure_clone();
+    let renter_game_acc = rental.renter_game_acc.pubkey();
+
+    add_asset(&mut rental.context, &program_id, &renter_game_acc, ASSET_ID).await;
+
+    let take = instruction::take_escrow(
+        &program_id,
+        &renter.pubkey(),
+        &rental.escrow_acc.pubkey(),
+        &renter_game_acc,
+        &renter_game_acc,
+        None,
+        TakeEscData::default(),
+    );
+    let take = instruction::with_history(take, ASSET_ID);
+    let err = send(&mut rental.context, &[take], &[&renter])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::OwnEscrow as u32));
+}
+
+#[tokio::test]
+async fn take_with_wrong_game_acc_fails_with_account_mismatch() {
+    let mut rental = setup_rental().await;
+    let lender = rental.lender.insecure_clone();
+
+    // the lender's game account given as the initialiser's
+    let take = instruction::take_escrow(
+        &rental.program_id,
+        &lender.pubkey(),
+        &rental.escrow_acc.pubkey(),
+        &rental.lender_game_acc.pubkey(),
+        &rental.lender_game_acc.pubkey(),
+        None,
+        TakeEscData::default(),
+    );
+    let err = send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AccountMismatch as u32));
+}
+
+#[tokio::test]
+async fn take_without_the_asset_fails() {
+    let mut rental = setup_rental().await;
+    let renter = rental.renter.insecure_clone();
+
+    // a second escrow for an asset the lender doesn't have
+    let program_id = rental.program_id;
+    let escrow_acc =
+        create_program_acc(&mut rental.context, &program_id, Escrow::LEN, RENT_AMOUNT).await;
+
+    send(
+        &mut rental.context,
+        &[instruction::init_escrow(
+            &program_id,
+            &escrow_acc.pubkey(),
+            &rental.renter_game_acc.pubkey(),
+            &renter.pubkey(),
+            None,
+            rent_request(ASSET_ID + 1),
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let lender = rental.lender.insecure_clone();
+    let take = instruction::take_escrow(
+        &program_id,
+        &lender.pubkey(),
+        &escrow_acc.pubkey(),
+        &rental.renter_game_acc.pubkey(),
+        &rental.lender_game_acc.pubkey(),
+        None,
+        TakeEscData::default(),
+    );
+    let err = send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AssetNotOwned as u32));
+}
+
+// each AddAsset takes the first empty slot only, the ones after it stay empty
+#[tokio::test]
+async fn add_asset_fills_a_single_empty_slot() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+    let owner = funded_wallet(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    let game_acc = create_game_acc(&mut context, &program_id, &owner).await;
+
+    add_asset(&mut context, &program_id, &game_acc.pubkey(), ASSET_ID).await;
+
+    let info = game_acc_info(&mut context, &game_acc.pubkey()).await;
+    assert_eq!(info.owned[0], ASSET_ID);
+    assert_eq!(info.owned[1..], [0; 19]);
+
+    add_asset(&mut context, &program_id, &game_acc.pubkey(), ASSET_ID + 1).await;
+
+    let info = game_acc_info(&mut context, &game_acc.pubkey()).await;
+    assert_eq!(info.owned[..2], [ASSET_ID, ASSET_ID + 1]);
+    assert_eq!(info.owned[2..], [0; 18]);
+}
+
+#[tokio::test]
+async fn add_asset_to_full_game_acc_fails_with_asset_space_full() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+    let owner = funded_wallet(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    let game_acc = create_game_acc(&mut context, &program_id, &owner).await;
+
+    let adds: Vec<_> = (1..=20)
+        .map(|asset_id| instruction::add_asset(&program_id, &game_acc.pubkey(), asset_id))
+        .collect();
+    send(&mut context, &adds, &[]).await.unwrap();
+
+    let info = game_acc_info(&mut context, &game_acc.pubkey()).await;
+    assert_eq!(info.owned, core::array::from_fn(|i| i as u64 + 1));
+
+    let err = send(
+        &mut context,
+        &[instruction::add_asset(&program_id, &game_acc.pubkey(), 21)],
+        &[],
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AssetSpaceFull as u32));
+}
+
+#[tokio::test]
+async fn init_escrow_without_the_rent_fails_with_insufficient_funds() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+    let renter = funded_wallet(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    let renter_game_acc = create_game_acc(&mut context, &program_id, &renter).await;
+
+    // only rent-exempt lamports, the rent amount is missing
+    let escrow_acc = create_program_acc(&mut context, &program_id, Escrow::LEN, 0).await;
+
+    let err = send(
+        &mut context,
+        &[instruction::init_escrow(
+            &program_id,
+            &escrow_acc.pubkey(),
+            &renter_game_acc.pubkey(),
+            &renter.pubkey(),
+            None,
+            rent_request(ASSET_ID),
+        )],
+        &[],
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::InsufficientFunds)
+    );
+}
+
+#[tokio::test]
+async fn init_escrow_with_escrow_as_game_acc_fails_with_not_game_account() {
+    let mut rental = setup_rental().await;
+    let renter = rental.renter.insecure_clone();
+    let program_id = rental.program_id;
+
+    let escrow_acc =
+        create_program_acc(&mut rental.context, &program_id, Escrow::LEN, RENT_AMOUNT).await;
+
+    // the open rent request given as the initialiser's game account
+    let err = send(
+        &mut rental.context,
+        &[instruction::init_escrow(
+            &program_id,
+            &escrow_acc.pubkey(),
+            &rental.escrow_acc.pubkey(),
+            &renter.pubkey(),
+            None,
+            rent_request(ASSET_ID),
+        )],
+        &[],
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::NotGameAccount as u32));
+}
+
+#[tokio::test]
+async fn close_game_acc_moves_owned_assets_and_returns_rent() {
+    let mut rental = setup_rental().await;
+    let lender = rental.lender.insecure_clone();
+    let program_id = rental.program_id;
+
+    let new_game_acc = create_game_acc(&mut rental.context, &program_id, &lender).await;
+
+    let game_acc_lamports = rental
+        .context
+        .banks_client
+        .get_balance(rental.lender_game_acc.pubkey())
+        .await
+        .unwrap();
+    let lender_before = rental
+        .context
+        .banks_client
+        .get_balance(lender.pubkey())
+        .await
+        .unwrap();
+
+    let close = instruction::close_game_acc(
+        &program_id,
+        &rental.lender_game_acc.pubkey(),
+        &lender.pubkey(),
+        Some(&new_game_acc.pubkey()),
+    );
+    send(&mut rental.context, &[close], &[&lender])
+        .await
+        .unwrap();
+
+    let closed = rental
+        .context
+        .banks_client
+        .get_account(rental.lender_game_acc.pubkey())
+        .await
+        .unwrap();
+    assert!(closed.is_none());
+
+    let new_info = game_acc_info(&mut rental.context, &new_game_acc.pubkey()).await;
+    assert!(new_info.owned.contains(&ASSET_ID));
+
+    let lender_after = rental
+        .context
+        .banks_client
+        .get_balance(lender.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(lender_after, lender_before + game_acc_lamports);
+}
+
+#[tokio::test]
+async fn close_game_acc_with_rental_in_progress_fails() {
+    let mut rental = setup_rental().await;
+    let lender = rental.lender.insecure_clone();
+    let renter = rental.renter.insecure_clone();
+    let program_id = rental.program_id;
+
+    let take = rental.take_ix(TakeEscData::default());
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    let close = instruction::close_game_acc(
+        &program_id,
+        &rental.lender_game_acc.pubkey(),
+        &lender.pubkey(),
+        None,
+    );
+    let err = send(&mut rental.context, &[close], &[&lender])
+        .await
+        .unwrap_err();
+    assert_eq!(custom_error(err), Some(EscrowError::AssetLentOut as u32));
+
+    let close = instruction::close_game_acc(
+        &program_id,
+        &rental.renter_game_acc.pubkey(),
+        &renter.pubkey(),
+        None,
+    );
+    let err = send(&mut rental.context, &[close], &[&renter])
+        .await
+        .unwrap_err();
+    assert_eq!(custom_error(err), Some(EscrowError::AssetRented as u32));
+}
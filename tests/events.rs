@@ -0,0 +1,47 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshSerialize;
+use game_nest::events::{Event, EVENT_TAG, LOG_DATA_PREFIX};
+use solana_program::pubkey::Pubkey;
+
+fn offer_cancelled() -> Event {
+    Event::OfferCancelled {
+        offer: Pubkey::new_unique(),
+        escrow: Pubkey::new_unique(),
+        offerer_main_acc: Pubkey::new_unique(),
+        timestamp: 1_700_000_000,
+    }
+}
+
+fn log_line(data: &[u8]) -> String {
+    format!("{}{}", LOG_DATA_PREFIX, STANDARD.encode(data))
+}
+
+#[test]
+fn event_round_trips_through_a_log_line() {
+    let event = offer_cancelled();
+
+    let data = event.encode();
+    assert!(data.starts_with(EVENT_TAG));
+    assert_eq!(Event::decode(&data), Some(event.clone()));
+
+    assert_eq!(Event::decode_log(&log_line(&data)), Some(event));
+}
+
+// another program's sol_log_data entry that happens to be a valid Borsh Event body
+#[test]
+fn foreign_log_line_is_not_an_event() {
+    let mut data = Vec::new();
+    offer_cancelled().serialize(&mut data).unwrap();
+
+    assert_eq!(Event::decode(&data), None);
+    assert_eq!(Event::decode_log(&log_line(&data)), None);
+    assert_eq!(
+        Event::decode_log("Program log: Instruction: Transfer"),
+        None
+    );
+}
+
+#[test]
+fn tag_alone_is_not_an_event() {
+    assert_eq!(Event::decode(EVENT_TAG), None);
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::*;
+use game_nest::{
+    error::EscrowError,
+    instruction::{self, InitEscData, OfferData, TakeEscData},
+    state::Offer,
+};
+use solana_program::{instruction::InstructionError, program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::{signature::Signer, transaction::TransactionError};
+
+const ASSET_ID: u64 = 47;
+const EXPIRES_IN: i64 = 10; // in minutes
+
+// a renter's rent request for ASSET_ID held by the lender, expiring in EXPIRES_IN minutes
+async fn setup() -> Rental {
+    let mut rental = Rental::start(&[ASSET_ID], None).await;
+    let now = unix_timestamp(&mut rental.context).await;
+
+    rental
+        .init_escrow(InitEscData {
+            expires_at: now + EXPIRES_IN * 60,
+            ..rent_request(ASSET_ID)
+        })
+        .await;
+
+    rental
+}
+
+#[tokio::test]
+async fn take_after_expiry_fails() {
+    let mut rental = setup().await;
+    let lender = rental.lender.insecure_clone();
+
+    warp_minutes(&mut rental.context, EXPIRES_IN + 1).await;
+
+    let take = rental.take_ix(TakeEscData::default());
+    let err = send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::EscrowExpired as u32));
+}
+
+#[tokio::test]
+async fn offer_after_expiry_fails() {
+    let mut rental = setup().await;
+    let program_id = rental.program_id;
+    let lender = rental.lender.insecure_clone();
+
+    let offer_acc = create_program_acc(&mut rental.context, &program_id, Offer::LEN, 0).await;
+
+    warp_minutes(&mut rental.context, EXPIRES_IN + 1).await;
+
+    let offer = instruction::make_offer(
+        &program_id,
+        &offer_acc.pubkey(),
+        &rental.escrow_acc.pubkey(),
+        &lender.pubkey(),
+        &rental.lender_game_acc.pubkey(),
+        OfferData {
+            amount: RENT_AMOUNT,
+            time: RENT_TIME,
+            asset_id: 0,
+            arbiter: Pubkey::default(),
+            oracle: Pubkey::default(),
+        },
+    );
+    let err = send(&mut rental.context, &[offer], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::EscrowExpired as u32));
+}
+
+// the payer cranks the cancel, the escrow's lamports still go back to the renter
+#[tokio::test]
+async fn anyone_can_cancel_after_expiry() {
+    let mut rental = setup().await;
+    let renter = rental.renter.pubkey();
+    let escrow_acc = rental.escrow_acc.pubkey();
+
+    warp_minutes(&mut rental.context, EXPIRES_IN + 1).await;
+
+    let renter_before = balance(&mut rental.context, &renter).await;
+    let escrow_lamports = balance(&mut rental.context, &escrow_acc).await;
+
+    let cancel = rental.cancel_ix(false);
+    send(&mut rental.context, &[cancel], &[]).await.unwrap();
+
+    assert!(is_closed(&mut rental.context, &escrow_acc).await);
+    assert_eq!(
+        balance(&mut rental.context, &renter).await,
+        renter_before + escrow_lamports
+    );
+}
+
+#[tokio::test]
+async fn only_the_initialiser_can_cancel_before_expiry() {
+    let mut rental = setup().await;
+
+    let cancel = rental.cancel_ix(false);
+    let err = send(&mut rental.context, &[cancel], &[]).await.unwrap_err();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+    assert!(!is_closed(&mut rental.context, &rental.escrow_acc.pubkey()).await);
+}
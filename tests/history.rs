@@ -0,0 +1,145 @@
+mod common;
+
+use common::*;
+use game_nest::{
+    error::EscrowError,
+    instruction::{self, TakeEscData},
+    state::{History, HISTORY_KIND_RENTAL, HISTORY_KIND_RETURN},
+};
+use solana_program::{instruction::InstructionError, program_pack::Pack, pubkey::Pubkey};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{signature::Signer, transaction::TransactionError};
+
+const ASSET_ID: u64 = 31;
+async fn history_info(context: &mut ProgramTestContext, program_id: &Pubkey) -> History {
+    let (history_acc, _) = History::find_address(program_id, ASSET_ID);
+    let account = context
+        .banks_client
+        .get_account(history_acc)
+        .await
+        .unwrap()
+        .unwrap();
+
+    History::unpack(&account.data).unwrap()
+}
+
+// a renter's open rent request for ASSET_ID held by the lender, the asset has no History A/c yet
+async fn setup() -> Rental {
+    let mut rental = Rental::setup(&[], rent_request(ASSET_ID), None).await;
+    let program_id = rental.program_id;
+    let lender_game_acc = rental.lender_game_acc.pubkey();
+
+    add_asset(&mut rental.context, &program_id, &lender_game_acc, ASSET_ID).await;
+
+    rental
+}
+
+// the lender's take without any History A/c
+fn take_ix(rental: &Rental) -> solana_program::instruction::Instruction {
+    let mut take = rental.take_ix(TakeEscData::default());
+    take.accounts.pop();
+
+    take
+}
+
+#[tokio::test]
+async fn take_and_revert_are_recorded() {
+    let mut rental = setup().await;
+    let program_id = rental.program_id;
+    let lender = rental.lender.insecure_clone();
+
+    create_history(&mut rental.context, &program_id, ASSET_ID).await;
+
+    let history = history_info(&mut rental.context, &program_id).await;
+    assert_eq!(history.asset_id, ASSET_ID);
+    assert_eq!(history.total, 0);
+
+    let take = instruction::with_history(take_ix(&rental), ASSET_ID);
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    warp_minutes(&mut rental.context, RENT_TIME as i64 + 1).await;
+
+    let revert = rental.revert_ix();
+    send(&mut rental.context, &[revert], &[]).await.unwrap();
+
+    let history = history_info(&mut rental.context, &program_id).await;
+    assert_eq!(history.total, 2);
+
+    let rental_entry = history.entries[0];
+    assert_eq!(rental_entry.kind, HISTORY_KIND_RENTAL);
+    assert_eq!(rental_entry.from_game_acc, rental.lender_game_acc.pubkey());
+    assert_eq!(rental_entry.to_game_acc, rental.renter_game_acc.pubkey());
+    assert_eq!(rental_entry.price, RENT_AMOUNT);
+
+    let return_entry = history.entries[1];
+    assert_eq!(return_entry.kind, HISTORY_KIND_RETURN);
+    assert_eq!(return_entry.from_game_acc, rental.renter_game_acc.pubkey());
+    assert_eq!(return_entry.to_game_acc, rental.lender_game_acc.pubkey());
+}
+
+#[tokio::test]
+async fn take_without_the_history_fails() {
+    let mut rental = setup().await;
+    let program_id = rental.program_id;
+    let lender = rental.lender.insecure_clone();
+
+    create_history(&mut rental.context, &program_id, ASSET_ID).await;
+
+    let take = take_ix(&rental);
+    let err = send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::MissingHistory as u32));
+}
+
+#[tokio::test]
+async fn take_with_another_asset_history_fails() {
+    let mut rental = setup().await;
+    let program_id = rental.program_id;
+    let lender = rental.lender.insecure_clone();
+
+    create_history(&mut rental.context, &program_id, ASSET_ID + 1).await;
+
+    let take = instruction::with_history(take_ix(&rental), ASSET_ID + 1);
+    let err = send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::MissingHistory as u32));
+}
+
+#[tokio::test]
+async fn take_before_the_history_is_created_fails() {
+    let mut rental = setup().await;
+    let lender = rental.lender.insecure_clone();
+
+    let take = instruction::with_history(take_ix(&rental), ASSET_ID);
+    let err = send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+    );
+}
+
+#[tokio::test]
+async fn history_cannot_be_created_twice() {
+    let mut rental = setup().await;
+    let program_id = rental.program_id;
+
+    create_history(&mut rental.context, &program_id, ASSET_ID).await;
+
+    let payer = rental.context.payer.pubkey();
+    let init = instruction::init_history(&program_id, &payer, ASSET_ID);
+    let err = send(&mut rental.context, &[init], &[]).await.unwrap_err();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::*;
+use game_nest::{
+    error::EscrowError,
+    instruction::{self, InitEscData, TakeEscData},
+    state::{Escrow, GameAcc, Market},
+};
+use solana_program::{instruction::InstructionError, program_pack::Pack, pubkey::Pubkey};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+
+const ASSET_ID: u64 = 19;
+
+async fn market_info(context: &mut ProgramTestContext, market_acc: &Pubkey) -> Market {
+    let account = context
+        .banks_client
+        .get_account(*market_acc)
+        .await
+        .unwrap()
+        .unwrap();
+
+    Market::unpack(&account.data).unwrap()
+}
+
+// a renter's rent request for ASSET_ID listed in a market, and a lender holding the asset
+async fn setup() -> Rental {
+    Rental::setup(
+        &[ASSET_ID],
+        rent_request(ASSET_ID),
+        Some(MarketRoles::default()),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn rent_requests_are_listed_best_price_first() {
+    let mut rental = setup().await;
+    let cheap = rental.escrow_acc.insecure_clone();
+    let market_acc = rental.market_acc.unwrap();
+
+    rental
+        .init_escrow(InitEscData {
+            amount: RENT_AMOUNT * 2,
+            ..rent_request(ASSET_ID)
+        })
+        .await;
+    let generous = rental.escrow_acc.pubkey();
+
+    let market = market_info(&mut rental.context, &market_acc).await;
+    assert_eq!(market.len, 2);
+    assert_eq!(market.entries[0].escrow, generous);
+    assert_eq!(market.entries[0].price, RENT_AMOUNT * 2);
+    assert_eq!(market.entries[1].escrow, cheap.pubkey());
+
+    let escrow = escrow_info(&mut rental.context, &cheap.pubkey()).await;
+    assert_eq!(escrow.market, market_acc);
+}
+
+#[tokio::test]
+async fn market_cannot_be_initialised_twice() {
+    let mut rental = setup().await;
+    let authority = Keypair::new();
+
+    let init = instruction::init_market(
+        &rental.program_id,
+        &rental.market_acc.unwrap(),
+        &authority.pubkey(),
+        None,
+        None,
+    );
+    let err = send(&mut rental.context, &[init], &[&authority])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn taking_a_listed_escrow_delists_it() {
+    let mut rental = setup().await;
+    let lender = rental.lender.insecure_clone();
+    let market_acc = rental.market_acc.unwrap();
+
+    let take = rental.take_ix(TakeEscData::default());
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    let market = market_info(&mut rental.context, &market_acc).await;
+    assert_eq!(market.len, 0);
+}
+
+#[tokio::test]
+async fn taking_a_listed_escrow_with_another_market_fails() {
+    let mut rental = setup().await;
+    let lender = rental.lender.insecure_clone();
+    let program_id = rental.program_id;
+
+    let other_market = create_program_acc(&mut rental.context, &program_id, Market::LEN, 0).await;
+    rental.market_acc = Some(other_market.pubkey());
+
+    let take = rental.take_ix(TakeEscData::default());
+    let err = send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AccountMismatch as u32));
+}
+
+#[tokio::test]
+async fn cancelling_a_listed_escrow_delists_it() {
+    let mut rental = setup().await;
+    let renter = rental.renter.insecure_clone();
+    let market_acc = rental.market_acc.unwrap();
+    let program_id = rental.program_id;
+
+    create_reputation(&mut rental.context, &program_id, &renter.pubkey()).await;
+
+    let cancel = rental.cancel_ix(true);
+    send(&mut rental.context, &[cancel], &[&renter])
+        .await
+        .unwrap();
+
+    let market = market_info(&mut rental.context, &market_acc).await;
+    assert_eq!(market.len, 0);
+}
+
+#[tokio::test]
+async fn escrow_cannot_be_listed_in_a_game_acc() {
+    let mut rental = setup().await;
+    let program_id = rental.program_id;
+
+    let escrow_acc =
+        create_program_acc(&mut rental.context, &program_id, Escrow::LEN, RENT_AMOUNT).await;
+    let game_acc = create_program_acc(&mut rental.context, &program_id, GameAcc::LEN, 0).await;
+
+    let init = instruction::init_escrow(
+        &program_id,
+        &escrow_acc.pubkey(),
+        &rental.renter_game_acc.pubkey(),
+        &rental.renter.pubkey(),
+        Some(&game_acc.pubkey()),
+        rent_request(ASSET_ID),
+    );
+    let err = send(&mut rental.context, &[init], &[]).await.unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::IncorrectAcc as u32));
+}
@@ -0,0 +1,229 @@
+mod common;
+
+use common::*;
+use game_nest::{
+    error::EscrowError,
+    instruction::{self, TakeEscData},
+    state::Escrow,
+};
+use solana_program::{
+    instruction::{AccountMeta, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+
+const ASSET_ID: u64 = 23;
+
+struct Match {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    player: Keypair,
+    game_acc: Keypair,
+    authority: Keypair,
+}
+
+// a player holding ASSET_ID and the authority of the match they're joining
+async fn setup() -> Match {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+
+    let player = funded_wallet(&mut program_test);
+
+    let mut context = program_test.start_with_context().await;
+
+    let game_acc = create_game_acc(&mut context, &program_id, &player).await;
+
+    add_asset(&mut context, &program_id, &game_acc.pubkey(), ASSET_ID).await;
+
+    Match {
+        context,
+        program_id,
+        player,
+        game_acc,
+        authority: Keypair::new(),
+    }
+}
+
+// the player's GameAcc locked by the match authority
+async fn setup_locked() -> Match {
+    let mut game = setup().await;
+    let player = game.player.insecure_clone();
+    let authority = game.authority.insecure_clone();
+
+    let lock = instruction::lock_for_match(
+        &game.program_id,
+        &game.game_acc.pubkey(),
+        &player.pubkey(),
+        &authority.pubkey(),
+    );
+    send(&mut game.context, &[lock], &[&player, &authority])
+        .await
+        .unwrap();
+
+    game
+}
+
+fn assert_owns_ix(
+    game: &Match,
+    player_main_acc: &Pubkey,
+    asset_id: u64,
+) -> solana_program::instruction::Instruction {
+    instruction::assert_owns(
+        &game.program_id,
+        &game.game_acc.pubkey(),
+        player_main_acc,
+        asset_id,
+    )
+}
+
+#[tokio::test]
+async fn assert_owns_passes_for_a_held_asset() {
+    let mut game = setup().await;
+
+    let assert = assert_owns_ix(&game, &game.player.pubkey(), ASSET_ID);
+    send(&mut game.context, &[assert], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn assert_owns_fails_for_an_asset_not_held() {
+    let mut game = setup().await;
+
+    let assert = assert_owns_ix(&game, &game.player.pubkey(), ASSET_ID + 1);
+    let err = send(&mut game.context, &[assert], &[]).await.unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AssetNotOwned as u32));
+}
+
+#[tokio::test]
+async fn assert_owns_fails_for_another_player() {
+    let mut game = setup().await;
+
+    let assert = assert_owns_ix(&game, &Pubkey::new_unique(), ASSET_ID);
+    let err = send(&mut game.context, &[assert], &[]).await.unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::OwnerMismatch as u32));
+}
+
+#[tokio::test]
+async fn lock_sets_the_authority_and_unlock_clears_it() {
+    let mut game = setup_locked().await;
+    let authority = game.authority.insecure_clone();
+
+    let game_info = game_acc_info(&mut game.context, &game.game_acc.pubkey()).await;
+    assert_eq!(game_info.locked_by, authority.pubkey());
+
+    let unlock = instruction::unlock_after_match(
+        &game.program_id,
+        &game.game_acc.pubkey(),
+        &authority.pubkey(),
+    );
+    send(&mut game.context, &[unlock], &[&authority])
+        .await
+        .unwrap();
+
+    let game_info = game_acc_info(&mut game.context, &game.game_acc.pubkey()).await;
+    assert!(!game_info.is_locked());
+}
+
+#[tokio::test]
+async fn lock_without_the_player_fails() {
+    let mut game = setup().await;
+    let authority = game.authority.insecure_clone();
+
+    let mut lock = instruction::lock_for_match(
+        &game.program_id,
+        &game.game_acc.pubkey(),
+        &game.player.pubkey(),
+        &authority.pubkey(),
+    );
+    lock.accounts[1] = AccountMeta::new_readonly(game.player.pubkey(), false);
+    let err = send(&mut game.context, &[lock], &[&authority])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+#[tokio::test]
+async fn locked_game_acc_cannot_be_locked_again() {
+    let mut game = setup_locked().await;
+    let player = game.player.insecure_clone();
+    let other_authority = Keypair::new();
+
+    let lock = instruction::lock_for_match(
+        &game.program_id,
+        &game.game_acc.pubkey(),
+        &player.pubkey(),
+        &other_authority.pubkey(),
+    );
+    let err = send(&mut game.context, &[lock], &[&player, &other_authority])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AccountLocked as u32));
+}
+
+#[tokio::test]
+async fn unlock_by_another_authority_fails() {
+    let mut game = setup_locked().await;
+    let other_authority = Keypair::new();
+
+    let unlock = instruction::unlock_after_match(
+        &game.program_id,
+        &game.game_acc.pubkey(),
+        &other_authority.pubkey(),
+    );
+    let err = send(&mut game.context, &[unlock], &[&other_authority])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        custom_error(err),
+        Some(EscrowError::NotLockAuthority as u32)
+    );
+}
+
+#[tokio::test]
+async fn locked_asset_cannot_be_lent() {
+    let mut game = setup_locked().await;
+    let program_id = game.program_id;
+    let lender = game.player.insecure_clone();
+
+    let renter = Keypair::new();
+    let renter_game_acc = create_game_acc(&mut game.context, &program_id, &renter).await;
+    let escrow_acc =
+        create_program_acc(&mut game.context, &program_id, Escrow::LEN, RENT_AMOUNT).await;
+
+    let init = instruction::init_escrow(
+        &program_id,
+        &escrow_acc.pubkey(),
+        &renter_game_acc.pubkey(),
+        &renter.pubkey(),
+        None,
+        rent_request(ASSET_ID),
+    );
+    send(&mut game.context, &[init], &[]).await.unwrap();
+
+    let take = instruction::take_escrow(
+        &program_id,
+        &lender.pubkey(),
+        &escrow_acc.pubkey(),
+        &renter_game_acc.pubkey(),
+        &game.game_acc.pubkey(),
+        None,
+        TakeEscData::default(),
+    );
+    let err = send(&mut game.context, &[take], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AccountLocked as u32));
+}
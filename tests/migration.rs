@@ -0,0 +1,243 @@
+mod common;
+
+use common::*;
+use game_nest::{
+    error::EscrowError,
+    instruction,
+    state::{Escrow, GameAcc, ACCOUNT_VERSION, ESCROW_KIND_RENT_REQUEST},
+};
+use solana_program::{
+    instruction::InstructionError, program_pack::Pack, pubkey::Pubkey, rent::Rent,
+};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{account::Account, signature::Signer, transaction::TransactionError};
+
+const BASELINE_ESCROW_LEN: usize = 130;
+const BASELINE_GAME_ACC_LEN: usize = 353;
+const RENT_AMOUNT: u64 = 1_000_000;
+
+struct Baseline {
+    initialiser_main_acc: Pubkey,
+    initialiser_game_acc: Pubkey,
+    taker_game_acc: Pubkey,
+    owner: Pubkey,
+}
+
+// a taken baseline rent request: acc_type, is_taken, initialiser main, initialiser game,
+// taker game, esc_taken_time, amount, time and asset_id, little endian
+fn baseline_escrow(baseline: &Baseline) -> Vec<u8> {
+    let mut data = vec![1, 1];
+    data.extend_from_slice(baseline.initialiser_main_acc.as_ref());
+    data.extend_from_slice(baseline.initialiser_game_acc.as_ref());
+    data.extend_from_slice(baseline.taker_game_acc.as_ref());
+    data.extend_from_slice(&1_600_000_000i64.to_le_bytes());
+    data.extend_from_slice(&RENT_AMOUNT.to_le_bytes());
+    data.extend_from_slice(&60u64.to_le_bytes());
+    data.extend_from_slice(&7u64.to_le_bytes());
+    assert_eq!(data.len(), BASELINE_ESCROW_LEN);
+
+    data
+}
+
+// a baseline GameAcc: acc_type, userspace_owner, owned[20] and rented[20], little endian
+fn baseline_game_acc(baseline: &Baseline) -> Vec<u8> {
+    let mut data = vec![2];
+    data.extend_from_slice(baseline.owner.as_ref());
+    for asset_id in 0..20u64 {
+        data.extend_from_slice(&(asset_id * 3).to_le_bytes());
+    }
+    for asset_id in 0..20u64 {
+        data.extend_from_slice(&(asset_id * 5).to_le_bytes());
+    }
+    assert_eq!(data.len(), BASELINE_GAME_ACC_LEN);
+
+    data
+}
+
+fn add_program_acc(
+    program_test: &mut ProgramTest,
+    program_id: &Pubkey,
+    data: Vec<u8>,
+    extra_lamports: u64,
+) -> Pubkey {
+    let key = Pubkey::new_unique();
+    let lamports = Rent::default().minimum_balance(data.len()) + extra_lamports;
+
+    program_test.add_account(
+        key,
+        Account {
+            lamports,
+            data,
+            owner: *program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    key
+}
+
+async fn migrate(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    acc: &Pubkey,
+) -> Result<(), TransactionError> {
+    let migrate = instruction::migrate_account(program_id, acc, &context.payer.pubkey());
+    send(context, &[migrate], &[]).await
+}
+
+fn baseline() -> Baseline {
+    Baseline {
+        initialiser_main_acc: Pubkey::new_unique(),
+        initialiser_game_acc: Pubkey::new_unique(),
+        taker_game_acc: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+    }
+}
+
+#[tokio::test]
+async fn baseline_escrow_is_migrated_field_by_field() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+    let baseline = baseline();
+
+    let escrow_acc = add_program_acc(
+        &mut program_test,
+        &program_id,
+        baseline_escrow(&baseline),
+        RENT_AMOUNT,
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let before = balance(&mut context, &escrow_acc).await;
+
+    migrate(&mut context, &program_id, &escrow_acc)
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(escrow_acc)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), Escrow::LEN);
+    assert_eq!(account.data[1], ACCOUNT_VERSION);
+
+    let escrow = Escrow::unpack(&account.data).unwrap();
+    assert!(escrow.is_taken);
+    assert_eq!(escrow.initialiser_main_acc, baseline.initialiser_main_acc);
+    assert_eq!(escrow.initialiser_game_acc, baseline.initialiser_game_acc);
+    assert_eq!(escrow.taker_game_acc, baseline.taker_game_acc);
+    assert_eq!(escrow.esc_taken_time, 1_600_000_000);
+    assert_eq!(escrow.amount, RENT_AMOUNT);
+    assert_eq!(escrow.time, 60);
+    assert_eq!(escrow.asset_id, 7);
+    assert_eq!(escrow.kind, ESCROW_KIND_RENT_REQUEST);
+    assert_eq!(escrow.taker_main_acc, Pubkey::default());
+
+    // the rent held for the lender is kept on top of the larger account's rent
+    let rent = Rent::default();
+    let added_rent = rent.minimum_balance(Escrow::LEN) - rent.minimum_balance(BASELINE_ESCROW_LEN);
+    assert_eq!(
+        balance(&mut context, &escrow_acc).await,
+        before + added_rent
+    );
+}
+
+#[tokio::test]
+async fn baseline_game_acc_is_migrated_field_by_field() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+    let baseline = baseline();
+
+    let game_acc = add_program_acc(
+        &mut program_test,
+        &program_id,
+        baseline_game_acc(&baseline),
+        0,
+    );
+
+    let mut context = program_test.start_with_context().await;
+
+    migrate(&mut context, &program_id, &game_acc).await.unwrap();
+
+    let game_info = game_acc_info(&mut context, &game_acc).await;
+    assert_eq!(game_info.userspace_owner, baseline.owner);
+    assert_eq!(game_info.owned[19], 19 * 3);
+    assert_eq!(game_info.rented[19], 19 * 5);
+    assert_eq!(game_info.lent, [0; 20]);
+    assert!(!game_info.is_locked());
+}
+
+#[tokio::test]
+async fn baseline_game_acc_is_outdated_till_migrated() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+    let baseline = baseline();
+
+    let game_acc = add_program_acc(
+        &mut program_test,
+        &program_id,
+        baseline_game_acc(&baseline),
+        0,
+    );
+
+    let mut context = program_test.start_with_context().await;
+
+    let assert = instruction::assert_owns(&program_id, &game_acc, &baseline.owner, 3);
+    let err = send(&mut context, std::slice::from_ref(&assert), &[])
+        .await
+        .unwrap_err();
+    assert_eq!(custom_error(err), Some(EscrowError::AccountOutdated as u32));
+
+    migrate(&mut context, &program_id, &game_acc).await.unwrap();
+
+    send(&mut context, &[assert], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn migrating_a_current_account_changes_nothing() {
+    let program_id = Pubkey::new_unique();
+    let program_test = program_test(program_id);
+    let owner = Pubkey::new_unique();
+
+    let mut context = program_test.start_with_context().await;
+
+    let game_acc = create_program_acc(&mut context, &program_id, GameAcc::LEN, 0).await;
+    let init = instruction::init_game_acc(&program_id, &game_acc.pubkey(), &owner);
+    send(&mut context, &[init], &[]).await.unwrap();
+
+    let before = game_acc_info(&mut context, &game_acc.pubkey()).await;
+
+    migrate(&mut context, &program_id, &game_acc.pubkey())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        game_acc_info(&mut context, &game_acc.pubkey()).await,
+        before
+    );
+}
+
+#[tokio::test]
+async fn baseline_escrow_with_a_bad_field_fails() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = program_test(program_id);
+    let baseline = baseline();
+
+    let mut data = baseline_escrow(&baseline);
+    data[1] = 2; // is_taken is neither false nor true
+    let escrow_acc = add_program_acc(&mut program_test, &program_id, data, 0);
+
+    let mut context = program_test.start_with_context().await;
+
+    let err = migrate(&mut context, &program_id, &escrow_acc)
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::InvalidAccountData)
+    );
+}
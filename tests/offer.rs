@@ -0,0 +1,280 @@
+mod common;
+
+use common::*;
+use game_nest::{
+    error::EscrowError,
+    instruction::{self, InitEscData, OfferData},
+    state::Offer,
+};
+use solana_program::{instruction::InstructionError, program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+
+const ASSET_ID: u64 = 17;
+const OFFER_AMOUNT: u64 = 600_000;
+const OFFER_TIME: u64 = 30; // in minutes
+
+// a renter's open rent request and the lender's Offer A/c, created but not initialised,
+// expires_in is the minutes till the rent request expires, 0 if it never does
+async fn setup(expires_in: i64) -> (Rental, Keypair) {
+    let mut rental = Rental::start(&[ASSET_ID], None).await;
+    let program_id = rental.program_id;
+
+    let expires_at = if expires_in != 0 {
+        unix_timestamp(&mut rental.context).await + expires_in * 60
+    } else {
+        0
+    };
+    rental
+        .init_escrow(InitEscData {
+            expires_at,
+            ..rent_request(ASSET_ID)
+        })
+        .await;
+
+    let offer_acc = create_program_acc(&mut rental.context, &program_id, Offer::LEN, 0).await;
+
+    (rental, offer_acc)
+}
+
+fn make_offer_ix(
+    rental: &Rental,
+    offer_acc: &Keypair,
+    offerer: &Keypair,
+    offerer_game_acc: &Pubkey,
+) -> solana_program::instruction::Instruction {
+    instruction::make_offer(
+        &rental.program_id,
+        &offer_acc.pubkey(),
+        &rental.escrow_acc.pubkey(),
+        &offerer.pubkey(),
+        offerer_game_acc,
+        OfferData {
+            amount: OFFER_AMOUNT,
+            time: OFFER_TIME,
+            asset_id: 0,
+            arbiter: Pubkey::default(),
+            oracle: Pubkey::default(),
+        },
+    )
+}
+
+// the lender's offer made on the renter's rent request
+async fn setup_offer(expires_in: i64) -> (Rental, Keypair) {
+    let (mut rental, offer_acc) = setup(expires_in).await;
+    let lender = rental.lender.insecure_clone();
+
+    let make = make_offer_ix(
+        &rental,
+        &offer_acc,
+        &lender,
+        &rental.lender_game_acc.pubkey(),
+    );
+    send(&mut rental.context, &[make], &[&lender])
+        .await
+        .unwrap();
+
+    (rental, offer_acc)
+}
+
+fn accept_ix(
+    rental: &Rental,
+    offer_acc: &Keypair,
+    initialiser: &Keypair,
+) -> solana_program::instruction::Instruction {
+    let accept = instruction::accept_offer(
+        &rental.program_id,
+        &initialiser.pubkey(),
+        &rental.escrow_acc.pubkey(),
+        &offer_acc.pubkey(),
+        &rental.renter_game_acc.pubkey(),
+        &rental.lender_game_acc.pubkey(),
+        &rental.lender.pubkey(),
+        None,
+    );
+
+    instruction::with_history(accept, ASSET_ID)
+}
+
+fn cancel_offer_ix(
+    rental: &Rental,
+    offer_acc: &Keypair,
+    offerer_signs: bool,
+) -> solana_program::instruction::Instruction {
+    instruction::cancel_offer(
+        &rental.program_id,
+        &offer_acc.pubkey(),
+        &rental.lender.pubkey(),
+        &rental.escrow_acc.pubkey(),
+        offerer_signs,
+    )
+}
+
+#[tokio::test]
+async fn accepted_offer_takes_the_escrow_on_its_terms() {
+    let (mut rental, offer_acc) = setup_offer(0).await;
+    let renter = rental.renter.insecure_clone();
+    let lender = rental.lender.pubkey();
+
+    let offer_lamports = balance(&mut rental.context, &offer_acc.pubkey()).await;
+    let lender_before = balance(&mut rental.context, &lender).await;
+    let renter_before = balance(&mut rental.context, &renter.pubkey()).await;
+
+    let accept = accept_ix(&rental, &offer_acc, &renter);
+    send(&mut rental.context, &[accept], &[&renter])
+        .await
+        .unwrap();
+
+    let escrow = escrow_info(&mut rental.context, &rental.escrow_acc.pubkey()).await;
+    assert!(escrow.is_taken);
+    assert_eq!(escrow.amount, OFFER_AMOUNT);
+    assert_eq!(escrow.time, OFFER_TIME);
+    assert_eq!(escrow.taker_main_acc, lender);
+
+    let renter_info = game_acc_info(&mut rental.context, &rental.renter_game_acc.pubkey()).await;
+    assert!(renter_info.rented.contains(&ASSET_ID));
+
+    // the lender gets the rental amount and the Offer A/c's lamports,
+    // the renter the part of the rent the offer didn't ask for
+    let lender_after = balance(&mut rental.context, &lender).await;
+    let renter_after = balance(&mut rental.context, &renter.pubkey()).await;
+    assert_eq!(lender_after, lender_before + OFFER_AMOUNT + offer_lamports);
+    assert_eq!(renter_after, renter_before + RENT_AMOUNT - OFFER_AMOUNT);
+    assert!(is_closed(&mut rental.context, &offer_acc.pubkey()).await);
+}
+
+#[tokio::test]
+async fn offer_on_own_escrow_fails() {
+    let (mut rental, offer_acc) = setup(0).await;
+    let renter = rental.renter.insecure_clone();
+
+    let make = make_offer_ix(
+        &rental,
+        &offer_acc,
+        &renter,
+        &rental.renter_game_acc.pubkey(),
+    );
+    let err = send(&mut rental.context, &[make], &[&renter])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::OwnEscrow as u32));
+}
+
+#[tokio::test]
+async fn offer_without_the_asset_fails() {
+    let (mut rental, offer_acc) = setup(0).await;
+    let program_id = rental.program_id;
+    let offerer = rental.lender.insecure_clone();
+
+    let empty_game_acc = create_game_acc(&mut rental.context, &program_id, &offerer).await;
+
+    let make = make_offer_ix(&rental, &offer_acc, &offerer, &empty_game_acc.pubkey());
+    let err = send(&mut rental.context, &[make], &[&offerer])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AssetNotOwned as u32));
+}
+
+#[tokio::test]
+async fn offer_accepted_by_someone_else_fails() {
+    let (mut rental, offer_acc) = setup_offer(0).await;
+    let lender = rental.lender.insecure_clone();
+
+    let accept = accept_ix(&rental, &offer_acc, &lender);
+    let err = send(&mut rental.context, &[accept], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AccountMismatch as u32));
+}
+
+#[tokio::test]
+async fn offerer_can_cancel_the_offer() {
+    let (mut rental, offer_acc) = setup_offer(0).await;
+    let lender = rental.lender.insecure_clone();
+
+    let offer_lamports = balance(&mut rental.context, &offer_acc.pubkey()).await;
+    let lender_before = balance(&mut rental.context, &lender.pubkey()).await;
+
+    let cancel = cancel_offer_ix(&rental, &offer_acc, true);
+    send(&mut rental.context, &[cancel], &[&lender])
+        .await
+        .unwrap();
+
+    let lender_after = balance(&mut rental.context, &lender.pubkey()).await;
+    assert_eq!(lender_after, lender_before + offer_lamports);
+    assert!(is_closed(&mut rental.context, &offer_acc.pubkey()).await);
+}
+
+#[tokio::test]
+async fn offer_on_an_open_escrow_needs_the_offerer() {
+    let (mut rental, offer_acc) = setup_offer(0).await;
+
+    let cancel = cancel_offer_ix(&rental, &offer_acc, false);
+    let err = send(&mut rental.context, &[cancel], &[]).await.unwrap_err();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+#[tokio::test]
+async fn anyone_can_cancel_the_offer_once_the_escrow_is_closed() {
+    let (mut rental, offer_acc) = setup_offer(0).await;
+    let renter = rental.renter.insecure_clone();
+    let program_id = rental.program_id;
+
+    create_reputation(&mut rental.context, &program_id, &renter.pubkey()).await;
+
+    let cancel_escrow = rental.cancel_ix(true);
+    send(&mut rental.context, &[cancel_escrow], &[&renter])
+        .await
+        .unwrap();
+
+    let cancel = cancel_offer_ix(&rental, &offer_acc, false);
+    send(&mut rental.context, &[cancel], &[]).await.unwrap();
+
+    assert!(is_closed(&mut rental.context, &offer_acc.pubkey()).await);
+}
+
+#[tokio::test]
+async fn anyone_can_cancel_the_offer_once_the_escrow_expired() {
+    let (mut rental, offer_acc) = setup_offer(10).await;
+
+    let cancel = cancel_offer_ix(&rental, &offer_acc, false);
+    let err = send(&mut rental.context, &[cancel], &[]).await.unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+
+    warp_minutes(&mut rental.context, 11).await;
+
+    let cancel = cancel_offer_ix(&rental, &offer_acc, false);
+    send(&mut rental.context, &[cancel], &[]).await.unwrap();
+
+    assert!(is_closed(&mut rental.context, &offer_acc.pubkey()).await);
+}
+
+#[tokio::test]
+async fn cancel_offer_with_another_escrow_fails() {
+    let (mut rental, offer_acc) = setup_offer(0).await;
+    let lender = rental.lender.insecure_clone();
+
+    // the renter's game account given as the offer's escrow
+    let cancel = instruction::cancel_offer(
+        &rental.program_id,
+        &offer_acc.pubkey(),
+        &lender.pubkey(),
+        &rental.renter_game_acc.pubkey(),
+        false,
+    );
+    let err = send(&mut rental.context, &[cancel], &[]).await.unwrap_err();
+
+    assert_eq!(custom_error(err), Some(EscrowError::AccountMismatch as u32));
+}
@@ -0,0 +1,192 @@
+mod common;
+
+use common::*;
+use game_nest::{
+    error::EscrowError,
+    instruction::{self, TakeEscData},
+    state::Escrow,
+};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_sdk::{
+    ed25519_program,
+    signature::{Keypair, Signer},
+};
+
+const ASSET_ID: u64 = 43;
+
+// a renter's rent request for ASSET_ID, listed in a market with the returned oracle,
+// and a lender holding the asset
+async fn setup() -> (Rental, Keypair) {
+    let oracle = Keypair::new();
+    let market = MarketRoles {
+        oracle: Some(oracle.pubkey()),
+        ..MarketRoles::default()
+    };
+
+    let rental = Rental::setup(&[ASSET_ID], rent_request(ASSET_ID), Some(market)).await;
+
+    (rental, oracle)
+}
+
+fn take_ix(rental: &Rental, oracle: Pubkey) -> Instruction {
+    rental.take_ix(TakeEscData {
+        oracle,
+        ..TakeEscData::default()
+    })
+}
+
+// the lender has lent the asset, the rent is held till delivery is confirmed
+async fn setup_taken() -> (Rental, Keypair) {
+    let (mut rental, oracle) = setup().await;
+    let lender = rental.lender.insecure_clone();
+
+    let take = take_ix(&rental, oracle.pubkey());
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    (rental, oracle)
+}
+
+// an Ed25519 program instruction verifying signer's signature over message,
+// with the key, signature and message in its own data
+fn ed25519_ix(signer: &Keypair, message: &[u8]) -> Instruction {
+    const DATA_START: u16 = 16;
+    const PUBLIC_KEY_OFFSET: u16 = DATA_START;
+    const SIGNATURE_OFFSET: u16 = PUBLIC_KEY_OFFSET + 32;
+    const MESSAGE_OFFSET: u16 = SIGNATURE_OFFSET + 64;
+    const THIS_IX: u16 = u16::MAX;
+
+    let mut data = vec![1, 0];
+    for offset in [
+        SIGNATURE_OFFSET,
+        THIS_IX,
+        PUBLIC_KEY_OFFSET,
+        THIS_IX,
+        MESSAGE_OFFSET,
+        message.len() as u16,
+        THIS_IX,
+    ] {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(signer.pubkey().as_ref());
+    data.extend_from_slice(signer.sign_message(message).as_ref());
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+fn attestation_ixs(rental: &Rental, signer: &Keypair, escrow: &Escrow) -> [Instruction; 2] {
+    let escrow_key = rental.escrow_acc.pubkey();
+
+    [
+        ed25519_ix(signer, &escrow.delivery_attestation(&escrow_key)),
+        instruction::confirm_delivery_with_attestation(
+            &rental.program_id,
+            &escrow_key,
+            &rental.lender.pubkey(),
+        ),
+    ]
+}
+
+#[tokio::test]
+async fn take_without_agreeing_to_the_oracle_fails() {
+    let (mut rental, _) = setup().await;
+    let lender = rental.lender.insecure_clone();
+
+    for oracle in [Pubkey::default(), Pubkey::new_unique()] {
+        let take = take_ix(&rental, oracle);
+        let err = send(&mut rental.context, &[take], &[&lender])
+            .await
+            .unwrap_err();
+
+        assert_eq!(custom_error(err), Some(EscrowError::OracleMismatch as u32));
+    }
+}
+
+#[tokio::test]
+async fn oracle_signing_releases_the_rent() {
+    let (mut rental, oracle) = setup_taken().await;
+    let lender = rental.lender.pubkey();
+
+    let lender_before = balance(&mut rental.context, &lender).await;
+
+    let confirm = instruction::confirm_delivery(
+        &rental.program_id,
+        &rental.escrow_acc.pubkey(),
+        &lender,
+        &oracle.pubkey(),
+    );
+    send(&mut rental.context, &[confirm], &[&oracle])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        balance(&mut rental.context, &lender).await,
+        lender_before + RENT_AMOUNT
+    );
+
+    let escrow = escrow_info(&mut rental.context, &rental.escrow_acc.pubkey()).await;
+    assert_ne!(escrow.delivered_at, 0);
+}
+
+#[tokio::test]
+async fn oracle_attestation_releases_the_rent() {
+    let (mut rental, oracle) = setup_taken().await;
+    let lender = rental.lender.pubkey();
+
+    let lender_before = balance(&mut rental.context, &lender).await;
+    let escrow = escrow_info(&mut rental.context, &rental.escrow_acc.pubkey()).await;
+
+    let confirm = attestation_ixs(&rental, &oracle, &escrow);
+    send(&mut rental.context, &confirm, &[]).await.unwrap();
+
+    assert_eq!(
+        balance(&mut rental.context, &lender).await,
+        lender_before + RENT_AMOUNT
+    );
+}
+
+#[tokio::test]
+async fn attestation_by_another_key_fails() {
+    let (mut rental, _) = setup_taken().await;
+    let other = Keypair::new();
+
+    let escrow = escrow_info(&mut rental.context, &rental.escrow_acc.pubkey()).await;
+
+    let confirm = attestation_ixs(&rental, &other, &escrow);
+    let err = send(&mut rental.context, &confirm, &[]).await.unwrap_err();
+
+    assert_eq!(
+        custom_error(err),
+        Some(EscrowError::InvalidAttestation as u32)
+    );
+}
+
+#[tokio::test]
+async fn delivery_cannot_be_confirmed_twice() {
+    let (mut rental, oracle) = setup_taken().await;
+
+    let escrow = escrow_info(&mut rental.context, &rental.escrow_acc.pubkey()).await;
+    let confirm = attestation_ixs(&rental, &oracle, &escrow);
+    send(&mut rental.context, &confirm, &[]).await.unwrap();
+
+    let confirm = instruction::confirm_delivery(
+        &rental.program_id,
+        &rental.escrow_acc.pubkey(),
+        &rental.lender.pubkey(),
+        &oracle.pubkey(),
+    );
+    let err = send(&mut rental.context, &[confirm], &[&oracle])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        custom_error(err),
+        Some(EscrowError::DeliveryNotPending as u32)
+    );
+}
@@ -0,0 +1,177 @@
+mod common;
+
+use common::*;
+use game_nest::{
+    error::EscrowError,
+    instruction::{self, InitEscData, TakeEscData},
+    state::Reputation,
+};
+use solana_program::{instruction::AccountMeta, program_pack::Pack, pubkey::Pubkey};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::Signer;
+
+const ASSET_ID: u64 = 37;
+const EXPIRES_IN: i64 = 10; // in minutes
+
+async fn reputation_info(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+) -> Reputation {
+    let (reputation_acc, _) = Reputation::find_address(program_id, wallet);
+    let account = context
+        .banks_client
+        .get_account(reputation_acc)
+        .await
+        .unwrap()
+        .unwrap();
+
+    Reputation::unpack(&account.data).unwrap()
+}
+
+// a renter's open rent request for ASSET_ID held by the lender, expiring in EXPIRES_IN minutes
+async fn setup(min_reputation: u64) -> Rental {
+    let mut rental = Rental::start(&[ASSET_ID], None).await;
+    let now = unix_timestamp(&mut rental.context).await;
+
+    rental
+        .init_escrow(InitEscData {
+            expires_at: now + EXPIRES_IN * 60,
+            min_reputation,
+            ..rent_request(ASSET_ID)
+        })
+        .await;
+
+    rental
+}
+
+#[tokio::test]
+async fn returned_rental_is_counted_for_both_parties() {
+    let mut rental = setup(0).await;
+    let program_id = rental.program_id;
+    let renter = rental.renter.pubkey();
+    let lender = rental.lender.insecure_clone();
+
+    create_reputation(&mut rental.context, &program_id, &renter).await;
+    create_reputation(&mut rental.context, &program_id, &lender.pubkey()).await;
+
+    let take = rental.take_ix(TakeEscData::default());
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    warp_minutes(&mut rental.context, RENT_TIME as i64 + 1).await;
+
+    let revert = instruction::with_reputation(rental.revert_ix(), &renter);
+    let revert = instruction::with_reputation(revert, &lender.pubkey());
+    send(&mut rental.context, &[revert], &[]).await.unwrap();
+
+    for wallet in [renter, lender.pubkey()] {
+        let reputation = reputation_info(&mut rental.context, &program_id, &wallet).await;
+        assert_eq!(reputation.completed, 1);
+        assert_eq!(reputation.score(), 1);
+    }
+}
+
+// a rental the renter took from itself before that was rejected,
+// its one Reputation A/c is given for both parties
+#[tokio::test]
+async fn rental_to_oneself_is_counted_once() {
+    let mut rental = setup(0).await;
+    let program_id = rental.program_id;
+    let renter = rental.renter.pubkey();
+    let lender = rental.lender.insecure_clone();
+    let escrow_acc = rental.escrow_acc.pubkey();
+
+    create_reputation(&mut rental.context, &program_id, &renter).await;
+
+    let take = rental.take_ix(TakeEscData::default());
+    send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap();
+
+    edit_escrow(&mut rental.context, &escrow_acc, |escrow| {
+        escrow.taker_main_acc = renter
+    })
+    .await;
+
+    warp_minutes(&mut rental.context, RENT_TIME as i64 + 1).await;
+
+    let mut revert = rental.revert_ix();
+    revert.accounts[4] = AccountMeta::new(renter, false);
+    let revert = instruction::with_reputation(revert, &renter);
+    send(&mut rental.context, &[revert], &[]).await.unwrap();
+
+    let reputation = reputation_info(&mut rental.context, &program_id, &renter).await;
+    assert_eq!(reputation.completed, 1);
+}
+
+#[tokio::test]
+async fn cancel_before_expiry_counts_against_the_initialiser() {
+    let mut rental = setup(0).await;
+    let program_id = rental.program_id;
+    let renter = rental.renter.insecure_clone();
+
+    create_reputation(&mut rental.context, &program_id, &renter.pubkey()).await;
+
+    let cancel = rental.cancel_ix(true);
+    send(&mut rental.context, &[cancel], &[&renter])
+        .await
+        .unwrap();
+
+    let reputation = reputation_info(&mut rental.context, &program_id, &renter.pubkey()).await;
+    assert_eq!(reputation.cancelled, 1);
+    assert!(is_closed(&mut rental.context, &rental.escrow_acc.pubkey()).await);
+}
+
+#[tokio::test]
+async fn cancel_before_expiry_without_the_reputation_fails() {
+    let mut rental = setup(0).await;
+    let program_id = rental.program_id;
+    let renter = rental.renter.insecure_clone();
+
+    create_reputation(&mut rental.context, &program_id, &renter.pubkey()).await;
+
+    let mut cancel = rental.cancel_ix(true);
+    cancel.accounts.pop();
+    let err = send(&mut rental.context, &[cancel], &[&renter])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        custom_error(err),
+        Some(EscrowError::MissingReputation as u32)
+    );
+}
+
+#[tokio::test]
+async fn expired_escrow_is_cancelled_without_a_reputation() {
+    let mut rental = setup(0).await;
+
+    warp_minutes(&mut rental.context, EXPIRES_IN + 1).await;
+
+    let cancel = rental.cancel_ix(false);
+    send(&mut rental.context, &[cancel], &[]).await.unwrap();
+
+    assert!(is_closed(&mut rental.context, &rental.escrow_acc.pubkey()).await);
+}
+
+#[tokio::test]
+async fn take_below_the_min_reputation_fails() {
+    let mut rental = setup(1).await;
+    let program_id = rental.program_id;
+    let lender = rental.lender.insecure_clone();
+
+    create_reputation(&mut rental.context, &program_id, &lender.pubkey()).await;
+
+    let take =
+        instruction::with_reputation(rental.take_ix(TakeEscData::default()), &lender.pubkey());
+    let err = send(&mut rental.context, &[take], &[&lender])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        custom_error(err),
+        Some(EscrowError::ReputationTooLow as u32)
+    );
+}
@@ -0,0 +1,522 @@
+use game_nest::{
+    instruction::{
+        InitAuctionData, InitDutchAuctionData, InitEscData, InstructionType, OfferData,
+        ResolveDisputeData, TakeEscData,
+    },
+    state::{
+        Escrow, GameAcc, History, HistoryEntry, Market, MarketEntry, Offer, Reputation,
+        ACCOUNT_VERSION, DISPUTE_FAULT_LENDER, HISTORY_CAPACITY, MARKET_CAPACITY,
+    },
+};
+use proptest::{array, collection, prelude::*};
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+
+fn pubkey() -> impl Strategy<Value = Pubkey> {
+    any::<[u8; 32]>().prop_map(Pubkey::new_from_array)
+}
+
+fn escrow() -> impl Strategy<Value = Escrow> {
+    (
+        (
+            any::<bool>(),
+            pubkey(),
+            pubkey(),
+            pubkey(),
+            any::<i64>(),
+            any::<u64>(),
+            any::<u64>(),
+            any::<u64>(),
+            array::uniform3(pubkey()),
+            any::<i64>(),
+            0u8..=2,
+        ),
+        (
+            pubkey(),
+            any::<u64>(),
+            any::<i64>(),
+            any::<u64>(),
+            any::<u64>(),
+            any::<i64>(),
+            any::<u64>(),
+            pubkey(),
+            any::<u64>(),
+        ),
+        (pubkey(), any::<i64>(), pubkey(), any::<i64>()),
+    )
+        .prop_map(
+            |(
+                (
+                    is_taken,
+                    initialiser_main_acc,
+                    initialiser_game_acc,
+                    taker_game_acc,
+                    esc_taken_time,
+                    amount,
+                    time,
+                    asset_id,
+                    allowed_takers,
+                    expires_at,
+                    kind,
+                ),
+                (
+                    taker_main_acc,
+                    min_increment,
+                    end_time,
+                    start_price,
+                    end_price,
+                    start_time,
+                    asset_id_max,
+                    market,
+                    min_reputation,
+                ),
+                (arbiter, disputed_at, oracle, delivered_at),
+            )| Escrow {
+                acc_type: 1,
+                is_taken,
+                initialiser_main_acc,
+                initialiser_game_acc,
+                taker_game_acc,
+                esc_taken_time,
+                amount,
+                time,
+                asset_id,
+                allowed_takers,
+                expires_at,
+                kind,
+                taker_main_acc,
+                min_increment,
+                end_time,
+                start_price,
+                end_price,
+                start_time,
+                asset_id_max,
+                market,
+                min_reputation,
+                arbiter,
+                disputed_at,
+                oracle,
+                delivered_at,
+            },
+        )
+}
+
+fn game_acc() -> impl Strategy<Value = GameAcc> {
+    (
+        pubkey(),
+        any::<[u64; 20]>(),
+        any::<[u64; 20]>(),
+        any::<[u64; 20]>(),
+        pubkey(),
+    )
+        .prop_map(
+            |(userspace_owner, owned, rented, lent, locked_by)| GameAcc {
+                acc_type: 2,
+                userspace_owner,
+                owned,
+                rented,
+                lent,
+                locked_by,
+            },
+        )
+}
+
+fn offer() -> impl Strategy<Value = Offer> {
+    (
+        pubkey(),
+        pubkey(),
+        pubkey(),
+        any::<u64>(),
+        any::<u64>(),
+        any::<u64>(),
+    )
+        .prop_map(
+            |(escrow, offerer_main_acc, offerer_game_acc, amount, time, asset_id)| Offer {
+                acc_type: 3,
+                escrow,
+                offerer_main_acc,
+                offerer_game_acc,
+                amount,
+                time,
+                asset_id,
+            },
+        )
+}
+
+fn market() -> impl Strategy<Value = Market> {
+    let entry =
+        (any::<u64>(), any::<u64>(), pubkey()).prop_map(|(price, asset_id, escrow)| MarketEntry {
+            price,
+            asset_id,
+            escrow,
+        });
+
+    (
+        pubkey(),
+        0..=MARKET_CAPACITY as u8,
+        collection::vec(entry, MARKET_CAPACITY),
+        pubkey(),
+        pubkey(),
+    )
+        .prop_map(|(authority, len, entries, arbiter, oracle)| {
+            let mut market = Market {
+                acc_type: 4,
+                authority,
+                len,
+                entries: [MarketEntry::default(); MARKET_CAPACITY],
+                arbiter,
+                oracle,
+            };
+            market.entries.copy_from_slice(&entries);
+            market
+        })
+}
+
+fn history() -> impl Strategy<Value = History> {
+    let entry = (
+        any::<u8>(),
+        pubkey(),
+        pubkey(),
+        any::<u64>(),
+        any::<i64>(),
+        any::<i64>(),
+    )
+        .prop_map(
+            |(kind, from_game_acc, to_game_acc, price, start, end)| HistoryEntry {
+                kind,
+                from_game_acc,
+                to_game_acc,
+                price,
+                start,
+                end,
+            },
+        );
+
+    (
+        any::<u64>(),
+        any::<u8>(),
+        0..HISTORY_CAPACITY as u8,
+        0..=HISTORY_CAPACITY as u8,
+        any::<u64>(),
+        collection::vec(entry, HISTORY_CAPACITY),
+    )
+        .prop_map(|(asset_id, bump, head, len, total, entries)| {
+            let mut history = History {
+                acc_type: 5,
+                asset_id,
+                bump,
+                head,
+                len,
+                total,
+                entries: [HistoryEntry::default(); HISTORY_CAPACITY],
+            };
+            history.entries.copy_from_slice(&entries);
+            history
+        })
+}
+
+fn reputation() -> impl Strategy<Value = Reputation> {
+    (
+        pubkey(),
+        any::<u8>(),
+        any::<u64>(),
+        any::<u64>(),
+        any::<u64>(),
+    )
+        .prop_map(
+            |(wallet, bump, completed, cancelled, disputed)| Reputation {
+                acc_type: 6,
+                wallet,
+                bump,
+                completed,
+                cancelled,
+                disputed,
+            },
+        )
+}
+
+fn init_esc_data() -> impl Strategy<Value = InitEscData> {
+    (
+        any::<u64>(),
+        any::<u64>(),
+        any::<u64>(),
+        array::uniform3(pubkey()),
+        any::<i64>(),
+        any::<u64>(),
+        any::<u64>(),
+    )
+        .prop_map(
+            |(amount, time, asset_id, allowed_takers, expires_at, asset_id_max, min_reputation)| {
+                InitEscData {
+                    amount,
+                    time,
+                    asset_id,
+                    allowed_takers,
+                    expires_at,
+                    asset_id_max,
+                    min_reputation,
+                }
+            },
+        )
+}
+
+fn instruction() -> impl Strategy<Value = InstructionType> {
+    let init_escrow = init_esc_data().prop_map(InstructionType::InitEscrow);
+
+    let init_auction = (
+        any::<u64>(),
+        any::<u64>(),
+        any::<u64>(),
+        any::<u64>(),
+        any::<i64>(),
+        any::<u64>(),
+    )
+        .prop_map(
+            |(reserve_price, min_increment, time, asset_id, end_time, min_reputation)| {
+                InstructionType::InitAuction(InitAuctionData {
+                    reserve_price,
+                    min_increment,
+                    time,
+                    asset_id,
+                    end_time,
+                    min_reputation,
+                })
+            },
+        );
+
+    let init_dutch_auction = (
+        any::<u64>(),
+        any::<u64>(),
+        any::<u64>(),
+        any::<u64>(),
+        any::<i64>(),
+        any::<i64>(),
+        any::<u64>(),
+    )
+        .prop_map(
+            |(start_price, end_price, time, asset_id, start_time, end_time, min_reputation)| {
+                InstructionType::InitDutchAuction(InitDutchAuctionData {
+                    start_price,
+                    end_price,
+                    time,
+                    asset_id,
+                    start_time,
+                    end_time,
+                    min_reputation,
+                })
+            },
+        );
+
+    let make_offer = (any::<u64>(), any::<u64>(), any::<u64>(), pubkey(), pubkey()).prop_map(
+        |(amount, time, asset_id, arbiter, oracle)| {
+            InstructionType::MakeOffer(OfferData {
+                amount,
+                time,
+                asset_id,
+                arbiter,
+                oracle,
+            })
+        },
+    );
+
+    let resolve_dispute = (any::<u64>(), any::<bool>(), 0..=DISPUTE_FAULT_LENDER).prop_map(
+        |(renter_refund, renter_keeps_asset, at_fault)| {
+            InstructionType::ResolveDispute(ResolveDisputeData {
+                renter_refund,
+                renter_keeps_asset,
+                at_fault,
+            })
+        },
+    );
+
+    prop_oneof![
+        init_escrow,
+        (any::<u64>(), any::<u64>(), pubkey(), pubkey()).prop_map(
+            |(asset_id, max_price, arbiter, oracle)| {
+                InstructionType::TakeEscrow(TakeEscData {
+                    asset_id,
+                    max_price,
+                    arbiter,
+                    oracle,
+                })
+            }
+        ),
+        Just(InstructionType::Revert),
+        Just(InstructionType::InitGameAcc),
+        (1..=u64::MAX).prop_map(InstructionType::AddAsset),
+        Just(InstructionType::CancelEscrow),
+        init_auction,
+        any::<u64>().prop_map(InstructionType::PlaceBid),
+        Just(InstructionType::SettleAuction),
+        init_dutch_auction,
+        make_offer,
+        Just(InstructionType::AcceptOffer),
+        Just(InstructionType::InitMarket),
+        any::<u64>().prop_map(InstructionType::AssertOwns),
+        Just(InstructionType::LockForMatch),
+        Just(InstructionType::UnlockAfterMatch),
+        Just(InstructionType::MigrateAccount),
+        any::<u64>().prop_map(InstructionType::InitHistory),
+        Just(InstructionType::InitReputation),
+        Just(InstructionType::RaiseDispute),
+        resolve_dispute,
+        Just(InstructionType::ConfirmDelivery),
+        Just(InstructionType::CloseGameAcc),
+        Just(InstructionType::CancelOffer),
+    ]
+}
+
+fn pack<T: Pack>(account: &T) -> Vec<u8> {
+    let mut data = vec![0; T::LEN];
+    account.pack_into_slice(&mut data);
+    data
+}
+
+proptest! {
+    #[test]
+    fn escrow_round_trips(escrow in escrow()) {
+        prop_assert_eq!(Escrow::unpack(&pack(&escrow))?, escrow);
+    }
+
+    #[test]
+    fn game_acc_round_trips(game_acc in game_acc()) {
+        prop_assert_eq!(GameAcc::unpack(&pack(&game_acc))?, game_acc);
+    }
+
+    #[test]
+    fn offer_round_trips(offer in offer()) {
+        prop_assert_eq!(Offer::unpack(&pack(&offer))?, offer);
+    }
+
+    #[test]
+    fn market_round_trips(market in market()) {
+        prop_assert_eq!(Market::unpack(&pack(&market))?, market);
+    }
+
+    #[test]
+    fn history_round_trips(history in history()) {
+        prop_assert_eq!(History::unpack(&pack(&history))?, history);
+    }
+
+    #[test]
+    fn reputation_round_trips(reputation in reputation()) {
+        prop_assert_eq!(Reputation::unpack(&pack(&reputation))?, reputation);
+    }
+
+    #[test]
+    fn instruction_round_trips(instruction in instruction()) {
+        prop_assert_eq!(InstructionType::unpack(&instruction.pack())?, instruction);
+    }
+
+    // whatever unpacks from arbitrary bytes, without panicking, must survive a repack
+    #[test]
+    fn instruction_unpack_is_stable(data in collection::vec(any::<u8>(), 0..200)) {
+        if let Ok(instruction) = InstructionType::unpack(&data) {
+            prop_assert_eq!(InstructionType::unpack(&instruction.pack())?, instruction);
+        }
+    }
+
+    // older clients send InitEscrow without the fields appended after asset_id
+    #[test]
+    fn init_escrow_fields_left_out_read_as_zero(
+        data in init_esc_data(),
+        cut in prop::sample::select(vec![25, 121, 129, 137]),
+    ) {
+        let packed = InstructionType::InitEscrow(data).pack();
+        let expected = InitEscData {
+            allowed_takers: if cut > 25 { data.allowed_takers } else { Default::default() },
+            expires_at: if cut > 121 { data.expires_at } else { 0 },
+            asset_id_max: if cut > 129 { data.asset_id_max } else { 0 },
+            min_reputation: 0,
+            ..data
+        };
+
+        prop_assert_eq!(
+            InstructionType::unpack(&packed[..cut])?,
+            InstructionType::InitEscrow(expected)
+        );
+        prop_assert!(InstructionType::unpack(&packed[..(cut - 1)]).is_err());
+    }
+
+    // TakeEscrow was sent with no data or only the fields before the later ones were appended
+    #[test]
+    fn take_escrow_fields_left_out_read_as_zero(
+        asset_id in any::<u64>(),
+        max_price in any::<u64>(),
+        arbiter in pubkey(),
+        oracle in pubkey(),
+        cut in prop::sample::select(vec![1, 9, 17, 49, 81]),
+    ) {
+        let data = TakeEscData { asset_id, max_price, arbiter, oracle };
+        let packed = InstructionType::TakeEscrow(data).pack();
+        let expected = TakeEscData {
+            asset_id: if cut > 1 { asset_id } else { 0 },
+            max_price: if cut > 9 { max_price } else { 0 },
+            arbiter: if cut > 17 { arbiter } else { Pubkey::default() },
+            oracle: if cut > 49 { oracle } else { Pubkey::default() },
+        };
+
+        prop_assert_eq!(
+            InstructionType::unpack(&packed[..cut])?,
+            InstructionType::TakeEscrow(expected)
+        );
+        if cut > 1 {
+            prop_assert!(InstructionType::unpack(&packed[..(cut - 1)]).is_err());
+        }
+    }
+
+    // MakeOffer was sent without the arbiter and oracle before they were appended
+    #[test]
+    fn make_offer_fields_left_out_read_as_zero(
+        amount in any::<u64>(),
+        time in any::<u64>(),
+        asset_id in any::<u64>(),
+        arbiter in pubkey(),
+        oracle in pubkey(),
+        cut in prop::sample::select(vec![25, 57]),
+    ) {
+        let data = OfferData { amount, time, asset_id, arbiter, oracle };
+        let packed = InstructionType::MakeOffer(data).pack();
+        let expected = OfferData {
+            arbiter: if cut > 25 { arbiter } else { Pubkey::default() },
+            oracle: Pubkey::default(),
+            ..data
+        };
+
+        prop_assert_eq!(
+            InstructionType::unpack(&packed[..cut])?,
+            InstructionType::MakeOffer(expected)
+        );
+        prop_assert!(InstructionType::unpack(&packed[..(cut - 1)]).is_err());
+    }
+
+    #[test]
+    fn escrow_header_is_checked(escrow in escrow(), acc_type in 2u8.., version in any::<u8>()) {
+        let mut data = pack(&escrow);
+        data[0] = acc_type;
+        prop_assert!(Escrow::unpack_from_slice(&data).is_err());
+
+        data[0] = 1;
+        data[1] = version;
+        prop_assert_eq!(Escrow::unpack_from_slice(&data).is_ok(), version == ACCOUNT_VERSION);
+    }
+
+    // unpacking untrusted account data must fail cleanly and never panic, whatever its length
+    #[test]
+    fn unpack_never_panics(data in collection::vec(any::<u8>(), 0..History::LEN + 8)) {
+        let _ = Escrow::unpack_from_slice(&data);
+        let _ = GameAcc::unpack_from_slice(&data);
+        let _ = Offer::unpack_from_slice(&data);
+        let _ = Market::unpack_from_slice(&data);
+        let _ = History::unpack_from_slice(&data);
+        let _ = Reputation::unpack_from_slice(&data);
+        let _ = Escrow::unpack_unchecked(&data);
+        let _ = GameAcc::unpack_unchecked(&data);
+    }
+
+    #[test]
+    fn truncated_accounts_are_rejected(game_acc in game_acc(), len in 0..GameAcc::LEN) {
+        let data = pack(&game_acc);
+        prop_assert!(GameAcc::unpack_from_slice(&data[..len]).is_err());
+        prop_assert!(GameAcc::unpack_unchecked(&data[..len]).is_err());
+    }
+}